@@ -3,6 +3,7 @@ use std::fmt;
 use std::result;
 
 use super::ErrorClone;
+use super::Location;
 use super::Trace;
 
 /// Convenience type alias for Liquid compiler errors
@@ -23,6 +24,7 @@ pub struct Error {
 #[derive(Debug, Clone)]
 struct InnerError {
     msg: crate::model::KString,
+    location: Option<Location>,
     user_backtrace: Vec<Trace>,
     cause: Option<BoxedError>,
 }
@@ -36,6 +38,7 @@ impl Error {
     fn with_msg_cow(msg: crate::model::KString) -> Self {
         let error = InnerError {
             msg,
+            location: None,
             user_backtrace: vec![Trace::empty()],
             cause: None,
         };
@@ -82,6 +85,32 @@ impl Error {
         self
     }
 
+    /// Record where in the template source the error occurred.
+    ///
+    /// The innermost location is the most precise, so this is ignored if a location was already
+    /// recorded.
+    pub fn location(mut self, location: Location) -> Self {
+        if self.inner.location.is_none() {
+            self.inner.location = Some(location);
+        }
+        self
+    }
+
+    /// Name the template the error occurred in, if not already known.
+    pub fn template_name<S: Into<crate::model::KString>>(mut self, name: S) -> Self {
+        if let Some(location) = self.inner.location.as_mut() {
+            if location.template().is_none() {
+                location.set_template(name.into());
+            }
+        }
+        self
+    }
+
+    /// Where in the template source the error occurred, if known.
+    pub fn get_location(&self) -> Option<&Location> {
+        self.inner.location.as_ref()
+    }
+
     /// Add an external cause to the error for debugging purposes.
     pub fn cause<E: ErrorClone>(self, cause: E) -> Self {
         let cause = Box::new(cause);
@@ -109,6 +138,9 @@ const ERROR_DESCRIPTION: &str = "liquid";
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}: {}", ERROR_DESCRIPTION, self.inner.msg)?;
+        if let Some(location) = &self.inner.location {
+            writeln!(f, "  at: {}", location)?;
+        }
         for trace in &self.inner.user_backtrace {
             if let Some(trace) = trace.get_trace() {
                 writeln!(f, "from: {}", trace)?;
@@ -0,0 +1,57 @@
+use std::fmt;
+
+/// Where in the template source an error occurred.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Location {
+    template: Option<crate::model::KString>,
+    line: usize,
+    column: usize,
+}
+
+impl Location {
+    /// Create a location within an unnamed template.
+    ///
+    /// Both `line` and `column` start at 1.
+    pub fn new(line: usize, column: usize) -> Self {
+        Self {
+            template: None,
+            line,
+            column,
+        }
+    }
+
+    /// Name the template (or partial-template) the location is within.
+    pub fn with_template<S: Into<crate::model::KString>>(mut self, template: S) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    pub(crate) fn set_template(&mut self, template: crate::model::KString) {
+        self.template = Some(template);
+    }
+
+    /// Name of the template (or partial-template), if known.
+    pub fn template(&self) -> Option<&str> {
+        self.template.as_deref()
+    }
+
+    /// Line number, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column number, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)?;
+        if let Some(template) = &self.template {
+            write!(f, " of {template}")?;
+        }
+        Ok(())
+    }
+}
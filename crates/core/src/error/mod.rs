@@ -6,10 +6,12 @@
 
 mod clone;
 mod error;
+mod location;
 mod result_ext;
 mod trace;
 
 pub use clone::*;
 pub use error::*;
+pub use location::*;
 pub use result_ext::*;
 use trace::*;
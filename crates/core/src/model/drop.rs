@@ -0,0 +1,92 @@
+//! Objects whose keys are computed on lookup, like Ruby's `Liquid::Drop`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync;
+
+use crate::error::Result;
+use crate::runtime::Runtime;
+
+use super::KString;
+use super::Value;
+use super::ValueView;
+
+/// An object whose keys are looked up by calling Rust code, like Ruby's `Liquid::Drop`.
+///
+/// A method only runs when a template reads its key, so drops are a cheap way to offer data that
+/// is expensive to compute.  Implement it with `#[derive(DropView)]` or, for keys only known at
+/// runtime, by hand.
+///
+/// To be stored in a [`Value`], see [`Value::from_drop`], the drop also has to be `Send + Sync`.
+pub trait DropView: ValueView {
+    /// Cast to `ValueView`
+    fn as_value(&self) -> &dyn ValueView;
+
+    /// Call the method named `name`, `None` if the drop has no such method.
+    fn invoke(&self, name: &str, runtime: &dyn Runtime) -> Result<Option<Value>>;
+
+    /// Answer `name` when the drop has no such method, like Ruby's `liquid_method_missing`.
+    ///
+    /// Answers nothing by default.
+    fn liquid_method_missing(&self, name: &str, runtime: &dyn Runtime) -> Result<Option<Value>> {
+        let _ = (name, runtime);
+        Ok(None)
+    }
+
+    /// Look up `name`, falling back to [`DropView::liquid_method_missing`].
+    fn lookup(&self, name: &str, runtime: &dyn Runtime) -> Result<Option<Value>> {
+        match self.invoke(name, runtime)? {
+            Some(value) => Ok(Some(value)),
+            None => self.liquid_method_missing(name, runtime),
+        }
+    }
+}
+
+/// A [`DropView`] shared as a [`Value`].
+pub type DropRef = sync::Arc<dyn DropView + Send + Sync>;
+
+/// Results of a drop's methods, kept so each method runs at most once.
+///
+/// Clones share their results, as do the clones of the drop holding it.
+#[derive(Clone, Default)]
+pub struct DropCache {
+    values: sync::Arc<sync::Mutex<HashMap<KString, Value>>>,
+}
+
+impl DropCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The result of the method named `name`, calling `method` if there is none yet.
+    pub fn get_or_try_insert_with<F>(&self, name: &str, method: F) -> Result<Value>
+    where
+        F: FnOnce() -> Result<Value>,
+    {
+        if let Some(value) = self.lock().get(name) {
+            return Ok(value.clone());
+        }
+        // Not locked while running, as `method` may look up other cached keys.
+        let value = method()?;
+        self.lock()
+            .entry(KString::from_ref(name))
+            .or_insert(value.clone());
+        Ok(value)
+    }
+
+    /// Forget all results, for the methods to run again.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> sync::MutexGuard<'_, HashMap<KString, Value>> {
+        self.values.lock().expect("not to be poisoned and reused")
+    }
+}
+
+impl fmt::Debug for DropCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.lock().keys()).finish()
+    }
+}
@@ -5,6 +5,7 @@ use std::slice;
 
 use crate::error::{Error, Result};
 use crate::model::KStringCow;
+use crate::runtime::Runtime;
 
 use super::ScalarCow;
 use super::Value;
@@ -117,49 +118,85 @@ impl<'i, 's: 'i> ExactSizeIterator for PathIter<'i, 's> {
 }
 
 /// Find a `ValueView` nested in an `ObjectView`
+///
+/// Drops are skipped, see [`try_find_with`] for looking into them.
 pub fn try_find<'o>(value: &'o dyn ValueView, path: &[ScalarCow<'_>]) -> Option<ValueCow<'o>> {
-    let indexes = path.iter();
-    try_find_borrowed(value, indexes)
+    try_find_borrowed(value, path.iter(), None).ok().flatten()
+}
+
+/// Find a `ValueView` nested in an `ObjectView`, calling the drops on the way with `runtime`.
+pub fn try_find_with<'o>(
+    value: &'o dyn ValueView,
+    path: &[ScalarCow<'_>],
+    runtime: &dyn Runtime,
+) -> Option<ValueCow<'o>> {
+    try_find_borrowed(value, path.iter(), Some(runtime))
+        .ok()
+        .flatten()
 }
 
 fn try_find_borrowed<'o, 'i>(
     value: &'o dyn ValueView,
     mut path: impl Iterator<Item = &'i ScalarCow<'i>>,
-) -> Option<ValueCow<'o>> {
+    runtime: Option<&dyn Runtime>,
+) -> Result<Option<ValueCow<'o>>> {
+    if let Some(value) = for_render(value, runtime) {
+        return try_find_owned(value, path, runtime);
+    }
     let index = match path.next() {
         Some(index) => index,
         None => {
-            return Some(ValueCow::Borrowed(value));
+            return Ok(Some(ValueCow::Borrowed(value)));
         }
     };
-    let child = augmented_get(value, index)?;
+    let Some(child) = augmented_get(value, index, runtime)? else {
+        return Ok(None);
+    };
     match child {
-        ValueCow::Owned(child) => try_find_owned(child, path),
-        ValueCow::Borrowed(child) => try_find_borrowed(child, path),
+        ValueCow::Owned(child) => try_find_owned(child, path, runtime),
+        ValueCow::Borrowed(child) => try_find_borrowed(child, path, runtime),
     }
 }
 
 fn try_find_owned<'o, 'i>(
     value: Value,
     mut path: impl Iterator<Item = &'i ScalarCow<'i>>,
-) -> Option<ValueCow<'o>> {
+    runtime: Option<&dyn Runtime>,
+) -> Result<Option<ValueCow<'o>>> {
+    let value = for_render(&value, runtime).unwrap_or(value);
     let index = match path.next() {
         Some(index) => index,
         None => {
-            return Some(ValueCow::Owned(value));
+            return Ok(Some(ValueCow::Owned(value)));
         }
     };
-    let child = augmented_get(&value, index)?;
+    let Some(child) = augmented_get(&value, index, runtime)? else {
+        return Ok(None);
+    };
     match child {
-        ValueCow::Owned(child) => try_find_owned(child, path),
+        ValueCow::Owned(child) => try_find_owned(child, path, runtime),
         ValueCow::Borrowed(child) => {
-            try_find_borrowed(child, path).map(|v| ValueCow::Owned(v.into_owned()))
+            Ok(try_find_borrowed(child, path, runtime)?.map(|v| ValueCow::Owned(v.into_owned())))
         }
     }
 }
 
-fn augmented_get<'o>(value: &'o dyn ValueView, index: &ScalarCow<'_>) -> Option<ValueCow<'o>> {
-    if let Some(arr) = value.as_array() {
+/// The lazy `value` as computed for the render of `runtime`, see `LazyValue::for_render`.
+fn for_render(value: &dyn ValueView, runtime: Option<&dyn Runtime>) -> Option<Value> {
+    let lazy = value.as_lazy()?.for_render(runtime?)?;
+    Some(Value::Lazy(lazy))
+}
+
+fn augmented_get<'o>(
+    value: &'o dyn ValueView,
+    index: &ScalarCow<'_>,
+    runtime: Option<&dyn Runtime>,
+) -> Result<Option<ValueCow<'o>>> {
+    if let (Some(lazy), Some(_)) = (value.as_lazy(), runtime) {
+        // Rather than not finding anything within a value that wasn't awaited.
+        lazy.try_get()?;
+    }
+    let child = if let Some(arr) = value.as_array() {
         if let Some(index) = index.to_integer() {
             arr.get(index).map(ValueCow::Borrowed)
         } else {
@@ -178,6 +215,13 @@ fn augmented_get<'o>(value: &'o dyn ValueView, index: &ScalarCow<'_>) -> Option<
                 "size" => Some(ValueCow::Owned(Value::scalar(obj.size()))),
                 _ => None,
             })
+    } else if let Some(drop) = value.as_drop() {
+        match runtime {
+            Some(runtime) => drop
+                .lookup(index.to_kstr().as_str(), runtime)?
+                .map(ValueCow::Owned),
+            None => None,
+        }
     } else if let Some(scalar) = value.as_scalar() {
         let index = index.to_kstr();
         match index.as_str() {
@@ -188,18 +232,40 @@ fn augmented_get<'o>(value: &'o dyn ValueView, index: &ScalarCow<'_>) -> Option<
         }
     } else {
         None
-    }
+    };
+    Ok(child)
 }
 
 /// Find a `ValueView` nested in an `ObjectView`
+///
+/// Drops are skipped, see [`find_with`] for looking into them.
 pub fn find<'o>(value: &'o dyn ValueView, path: &[ScalarCow<'_>]) -> Result<ValueCow<'o>> {
-    if let Some(res) = try_find(value, path) {
+    find_in(value, path, None)
+}
+
+/// Find a `ValueView` nested in an `ObjectView`, calling the drops on the way with `runtime`.
+///
+/// Unlike [`try_find_with`], errors of the drops are reported.
+pub fn find_with<'o>(
+    value: &'o dyn ValueView,
+    path: &[ScalarCow<'_>],
+    runtime: &dyn Runtime,
+) -> Result<ValueCow<'o>> {
+    find_in(value, path, Some(runtime))
+}
+
+fn find_in<'o>(
+    value: &'o dyn ValueView,
+    path: &[ScalarCow<'_>],
+    runtime: Option<&dyn Runtime>,
+) -> Result<ValueCow<'o>> {
+    if let Some(res) = try_find_borrowed(value, path.iter(), runtime)? {
         Ok(res)
     } else {
         for cur_idx in 1..path.len() {
             let subpath_end = path.len() - cur_idx;
             let subpath = &path[0..subpath_end];
-            if let Some(parent) = try_find(value, subpath) {
+            if let Some(parent) = try_find_borrowed(value, subpath.iter(), runtime)? {
                 let subpath = itertools::join(subpath.iter().map(ValueView::render), ".");
                 let requested = &path[subpath_end];
                 let available = if let Some(arr) = parent.as_array() {
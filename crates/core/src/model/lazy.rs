@@ -0,0 +1,327 @@
+//! Values computed on first access, like procs in Ruby's assigns.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync;
+use std::task::{Context, Poll, Waker};
+
+use crate::error::{Error, Result};
+use crate::runtime::Runtime;
+
+use super::DisplayCow;
+use super::KStringCow;
+use super::State;
+use super::Value;
+use super::ValueView;
+use super::{ArrayView, DropView, ObjectView, ScalarCow};
+
+type InitFn = sync::Arc<dyn Fn() -> Value + Send + Sync>;
+type InitFuture = Pin<Box<dyn Future<Output = Value> + Send>>;
+type MakeFuture = sync::Arc<dyn Fn() -> InitFuture + Send + Sync>;
+
+/// A value computed the first time a template reads it, like a proc in Ruby's assigns.
+///
+/// Each render computes the value again, the first time it reads it, and remembers it until the
+/// render finishes.  Read outside of a render, like when serialized, the result is remembered by
+/// the `LazyValue`, and shared with its clones and the copies made with `to_value`.
+///
+/// ```rust
+/// let mut globals = liquid_core::Object::new();
+/// globals.insert(
+///     "inventory".into(),
+///     liquid_core::Value::lazy(|| liquid_core::Value::scalar(42)),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct LazyValue {
+    inner: sync::Arc<Inner>,
+}
+
+struct Inner {
+    init: Init,
+    value: sync::OnceLock<Value>,
+    pending: sync::Mutex<Pending>,
+    /// Computed for a render, see `LazyValue::for_render`.
+    rendering: bool,
+}
+
+#[derive(Clone)]
+enum Init {
+    Fn(InitFn),
+    Future(MakeFuture),
+}
+
+/// The future being awaited, by the caller polling it and the others waiting for it.
+#[derive(Default)]
+struct Pending {
+    future: Option<InitFuture>,
+    polling: bool,
+    wakers: Vec<Waker>,
+}
+
+impl LazyValue {
+    /// Create a value computed by `init` on first access.
+    pub fn new<F, V>(init: F) -> Self
+    where
+        F: Fn() -> V + Send + Sync + 'static,
+        V: ValueView,
+    {
+        let init: InitFn = sync::Arc::new(move || init().to_value());
+        Self::with_init(Init::Fn(init), false)
+    }
+
+    /// Create a value computed by awaiting the future made by `init` on first access.
+    ///
+    /// Asynchronous rendering awaits it, see `Renderable::render_to_async`.  Other renders fail to
+    /// read it, and reading it some other way before [`LazyValue::resolve`] finds `nil`, rather
+    /// than blocking the thread until it is ready.
+    pub fn from_future<F, Fut, V>(init: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = V> + Send + 'static,
+        V: ValueView,
+    {
+        let init: MakeFuture = sync::Arc::new(move || {
+            let future = init();
+            Box::pin(async move { future.await.to_value() })
+        });
+        Self::with_init(Init::Future(init), false)
+    }
+
+    fn with_init(init: Init, rendering: bool) -> Self {
+        Self {
+            inner: sync::Arc::new(Inner {
+                init,
+                value: sync::OnceLock::new(),
+                pending: Default::default(),
+                rendering,
+            }),
+        }
+    }
+
+    /// The value, computing it if this is the first access.
+    ///
+    /// A value awaited from a future is `nil` until [`LazyValue::resolve`] finished.
+    pub fn get(&self) -> &Value {
+        static NIL: Value = Value::Nil;
+        self.try_get().unwrap_or(&NIL)
+    }
+
+    /// The value, computing it if this is the first access, or an error if it has to be awaited
+    /// with [`LazyValue::resolve`] first.
+    pub fn try_get(&self) -> Result<&Value> {
+        if let Some(value) = self.inner.value.get() {
+            return Ok(value);
+        }
+        match &self.inner.init {
+            Init::Fn(init) => {
+                let value = init();
+                Ok(self.inner.value.get_or_init(|| value))
+            }
+            Init::Future(_) => Error::with_msg("Value has to be awaited")
+                .context("cause", "render asynchronously, like with `render_async`")
+                .into_err(),
+        }
+    }
+
+    /// The value, computing or awaiting it if this is the first access.
+    pub async fn resolve(&self) -> &Value {
+        std::future::poll_fn(|cx| self.poll_resolve(cx)).await
+    }
+
+    /// Whether the value was computed yet.
+    pub fn is_computed(&self) -> bool {
+        self.inner.value.get().is_some()
+    }
+
+    /// The value as computed for the render of `runtime`, unless this already is.
+    ///
+    /// The render remembers it, so it is only computed once per render.
+    pub(crate) fn for_render(&self, runtime: &dyn Runtime) -> Option<LazyValue> {
+        if self.inner.rendering {
+            return None;
+        }
+        let mut register = runtime.shared_registers().get_mut::<LazyRegister>();
+        // Keyed by the value shared by the clones, which the register keeps alive.
+        let key = sync::Arc::as_ptr(&self.inner) as usize;
+        let (_, value) = register.values.entry(key).or_insert_with(|| {
+            let value = Self::with_init(self.inner.init.clone(), true);
+            (self.clone(), value)
+        });
+        Some(value.clone())
+    }
+
+    fn poll_resolve(&self, cx: &mut Context<'_>) -> Poll<&Value> {
+        if let Some(value) = self.inner.value.get() {
+            return Poll::Ready(value);
+        }
+        let init = match &self.inner.init {
+            Init::Fn(_) => return Poll::Ready(self.get()),
+            Init::Future(init) => init,
+        };
+        let mut future = {
+            let mut pending = self.pending();
+            // Another caller may have finished while we waited for the lock.
+            if let Some(value) = self.inner.value.get() {
+                return Poll::Ready(value);
+            }
+            if pending.polling {
+                // Futures only wake the last caller to poll them, so the others are woken once
+                // it is ready.
+                if !pending
+                    .wakers
+                    .iter()
+                    .any(|waker| waker.will_wake(cx.waker()))
+                {
+                    pending.wakers.push(cx.waker().clone());
+                }
+                return Poll::Pending;
+            }
+            pending.polling = true;
+            pending.future.take().unwrap_or_else(|| init())
+        };
+        // Polled without holding the lock, as the future may read other lazy values.
+        let poll = future.as_mut().poll(cx);
+        let mut pending = self.pending();
+        pending.polling = false;
+        match poll {
+            Poll::Ready(value) => {
+                for waker in pending.wakers.drain(..) {
+                    waker.wake();
+                }
+                Poll::Ready(self.inner.value.get_or_init(|| value))
+            }
+            Poll::Pending => {
+                pending.future = Some(future);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn pending(&self) -> sync::MutexGuard<'_, Pending> {
+        self.inner
+            .pending
+            .lock()
+            .expect("not to be poisoned and reused")
+    }
+}
+
+/// The lazy values computed by a render, by the value the clones of the original share.
+#[derive(Default)]
+struct LazyRegister {
+    values: HashMap<usize, (LazyValue, LazyValue)>,
+}
+
+impl fmt::Debug for LazyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner.value.get() {
+            Some(value) => f.debug_tuple("LazyValue").field(value).finish(),
+            None => f.write_str("LazyValue(<not computed>)"),
+        }
+    }
+}
+
+impl ValueView for LazyValue {
+    fn as_debug(&self) -> &dyn fmt::Debug {
+        self
+    }
+
+    fn render(&self) -> DisplayCow<'_> {
+        self.get().render()
+    }
+    fn source(&self) -> DisplayCow<'_> {
+        self.get().source()
+    }
+    fn type_name(&self) -> &'static str {
+        self.get().type_name()
+    }
+    fn query_state(&self, state: State) -> bool {
+        self.get().query_state(state)
+    }
+
+    fn to_kstr(&self) -> KStringCow<'_> {
+        self.get().to_kstr()
+    }
+    fn to_value(&self) -> Value {
+        Value::Lazy(self.clone())
+    }
+
+    fn as_scalar(&self) -> Option<ScalarCow<'_>> {
+        self.get().as_scalar()
+    }
+    fn as_array(&self) -> Option<&dyn ArrayView> {
+        self.get().as_array()
+    }
+    fn as_object(&self) -> Option<&dyn ObjectView> {
+        self.get().as_object()
+    }
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        self.get().as_drop()
+    }
+    fn as_lazy(&self) -> Option<&LazyValue> {
+        Some(self)
+    }
+    fn as_state(&self) -> Option<State> {
+        self.get().as_state()
+    }
+    fn is_nil(&self) -> bool {
+        self.get().is_nil()
+    }
+}
+
+impl serde::Serialize for LazyValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_computed_once() {
+        let calls = sync::Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let uut = LazyValue::new(move || counter.fetch_add(1, Ordering::SeqCst) as i64 + 1);
+        let clone = uut.clone();
+        assert!(!uut.is_computed());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        assert_eq!(uut.render().to_string(), "1");
+        assert_eq!(clone.render().to_string(), "1");
+        assert_eq!(uut.to_value(), 1);
+        assert!(uut.to_value().is_lazy());
+        assert!(clone.is_computed());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_from_future() {
+        let uut = LazyValue::from_future(|| async { "ready" });
+        assert!(!uut.is_computed());
+        assert_eq!(futures_executor::block_on(uut.resolve()), "ready");
+        assert!(uut.is_computed());
+
+        let uut = LazyValue::from_future(|| async { "ready" });
+        uut.try_get().unwrap_err();
+        assert!(uut.render().to_string().is_empty());
+        assert!(!uut.is_computed());
+    }
+
+    #[test]
+    fn test_find() {
+        let mut globals = crate::Object::new();
+        globals.insert(
+            "product".into(),
+            Value::lazy(|| crate::value!({"title": "Shirt"})),
+        );
+        let path = [ScalarCow::new("product"), ScalarCow::new("title")];
+        let title = crate::model::try_find(&globals, &path).unwrap();
+        assert_eq!(title.render().to_string(), "Shirt");
+    }
+}
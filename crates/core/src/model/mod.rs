@@ -5,7 +5,9 @@
 #![warn(unused_extern_crates)]
 
 mod array;
+mod drop;
 mod find;
+mod lazy;
 mod object;
 mod scalar;
 mod value;
@@ -13,7 +15,9 @@ mod value;
 mod ser;
 
 pub use array::*;
+pub use drop::*;
 pub use find::*;
+pub use lazy::*;
 pub use object::*;
 pub use scalar::*;
 pub use value::*;
@@ -9,6 +9,8 @@ use super::{ValueView, ValueViewCmp};
 use crate::model::array::{Array, ArrayView};
 use crate::model::object::{Object, ObjectView};
 use crate::model::scalar::{Scalar, ScalarCow};
+use crate::model::DropView;
+use crate::model::LazyValue;
 
 /// Abstract the lifetime of a `Value`.
 #[derive(Clone, Debug)]
@@ -76,6 +78,14 @@ impl ValueView for ValueCow<'_> {
         self.as_view().as_object()
     }
 
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        self.as_view().as_drop()
+    }
+
+    fn as_lazy(&self) -> Option<&LazyValue> {
+        self.as_view().as_lazy()
+    }
+
     fn as_state(&self) -> Option<State> {
         self.as_view().as_state()
     }
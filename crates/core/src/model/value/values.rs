@@ -11,6 +11,7 @@ use super::{ValueView, ValueViewCmp};
 use crate::model::array::{Array, ArrayView};
 use crate::model::object::{Object, ObjectView};
 use crate::model::scalar::{Scalar, ScalarCow};
+use crate::model::{DropRef, DropView, LazyValue};
 
 /// An enum to represent different value types
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -25,6 +26,16 @@ pub enum Value {
     Object(Object),
     /// Query symbol.
     State(State),
+    /// An object whose keys are computed on lookup.
+    ///
+    /// Serialized as it renders, never deserialized.
+    #[serde(serialize_with = "serialize_drop", skip_deserializing)]
+    Drop(DropRef),
+    /// A value computed on first access.
+    ///
+    /// Serialized as the computed value, never deserialized.
+    #[serde(skip_deserializing)]
+    Lazy(LazyValue),
     /// Nothing.
     #[default]
     Nil,
@@ -42,6 +53,20 @@ impl Value {
         Value::Array(v)
     }
 
+    /// Create as a `Drop`.
+    pub fn from_drop<D: DropView + Send + Sync + 'static>(drop: D) -> Self {
+        Value::Drop(std::sync::Arc::new(drop))
+    }
+
+    /// Create as a `Lazy`, computed by `init` on first access.
+    pub fn lazy<F, V>(init: F) -> Self
+    where
+        F: Fn() -> V + Send + Sync + 'static,
+        V: ValueView,
+    {
+        Value::Lazy(LazyValue::new(init))
+    }
+
     /// Performs the conversion.
     pub fn as_view(&self) -> &dyn ValueView {
         match &self {
@@ -49,6 +74,8 @@ impl Value {
             Value::Object(ref x) => x,
             Value::Array(ref x) => x,
             Value::State(ref x) => x,
+            Value::Drop(ref x) => x.as_value(),
+            Value::Lazy(ref x) => x,
             Value::Nil => self,
         }
     }
@@ -57,6 +84,7 @@ impl Value {
     pub fn into_scalar(self) -> Option<Scalar> {
         match self {
             Value::Scalar(s) => Some(s),
+            Value::Lazy(x) => x.get().clone().into_scalar(),
             _ => None,
         }
     }
@@ -65,6 +93,7 @@ impl Value {
     pub fn into_array(self) -> Option<Array> {
         match self {
             Value::Array(s) => Some(s),
+            Value::Lazy(x) => x.get().clone().into_array(),
             _ => None,
         }
     }
@@ -81,6 +110,7 @@ impl Value {
     pub fn into_object(self) -> Option<Object> {
         match self {
             Value::Object(s) => Some(s),
+            Value::Lazy(x) => x.get().clone().into_object(),
             _ => None,
         }
     }
@@ -97,6 +127,7 @@ impl Value {
     pub fn into_state(self) -> Option<State> {
         match self {
             Value::State(s) => Some(s),
+            Value::Lazy(x) => x.get().clone().into_state(),
             _ => None,
         }
     }
@@ -113,6 +144,8 @@ impl ValueView for Value {
             Value::Array(ref x) => x.render(),
             Value::Object(ref x) => x.render(),
             Value::State(ref x) => x.render(),
+            Value::Drop(ref x) => x.render(),
+            Value::Lazy(ref x) => x.render(),
             Value::Nil => DisplayCow::Borrowed(&""),
         }
     }
@@ -122,6 +155,8 @@ impl ValueView for Value {
             Value::Array(ref x) => x.source(),
             Value::Object(ref x) => x.source(),
             Value::State(ref x) => x.source(),
+            Value::Drop(ref x) => x.source(),
+            Value::Lazy(ref x) => x.source(),
             Value::Nil => DisplayCow::Owned(Box::new(super::StrDisplay {
                 s: self.type_name(),
             })),
@@ -133,6 +168,8 @@ impl ValueView for Value {
             Value::Array(ref x) => x.type_name(),
             Value::Object(ref x) => x.type_name(),
             Value::State(ref x) => x.type_name(),
+            Value::Drop(ref x) => x.type_name(),
+            Value::Lazy(ref x) => x.type_name(),
             Value::Nil => "nil",
         }
     }
@@ -142,6 +179,8 @@ impl ValueView for Value {
             Value::Array(ref x) => x.query_state(state),
             Value::Object(ref x) => x.query_state(state),
             Value::State(ref x) => x.query_state(state),
+            Value::Drop(ref x) => x.query_state(state),
+            Value::Lazy(ref x) => x.query_state(state),
             Value::Nil => match state {
                 State::Truthy => false,
                 State::DefaultValue => true,
@@ -157,6 +196,8 @@ impl ValueView for Value {
             Value::Array(ref x) => x.to_kstr(),
             Value::Object(ref x) => x.to_kstr(),
             Value::State(ref x) => x.to_kstr(),
+            Value::Drop(ref x) => x.to_kstr(),
+            Value::Lazy(ref x) => x.to_kstr(),
             Value::Nil => KStringCow::from_static(""),
         }
     }
@@ -170,6 +211,8 @@ impl ValueView for Value {
             Value::Array(ref x) => Value::Array(x.clone()),
             Value::Object(ref x) => Value::Object(x.clone()),
             Value::State(ref x) => Value::State(*x),
+            Value::Drop(ref x) => Value::Drop(x.clone()),
+            Value::Lazy(ref x) => Value::Lazy(x.clone()),
             Value::Nil => Value::Nil,
         }
     }
@@ -177,32 +220,59 @@ impl ValueView for Value {
     fn as_scalar(&self) -> Option<ScalarCow<'_>> {
         match self {
             Value::Scalar(s) => Some(s.as_ref()),
+            Value::Lazy(x) => x.as_scalar(),
             _ => None,
         }
     }
     fn as_array(&self) -> Option<&dyn ArrayView> {
         match self {
+            Value::Lazy(ref x) => x.as_array(),
             Value::Array(ref s) => Some(s),
             _ => None,
         }
     }
     fn as_object(&self) -> Option<&dyn ObjectView> {
         match self {
+            Value::Lazy(ref x) => x.as_object(),
             Value::Object(ref s) => Some(s),
             _ => None,
         }
     }
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        match self {
+            Value::Lazy(ref x) => x.as_drop(),
+            Value::Drop(ref s) => Some(&**s),
+            _ => None,
+        }
+    }
+    fn as_lazy(&self) -> Option<&LazyValue> {
+        match self {
+            Value::Lazy(ref s) => Some(s),
+            _ => None,
+        }
+    }
     fn as_state(&self) -> Option<State> {
         match self {
             Value::State(s) => Some(*s),
+            Value::Lazy(x) => x.as_state(),
             _ => None,
         }
     }
     fn is_nil(&self) -> bool {
-        matches!(self, Value::Nil)
+        match self {
+            Value::Lazy(x) => x.is_nil(),
+            _ => matches!(self, Value::Nil),
+        }
     }
 }
 
+fn serialize_drop<S: serde::Serializer>(
+    drop: &DropRef,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_str(drop.to_kstr().as_str())
+}
+
 impl From<Scalar> for Value {
     fn from(other: Scalar) -> Self {
         Value::Scalar(other)
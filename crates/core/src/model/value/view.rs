@@ -7,6 +7,8 @@ use super::DisplayCow;
 use super::State;
 use super::Value;
 use crate::model::ArrayView;
+use crate::model::DropView;
+use crate::model::LazyValue;
 use crate::model::ObjectView;
 use crate::model::ScalarCow;
 
@@ -56,6 +58,24 @@ pub trait ValueView: fmt::Debug {
         self.as_object().is_some()
     }
 
+    /// Extracts the drop if it is one.
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        None
+    }
+    /// Tests whether this value is a drop
+    fn is_drop(&self) -> bool {
+        self.as_drop().is_some()
+    }
+
+    /// Extracts the lazy value if it is one, without computing it.
+    fn as_lazy(&self) -> Option<&LazyValue> {
+        None
+    }
+    /// Tests whether this value is lazy
+    fn is_lazy(&self) -> bool {
+        self.as_lazy().is_some()
+    }
+
     /// Extracts the state if it is one
     fn as_state(&self) -> Option<State> {
         None
@@ -110,6 +130,14 @@ impl<V: ValueView + ?Sized> ValueView for &V {
         <V as ValueView>::as_object(self)
     }
 
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        <V as ValueView>::as_drop(self)
+    }
+
+    fn as_lazy(&self) -> Option<&LazyValue> {
+        <V as ValueView>::as_lazy(self)
+    }
+
     fn as_state(&self) -> Option<State> {
         <V as ValueView>::as_state(self)
     }
@@ -158,6 +186,14 @@ impl<T: ValueView> ValueView for Option<T> {
         forward(self).as_object()
     }
 
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        forward(self).as_drop()
+    }
+
+    fn as_lazy(&self) -> Option<&LazyValue> {
+        forward(self).as_lazy()
+    }
+
     fn as_state(&self) -> Option<State> {
         forward(self).as_state()
     }
@@ -282,6 +318,11 @@ pub(crate) fn value_eq(lhs: &dyn ValueView, rhs: &dyn ValueView) -> bool {
         });
     }
 
+    if let (Some(x), Some(y)) = (lhs.as_drop(), rhs.as_drop()) {
+        // Like Ruby, a drop is only equal to itself.
+        return std::ptr::addr_eq(x, y);
+    }
+
     if lhs.is_nil() && rhs.is_nil() {
         return true;
     }
@@ -0,0 +1,229 @@
+/// Documentation of a template, from a `{% doc %}` block.
+///
+/// The block holds a free-form description followed by `@`-tags:
+///
+/// ```text
+/// {% doc %}
+///   Renders a product card.
+///
+///   @param {object} product - The product to show
+///   @param {string} [size] - One of `small` or `large`
+///
+///   @example
+///   {% render 'card', product: product %}
+/// {% enddoc %}
+/// ```
+///
+/// Unknown `@`-tags are kept as part of whatever they follow.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Doc {
+    description: String,
+    params: Vec<DocParam>,
+    examples: Vec<String>,
+}
+
+impl Doc {
+    /// Read the contents of a `{% doc %}` block.
+    pub fn parse(text: &str) -> Self {
+        let mut section = Section::Description;
+        let mut description = Vec::new();
+        let mut examples: Vec<Vec<&str>> = Vec::new();
+        let mut params: Vec<(DocParam, Vec<&str>)> = Vec::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(rest) = strip_tag(line, "@param") {
+                params.push((DocParam::parse(rest), Vec::new()));
+                section = Section::Param;
+                continue;
+            }
+            let (rest, next) = if let Some(rest) = strip_tag(line, "@example") {
+                examples.push(Vec::new());
+                (rest, Section::Example)
+            } else if let Some(rest) = strip_tag(line, "@description") {
+                (rest, Section::Description)
+            } else {
+                (line, section)
+            };
+            section = next;
+            let lines = match section {
+                Section::Description => &mut description,
+                Section::Example => examples.last_mut().expect("started by `@example`"),
+                Section::Param => &mut params.last_mut().expect("started by `@param`").1,
+            };
+            lines.push(rest);
+        }
+
+        let params = params
+            .into_iter()
+            .map(|(mut param, lines)| {
+                let more = join_lines(&lines);
+                if !param.description.is_empty() && !more.is_empty() {
+                    param.description.push('\n');
+                }
+                param.description.push_str(&more);
+                param
+            })
+            .collect();
+        Self {
+            description: join_lines(&description),
+            params,
+            examples: examples.iter().map(|lines| join_lines(lines)).collect(),
+        }
+    }
+
+    /// What the template is for.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Variables the template expects, in order.
+    pub fn params(&self) -> &[DocParam] {
+        &self.params
+    }
+
+    /// Example uses of the template.
+    pub fn examples(&self) -> &[String] {
+        &self.examples
+    }
+}
+
+/// A variable a template expects, from an `@param {type} name - description` line.
+///
+/// Wrapping the name in brackets, like `[name]`, marks it optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocParam {
+    name: String,
+    type_name: Option<String>,
+    description: String,
+    required: bool,
+}
+
+impl DocParam {
+    fn parse(text: &str) -> Self {
+        let mut text = text.trim();
+        let mut type_name = None;
+        if let Some(rest) = text.strip_prefix('{') {
+            if let Some((kind, rest)) = rest.split_once('}') {
+                type_name = Some(kind.trim().to_owned());
+                text = rest.trim_start();
+            }
+        }
+        let (name, description) = match text.split_once(char::is_whitespace) {
+            Some((name, description)) => (name, description.trim()),
+            None => (text, ""),
+        };
+        let description = description.strip_prefix('-').unwrap_or(description).trim();
+        let optional = name.strip_prefix('[').and_then(|n| n.strip_suffix(']'));
+        Self {
+            name: optional.unwrap_or(name).to_owned(),
+            type_name,
+            description: description.to_owned(),
+            required: optional.is_none(),
+        }
+    }
+
+    /// Name of the variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Expected type of the variable, like `string` or `product`, if given.
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    /// What the variable is used for.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Whether the template needs the variable.
+    pub fn required(&self) -> bool {
+        self.required
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Section {
+    Description,
+    Param,
+    Example,
+}
+
+/// The rest of `line`, if it starts with the `@`-tag `tag`.
+fn strip_tag<'l>(line: &'l str, tag: &str) -> Option<&'l str> {
+    let rest = line.strip_prefix(tag)?;
+    match rest.chars().next() {
+        None => Some(rest),
+        Some(c) if c.is_whitespace() => Some(rest.trim_start()),
+        Some(_) => None,
+    }
+}
+
+/// Join `lines`, without the blank lines around them.
+fn join_lines(lines: &[&str]) -> String {
+    let start = lines
+        .iter()
+        .position(|l| !l.is_empty())
+        .unwrap_or(lines.len());
+    let end = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(start);
+    lines[start..end].join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_doc() {
+        let doc = Doc::parse(
+            "
+            Renders a product card.
+
+            @param {object} product - The product to show
+            @param {string} [size] - One of `small`
+              or `large`
+            @param title
+            @example
+            {% render 'card', product: product %}
+            ",
+        );
+        assert_eq!(doc.description(), "Renders a product card.");
+        assert_eq!(
+            doc.params(),
+            [
+                DocParam {
+                    name: "product".to_owned(),
+                    type_name: Some("object".to_owned()),
+                    description: "The product to show".to_owned(),
+                    required: true,
+                },
+                DocParam {
+                    name: "size".to_owned(),
+                    type_name: Some("string".to_owned()),
+                    description: "One of `small`\nor `large`".to_owned(),
+                    required: false,
+                },
+                DocParam {
+                    name: "title".to_owned(),
+                    type_name: None,
+                    description: "".to_owned(),
+                    required: true,
+                },
+            ]
+        );
+        assert_eq!(doc.examples(), ["{% render 'card', product: product %}"]);
+    }
+
+    #[test]
+    fn test_parse_description_tag() {
+        let doc = Doc::parse("@param a\n@description Sums\nthings\n@examples");
+        assert_eq!(doc.description(), "Sums\nthings\n@examples");
+        assert_eq!(doc.params().len(), 1);
+    }
+}
@@ -2,23 +2,94 @@ use std::fmt;
 use std::io::Write;
 
 use super::Filter;
+use super::ParseTreeVisitor;
 use crate::error::{Result, ResultLiquidExt, ResultLiquidReplaceExt};
-use crate::model::{ValueCow, ValueView};
+use crate::model::{KString, ValueCow, ValueView};
 use crate::runtime::Expression;
 use crate::runtime::Renderable;
 use crate::runtime::Runtime;
 
+/// A filter as called within a `FilterChain`, for walking the parse tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterCall {
+    name: KString,
+    positional: Vec<Expression>,
+    keyword: Vec<(KString, Expression)>,
+}
+
+impl FilterCall {
+    /// Describe a filter call.
+    pub fn new<S: Into<KString>>(
+        name: S,
+        positional: Vec<Expression>,
+        keyword: Vec<(KString, Expression)>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            positional,
+            keyword,
+        }
+    }
+
+    /// Name of the filter.
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Positional arguments, in order.
+    pub fn positional(&self) -> &[Expression] {
+        &self.positional
+    }
+
+    /// Keyword arguments, in order.
+    pub fn keyword(&self) -> &[(KString, Expression)] {
+        &self.keyword
+    }
+
+    /// Walks the filter call, reporting each node to `visitor`.
+    pub fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_filter(self);
+        for arg in &self.positional {
+            arg.accept(visitor);
+        }
+        for (_, arg) in &self.keyword {
+            arg.accept(visitor);
+        }
+    }
+}
+
 /// A `Value` expression.
 #[derive(Debug)]
 pub struct FilterChain {
     entry: Expression,
     filters: Vec<Box<dyn Filter>>,
+    calls: Vec<FilterCall>,
 }
 
 impl FilterChain {
     /// Create a new expression.
     pub fn new(entry: Expression, filters: Vec<Box<dyn Filter>>) -> Self {
-        Self { entry, filters }
+        Self {
+            entry,
+            filters,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Describe the filters for walking the parse tree.
+    pub fn with_calls(mut self, calls: Vec<FilterCall>) -> Self {
+        self.calls = calls;
+        self
+    }
+
+    /// The value the filters are applied to.
+    pub fn entry(&self) -> &Expression {
+        &self.entry
+    }
+
+    /// The filters applied, as described by [`FilterChain::with_calls`].
+    pub fn calls(&self) -> &[FilterCall] {
+        &self.calls
     }
 
     /// Process `Value` expression within `runtime`'s stack.
@@ -60,4 +131,12 @@ impl Renderable for FilterChain {
         write!(writer, "{}", entry.render()).replace("Failed to render")?;
         Ok(())
     }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_filter_chain(self);
+        self.entry.accept(visitor);
+        for call in &self.calls {
+            call.accept(visitor);
+        }
+    }
 }
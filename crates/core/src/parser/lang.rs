@@ -1,14 +1,45 @@
+use crate::error::{Error, Result};
+
 use super::ParseBlock;
 use super::ParseFilter;
 use super::ParseTag;
 use super::PluginRegistry;
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct Language {
     pub blocks: PluginRegistry<Box<dyn ParseBlock>>,
     pub tags: PluginRegistry<Box<dyn ParseTag>>,
     pub filters: PluginRegistry<Box<dyn ParseFilter>>,
+    /// Maximum nesting of blocks, see [`ResourceLimits::max_depth`].
+    ///
+    /// [`ResourceLimits::max_depth`]: crate::runtime::ResourceLimits::max_depth
+    pub max_depth: usize,
+    /// How malformed markup is handled, failing to parse by default, see [`ErrorMode`].
+    pub error_mode: ErrorMode,
+    /// Whether an unknown filter fails to parse, rather than being skipped with a render warning.
+    pub strict_filters: bool,
+    /// Whether backslash escapes in string literals, like `"\n"` or `"\u{1F600}"`, are replaced.
+    ///
+    /// Off by default, like Ruby's liquid, where backslashes are kept as-is.  Either way, a quote
+    /// after a backslash doesn't end the string.
+    pub string_escapes: bool,
+    /// Whether conditions, in `{% if %}` as well as in outputs and filter arguments, may be
+    /// grouped with `( )` and negated with `not`, with `and` binding tighter than `or`.
+    ///
+    /// Off by default, like Ruby's liquid, where `and` and `or` apply from right to left.
+    pub grouped_conditions: bool,
+    /// Whether the first newline after a tag is removed, unless the tag ends with `+%}`.
+    ///
+    /// Off by default, like Ruby's liquid, where only `-%}` trims whitespace.
+    pub trim_blocks: bool,
+    /// Whether the spaces and tabs from the start of a line up to a tag are removed, unless the
+    /// tag starts with `{%+`.
+    ///
+    /// Off by default, like Ruby's liquid, where only `{%-` trims whitespace.
+    pub lstrip_blocks: bool,
+    /// The delimiters of tags and outputs, `{% %}` and `{{ }}` by default.
+    pub delimiters: Delimiters,
 }
 
 impl Language {
@@ -16,3 +47,142 @@ impl Language {
         Default::default()
     }
 }
+
+impl Default for Language {
+    fn default() -> Self {
+        Self {
+            blocks: Default::default(),
+            tags: Default::default(),
+            filters: Default::default(),
+            max_depth: crate::runtime::ResourceLimits::new().max_depth(),
+            error_mode: Default::default(),
+            strict_filters: true,
+            string_escapes: false,
+            grouped_conditions: false,
+            trim_blocks: false,
+            lstrip_blocks: false,
+            delimiters: Default::default(),
+        }
+    }
+}
+
+/// The delimiters of tags and outputs, like `<%`/`%>` and `[[`/`]]` for templates that also hold
+/// `{{ }}` of another language.
+///
+/// The whitespace control of `{%-`, `-%}` and such applies to them too, like in `<%-` and `-]]`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Delimiters {
+    tag: (String, String),
+    output: (String, String),
+}
+
+impl Delimiters {
+    /// Delimit tags with `tag_start` and `tag_end`, and outputs with `output_start` and
+    /// `output_end`.
+    ///
+    /// Each delimiter must be non-empty and without whitespace.  The starts of tags and outputs
+    /// can't be prefixes of one another, like `[` and `[[`, and neither can their ends.
+    pub fn new(
+        tag_start: &str,
+        tag_end: &str,
+        output_start: &str,
+        output_end: &str,
+    ) -> Result<Self> {
+        for delimiter in [tag_start, tag_end, output_start, output_end] {
+            if delimiter.is_empty() || delimiter.contains(char::is_whitespace) {
+                return Error::with_msg("Delimiters must be non-empty, without whitespace")
+                    .context("delimiter", delimiter.to_owned())
+                    .into_err();
+            }
+        }
+        for (tag, output) in [(tag_start, output_start), (tag_end, output_end)] {
+            if tag.starts_with(output) || output.starts_with(tag) {
+                return Error::with_msg(
+                    "Delimiters of tags and outputs can't be prefixes of one another",
+                )
+                .context("tag delimiter", tag.to_owned())
+                .context("output delimiter", output.to_owned())
+                .into_err();
+            }
+        }
+        Ok(Self {
+            tag: (tag_start.to_owned(), tag_end.to_owned()),
+            output: (output_start.to_owned(), output_end.to_owned()),
+        })
+    }
+
+    /// The start and end of tags.
+    pub fn tag(&self) -> (&str, &str) {
+        (&self.tag.0, &self.tag.1)
+    }
+
+    /// The start and end of outputs.
+    pub fn output(&self) -> (&str, &str) {
+        (&self.output.0, &self.output.1)
+    }
+
+    /// `text` with each delimiter replaced by a mark of the same length, for the grammar to find
+    /// tags and outputs by, see `Rule::TagOpen`.
+    ///
+    /// `None` for the default delimiters, which the grammar knows on its own.
+    pub(super) fn mark(&self, text: &str) -> Option<String> {
+        if *self == Self::default() {
+            return None;
+        }
+        let mut delimiters = [
+            (&self.tag.0, '\u{1}', false),
+            (&self.tag.1, '\u{2}', true),
+            (&self.output.0, '\u{3}', false),
+            (&self.output.1, '\u{4}', true),
+        ];
+        // The longest first, so a delimiter is never taken for one it starts with.
+        delimiters.sort_by_key(|(delimiter, _, _)| std::cmp::Reverse(delimiter.len()));
+
+        let mut marked = String::with_capacity(text.len());
+        let mut rest = text;
+        'text: while let Some(c) = rest.chars().next() {
+            let next = &rest[c.len_utf8()..];
+            for (delimiter, mark, end) in &delimiters {
+                // Ends are taken as late as they overlap, so `[[ a[0]]]` ends at its last `]]`.
+                if rest.starts_with(delimiter.as_str())
+                    && !(*end && next.starts_with(delimiter.as_str()))
+                {
+                    marked.push(*mark);
+                    marked.extend(std::iter::repeat_n('\u{5}', delimiter.len() - 1));
+                    rest = &rest[delimiter.len()..];
+                    continue 'text;
+                }
+            }
+            // Characters of the template that would pass for marks.
+            marked.push(if ('\u{1}'..='\u{5}').contains(&c) {
+                '\0'
+            } else {
+                c
+            });
+            rest = next;
+        }
+        Some(marked)
+    }
+}
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Self {
+            tag: ("{%".to_owned(), "%}".to_owned()),
+            output: ("{{".to_owned(), "}}".to_owned()),
+        }
+    }
+}
+
+/// How the parser handles markup it does not understand.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Fail to parse the template.
+    #[default]
+    Strict,
+    /// Recover from malformed markup like [`ErrorMode::Lax`], recording a warning for each.
+    Warn,
+    /// Drop the parts of malformed markup that can't be parsed, skipping the tag or expression
+    /// entirely if nothing usable is left.
+    Lax,
+}
@@ -1,18 +1,24 @@
 mod block;
+mod doc;
 mod filter;
 mod filter_chain;
 mod lang;
+mod node;
 mod parser;
 mod registry;
 mod tag;
 mod text;
+mod visitor;
 
 pub use block::*;
+pub use doc::*;
 pub use filter::*;
 pub use filter_chain::*;
 pub use lang::*;
 pub use parser::*;
 pub use registry::*;
 pub use tag::*;
+pub use visitor::*;
 
+use node::Node;
 use text::Text;
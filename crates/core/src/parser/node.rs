@@ -0,0 +1,155 @@
+use std::io::Write;
+
+use crate::error::{Location, Result, ResultLiquidReplaceExt};
+use crate::parser::ParseTreeVisitor;
+use crate::runtime::Renderable;
+use crate::runtime::Runtime;
+#[cfg(feature = "async")]
+use crate::runtime::{AsyncWrite, RenderFuture};
+
+/// A tag or expression, annotated with where it came from in the template source.
+#[derive(Debug)]
+pub(crate) struct Node {
+    tag: Option<String>,
+    code: String,
+    location: Location,
+    renderable: Box<dyn Renderable>,
+}
+
+impl Node {
+    pub(crate) fn new<S: Into<String>>(
+        code: S,
+        location: Location,
+        renderable: Box<dyn Renderable>,
+    ) -> Self {
+        Self {
+            tag: None,
+            code: code.into(),
+            location,
+            renderable,
+        }
+    }
+
+    /// Mark this node as the tag or block `name`.
+    pub(crate) fn with_tag<S: Into<String>>(mut self, name: S) -> Self {
+        self.tag = Some(name.into());
+        self
+    }
+
+    fn location(&self, runtime: &dyn Runtime) -> Location {
+        match runtime.name() {
+            Some(name) => self
+                .location
+                .clone()
+                .with_template(crate::model::KString::from_ref(&name)),
+            None => self.location.clone(),
+        }
+    }
+
+    fn render_node(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let warnings = runtime.warnings().len();
+        let result = self.renderable.render_to(writer, runtime);
+        match self.finish_node(result, warnings, runtime)? {
+            Some(placeholder) => write!(writer, "{placeholder}").replace("Failed to render"),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    async fn render_node_async(
+        &self,
+        writer: &mut (dyn AsyncWrite + Unpin),
+        runtime: &dyn Runtime,
+    ) -> Result<()> {
+        let warnings = runtime.warnings().len();
+        let result = self.renderable.render_to_async(writer, runtime).await;
+        match self.finish_node(result, warnings, runtime)? {
+            Some(placeholder) => {
+                let mut buffer = Vec::new();
+                write!(runtime.resource_usage().meter(&mut buffer), "{placeholder}")
+                    .replace("Failed to render")?;
+                crate::runtime::write_all_async(writer, &buffer).await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Locate the errors of rendering the node, returning the placeholder to write in place of a
+    /// failure.
+    fn finish_node<'r>(
+        &self,
+        result: Result<()>,
+        warnings: usize,
+        runtime: &'r dyn Runtime,
+    ) -> Result<Option<&'r str>> {
+        let result = result.map_err(|err| err.location(self.location(runtime)));
+        if warnings < runtime.warnings().len() {
+            runtime.warnings().locate(warnings, &self.location(runtime));
+        }
+        match (result, runtime.error_placeholder()) {
+            (Err(err), Some(placeholder)) if !runtime.resource_usage().exceeded() => {
+                runtime.warnings().push(err);
+                Ok(Some(placeholder))
+            }
+            (result, _) => result.map(|()| None),
+        }
+    }
+}
+
+impl Renderable for Node {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        if !runtime.tracks_nodes() {
+            return self
+                .renderable
+                .render_to(writer, runtime)
+                .map_err(|err| err.location(self.location(runtime)));
+        }
+        match runtime.profiler() {
+            Some(profiler) => {
+                let partial = runtime.name();
+                profiler.profile(&self.code, partial.as_deref(), self.location.line(), || {
+                    self.render_node(writer, runtime)
+                })
+            }
+            None => self.render_node(writer, runtime),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            if !runtime.tracks_nodes() {
+                return self
+                    .renderable
+                    .render_to_async(writer, runtime)
+                    .await
+                    .map_err(|err| err.location(self.location(runtime)));
+            }
+            match runtime.profiler() {
+                Some(profiler) => {
+                    let partial = runtime.name();
+                    profiler.enter(&self.code, partial.as_deref(), self.location.line());
+                    let result = self.render_node_async(writer, runtime).await;
+                    profiler.leave();
+                    result
+                }
+                None => self.render_node_async(writer, runtime).await,
+            }
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        match &self.tag {
+            Some(tag) => {
+                visitor.enter_tag(tag);
+                self.renderable.accept(visitor);
+                visitor.leave_tag(tag);
+            }
+            None => self.renderable.accept(visitor),
+        }
+    }
+}
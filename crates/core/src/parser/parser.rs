@@ -3,15 +3,22 @@
 //! This module contains functions than can be used for writing plugins
 //! but should be ignored for simple usage.
 
-use crate::error::{Error, Result, ResultLiquidExt};
-use crate::model::Value;
+use std::cell::{Cell, RefCell};
+
+use crate::error::{Error, Location, Result, ResultLiquidExt};
+use crate::model::{KString, Value, ValueView};
 use crate::runtime::Expression;
 use crate::runtime::Renderable;
+use crate::runtime::Runtime;
 use crate::runtime::Variable;
+use crate::runtime::{ComparisonOperator, Condition};
 
+use super::Delimiters;
+use super::ErrorMode;
 use super::Language;
+use super::Node;
 use super::Text;
-use super::{Filter, FilterArguments, FilterChain};
+use super::{Filter, FilterArguments, FilterCall, FilterChain};
 
 use pest::Parser;
 
@@ -23,8 +30,7 @@ mod inner {
 
 use self::inner::*;
 
-type Pair<'a> = ::pest::iterators::Pair<'a, Rule>;
-type Pairs<'a> = ::pest::iterators::Pairs<'a, Rule>;
+type Renderables = Vec<Box<dyn Renderable>>;
 
 /// Converts a `pest::Error` into a `liquid::Error`.
 fn convert_pest_error(err: ::pest::error::Error<Rule>) -> Error {
@@ -39,25 +45,183 @@ fn convert_pest_error(err: ::pest::error::Error<Rule>) -> Error {
         Rule::Assign => "\"=\"".to_string(),
         Rule::Comma => "\",\"".to_string(),
         Rule::Colon => "\":\"".to_string(),
+        Rule::OpenParen => "\"(\"".to_string(),
+        Rule::CloseParen => "\")\"".to_string(),
+        Rule::Or => "\"or\"".to_string(),
+        Rule::And => "\"and\"".to_string(),
+        Rule::Not => "\"not\"".to_string(),
+        Rule::Contains => "\"contains\"".to_string(),
         other => format!("{:?}", other),
     });
-    Error::with_msg(err.to_string())
+    let (line, column) = match err.line_col {
+        ::pest::error::LineColLocation::Pos(start) => start,
+        ::pest::error::LineColLocation::Span(start, _) => start,
+    };
+    // `pest` starts with where the error is, which is recorded as the location instead, and
+    // ends with the message, which goes first.
+    let rendered = err.to_string();
+    let snippet = rendered
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim_start().starts_with("= "));
+    let msg = std::iter::once(err.variant.message().as_ref())
+        .chain(snippet)
+        .collect::<Vec<_>>()
+        .join("\n");
+    Error::with_msg(msg).location(Location::new(line, column))
+}
+
+/// Generates a `liquid::Error` with the given message pointing to `span`.
+fn error_from_span(variant: ::pest::error::ErrorVariant<Rule>, span: pest::Span) -> Error {
+    convert_pest_error(::pest::error::Error::new_from_span(variant, span))
+}
+
+/// A `pest` pair that reads from the template source, which differs from what was parsed once
+/// custom delimiters are marked, see `Delimiters::mark`.
+#[derive(Clone, Debug)]
+struct Pair<'a> {
+    pair: ::pest::iterators::Pair<'a, Rule>,
+    source: &'a str,
+}
+
+impl<'a> Pair<'a> {
+    fn as_rule(&self) -> Rule {
+        self.pair.as_rule()
+    }
+
+    fn as_span(&self) -> pest::Span<'a> {
+        let span = self.pair.as_span();
+        position(self.source, span.start()).span(&position(self.source, span.end()))
+    }
+
+    fn as_str(&self) -> &'a str {
+        self.as_span().as_str()
+    }
+
+    fn into_inner(self) -> Pairs<'a> {
+        Pairs {
+            pairs: self.pair.into_inner(),
+            source: self.source,
+        }
+    }
+}
+
+/// The `pest` pairs of a template source, see `Pair`.
+#[derive(Clone, Debug)]
+struct Pairs<'a> {
+    pairs: ::pest::iterators::Pairs<'a, Rule>,
+    source: &'a str,
+}
+
+#[allow(clippy::result_large_err)] // The same error as `pest::Parser::parse`.
+impl<'a> Pairs<'a> {
+    /// Parses `text` from `rule`.
+    fn parse(rule: Rule, text: &'a str) -> std::result::Result<Self, ::pest::error::Error<Rule>> {
+        Self::parse_template(rule, text, None)
+    }
+
+    /// Parses the template `source` from `rule`, finding its delimiters by the marks of `marked`
+    /// instead of the default ones, if any.
+    fn parse_template(
+        rule: Rule,
+        source: &'a str,
+        marked: Option<&'a str>,
+    ) -> std::result::Result<Self, ::pest::error::Error<Rule>> {
+        use pest::error::InputLocation;
+
+        let Some(marked) = marked else {
+            let pairs = LiquidParser::parse(rule, source)?;
+            return Ok(Self { pairs, source });
+        };
+        let rule = match rule {
+            Rule::LaxLiquidFile => Rule::DelimitedLaxLiquidFile,
+            Rule::LiquidFile => Rule::DelimitedLiquidFile,
+            _ => unreachable!("no delimited flavor of {rule:?}"),
+        };
+        match LiquidParser::parse(rule, marked) {
+            Ok(pairs) => Ok(Self { pairs, source }),
+            // Point to the template rather than to its marks.
+            Err(error) => Err(match error.location {
+                InputLocation::Pos(pos) => {
+                    ::pest::error::Error::new_from_pos(error.variant, position(source, pos))
+                }
+                InputLocation::Span((start, end)) => ::pest::error::Error::new_from_span(
+                    error.variant,
+                    position(source, start).span(&position(source, end)),
+                ),
+            }),
+        }
+    }
+
+    fn peek(&self) -> Option<Pair<'a>> {
+        self.pairs.peek().map(|pair| Pair {
+            pair,
+            source: self.source,
+        })
+    }
+}
+
+impl<'a> Iterator for Pairs<'a> {
+    type Item = Pair<'a>;
+
+    fn next(&mut self) -> Option<Pair<'a>> {
+        self.pairs.next().map(|pair| Pair {
+            pair,
+            source: self.source,
+        })
+    }
+}
+
+/// The position at byte `pos` of `source`, or the start of the character it is within, as the
+/// marks of a delimiter take a byte each.
+fn position(source: &str, pos: usize) -> pest::Position<'_> {
+    let pos = (0..=pos)
+        .rev()
+        .find(|&pos| source.is_char_boundary(pos))
+        .unwrap_or(0);
+    pest::Position::new(source, pos).expect("`pos` is a char boundary")
+}
+
+/// Where `pair` starts in the template source.
+fn location_of(pair: &Pair) -> Location {
+    let (line, column) = pair.as_span().start_pos().line_col();
+    Location::new(line, column)
 }
 
 /// Generates a `liquid::Error` with the given message pointing to
 /// the pest
 fn error_from_pair(pair: Pair, msg: String) -> Error {
-    let pest_error = ::pest::error::Error::new_from_span(
+    error_from_span(
         ::pest::error::ErrorVariant::CustomError { message: msg },
         pair.as_span(),
-    );
-    convert_pest_error(pest_error)
+    )
 }
 
 /// Parses the provided &str into a number of Renderable items.
 pub fn parse(text: &str, options: &Language) -> Result<Vec<Box<dyn Renderable>>> {
-    let mut liquid = LiquidParser::parse(Rule::LaxLiquidFile, text)
-        .expect("Parsing with Rule::LaxLiquidFile should not raise errors, but InvalidLiquid tokens instead.")
+    parse_with_warnings(text, options).map(|(renderables, _)| renderables)
+}
+
+/// Parses the provided &str into a number of Renderable items, along with an error for each piece
+/// of malformed markup recovered from under [`ErrorMode::Warn`].
+pub fn parse_with_warnings(text: &str, options: &Language) -> Result<(Renderables, Vec<Error>)> {
+    let warnings = RefCell::new(Vec::new());
+    let context = Context {
+        depth: 0,
+        string_escapes: options.string_escapes,
+        delimiters: &options.delimiters,
+        warnings: &warnings,
+    };
+    let renderables = parse_in(text, context, options)?;
+    Ok((renderables, warnings.into_inner()))
+}
+
+/// Parses `text` as if it were part of the template `context` is about, like the lines of a
+/// `{% liquid %}` tag.
+fn parse_in(text: &str, context: Context<'_>, options: &Language) -> Result<Renderables> {
+    let marked = options.delimiters.mark(text);
+    let mut liquid = Pairs::parse_template(Rule::LaxLiquidFile, text, marked.as_deref())
+        .map_err(convert_pest_error)?
         .next()
         .expect("Unwrapping LiquidFile to access the elements.")
         .into_inner();
@@ -72,25 +236,119 @@ pub fn parse(text: &str, options: &Language) -> Result<Vec<Box<dyn Renderable>>>
         renderables.push(BlockElement::parse_pair(
             element.into(),
             &mut liquid,
+            context,
             options,
         )?);
     }
     Ok(renderables)
 }
 
+/// Where an element is being parsed.
+#[derive(Copy, Clone)]
+struct Context<'b> {
+    /// Number of blocks the element is nested within.
+    depth: usize,
+    /// Whether string literals are unescaped, see [`Language::string_escapes`].
+    string_escapes: bool,
+    /// The delimiters of the template, see [`Language::delimiters`].
+    delimiters: &'b Delimiters,
+    warnings: &'b RefCell<Vec<Error>>,
+}
+
+impl Context<'_> {
+    /// Fail with `error`, unless the error mode allows recovering from it.
+    fn recover(&self, error: Error, options: &Language) -> Result<()> {
+        match options.error_mode {
+            ErrorMode::Strict => Err(error),
+            ErrorMode::Warn => {
+                self.warnings.borrow_mut().push(error);
+                Ok(())
+            }
+            ErrorMode::Lax => Ok(()),
+        }
+    }
+
+    fn nested(self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..self
+        }
+    }
+}
+
+/// Drop whatever `rule` can't make sense of, for lax parsing.
+///
+/// Returns `None` if nothing usable is left.
+fn sanitize(markup: &str, rule: Rule) -> Option<String> {
+    let markup = collapse_dots(markup.trim());
+    let lax_rule = match rule {
+        Rule::TagInner => Rule::LaxTagInner,
+        Rule::ConditionChain => Rule::LaxConditionChain,
+        _ => unreachable!("no lax flavor of {rule:?}"),
+    };
+    let parsed = LiquidParser::parse(lax_rule, &markup).ok()?.next()?;
+
+    let mut sanitized = String::with_capacity(markup.len());
+    let mut start = 0;
+    for junk in parsed.into_inner().flatten() {
+        if junk.as_rule() == Rule::LaxJunk {
+            let span = junk.as_span();
+            sanitized.push_str(&markup[start..span.start()]);
+            start = span.end();
+        }
+    }
+    sanitized.push_str(&markup[start..]);
+
+    let end = LiquidParser::parse(rule, &sanitized)
+        .ok()?
+        .next()?
+        .as_span()
+        .end();
+    sanitized[end..].trim().is_empty().then_some(sanitized)
+}
+
+/// Treat extra dots in ranges, like `(1...5)`, as `..`.
+fn collapse_dots(markup: &str) -> String {
+    let mut collapsed = String::with_capacity(markup.len());
+    let mut quote = None;
+    for c in markup.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == '.' && collapsed.ends_with("..") => continue,
+            None => {}
+        }
+        collapsed.push(c);
+    }
+    collapsed
+}
+
+/// The markup between `start` and `end`, without any whitespace control.
+fn strip_delimiters<'m>(markup: &'m str, start: &str, end: &str) -> Option<&'m str> {
+    let markup = markup.strip_prefix(start)?.strip_suffix(end)?;
+    let markup = markup.strip_prefix(['-', '+']).unwrap_or(markup);
+    Some(markup.strip_suffix(['-', '+']).unwrap_or(markup))
+}
+
+/// Rendered in place of malformed markup that was skipped.
+fn skipped() -> Box<dyn Renderable> {
+    Box::new(Text::new(""))
+}
+
 /// Given a `Variable` as a string, parses it into a `Variable`.
 pub fn parse_variable(text: &str) -> Result<Variable> {
-    let variable = LiquidParser::parse(Rule::Variable, text)
+    let variable = Pairs::parse(Rule::Variable, text)
         .map_err(convert_pest_error)?
         .next()
         .expect("Parsing a variable failed.");
 
-    Ok(parse_variable_pair(variable))
+    Ok(parse_variable_pair(variable, false))
 }
 
 /// Parses a `Scalar` from a `Pair` with a literal value.
 /// This `Pair` must be `Rule::Literal`.
-fn parse_literal(literal: Pair) -> Value {
+fn parse_literal(literal: Pair, string_escapes: bool) -> Value {
     if literal.as_rule() != Rule::Literal {
         panic!("Expected literal.");
     }
@@ -104,12 +362,7 @@ fn parse_literal(literal: Pair) -> Value {
         Rule::NilLiteral => Value::Nil,
         Rule::EmptyLiteral => Value::State(crate::model::State::Empty),
         Rule::BlankLiteral => Value::State(crate::model::State::Blank),
-        Rule::StringLiteral => {
-            let literal = literal.as_str();
-            let trim_quotes = &literal[1..literal.len() - 1];
-
-            Value::scalar(trim_quotes.to_owned())
-        }
+        Rule::StringLiteral => Value::scalar(parse_string(literal, string_escapes)),
         Rule::IntegerLiteral => Value::scalar(
             literal
                 .as_str()
@@ -132,9 +385,152 @@ fn parse_literal(literal: Pair) -> Value {
     }
 }
 
+/// Parses the contents of a `Rule::StringLiteral`, replacing its escapes with
+/// [`Language::string_escapes`].
+fn parse_string(literal: Pair, string_escapes: bool) -> String {
+    let literal = literal.as_str();
+    let trim_quotes = &literal[1..literal.len() - 1];
+    if string_escapes {
+        unescape(trim_quotes)
+    } else {
+        trim_quotes.to_owned()
+    }
+}
+
+/// Replaces the escapes matched by `Rule::StringEscape`, keeping any other backslash as-is.
+fn unescape(escaped: &str) -> String {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut rest = escaped;
+    while let Some(start) = rest.find('\\') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(escape) = LiquidParser::parse(Rule::StringEscape, rest)
+            .ok()
+            .and_then(|mut escape| escape.next())
+        else {
+            // Not an escape, like the `\U` of `C:\Users`.
+            unescaped.push('\\');
+            rest = &rest[1..];
+            continue;
+        };
+        let escape = escape.as_str();
+        rest = &rest[escape.len()..];
+        unescaped.push(match &escape[1..] {
+            "n" => '\n',
+            "r" => '\r',
+            "t" => '\t',
+            "0" => '\0',
+            code if code.starts_with('u') => u32::from_str_radix(&code[2..code.len() - 1], 16)
+                .ok()
+                .and_then(char::from_u32)
+                .unwrap_or(char::REPLACEMENT_CHARACTER),
+            c => c
+                .chars()
+                .next()
+                .expect("Grammar ensures escapes are complete."),
+        });
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Parses an `Expression` from a `Pair` with a condition, which is only a `Condition` if it uses
+/// any operator.
+///
+/// Comparisons bind tighter than `and` and `or`, which apply from right to left like in `{% if %}`.
+/// With [`Language::grouped_conditions`], `not` binds tightest, then comparisons, then `and`,
+/// then `or`, and only then are `not` and `( )` allowed.
+/// This `Pair` must be `Rule::Condition`.
+fn parse_condition(condition: Pair, options: &Language) -> Result<Expression> {
+    if condition.as_rule() != Rule::Condition {
+        panic!("Expected condition.");
+    }
+
+    parse_operation(&mut condition.into_inner().peekable(), 0, options)
+}
+
+/// Parses operands and the operators between them binding at least as tight as `min_precedence`.
+fn parse_operation(
+    pairs: &mut std::iter::Peekable<Pairs>,
+    min_precedence: u8,
+    options: &Language,
+) -> Result<Expression> {
+    let grouped = options.grouped_conditions;
+    let mut lh = parse_operand(pairs, options)?;
+    while let Some(precedence) = pairs
+        .peek()
+        .and_then(|op| precedence_of(op.as_rule(), grouped))
+    {
+        if precedence < min_precedence {
+            break;
+        }
+        let op = pairs.next().expect("peeked");
+        // Without grouping, `and` and `or` share a precedence and apply from right to left.
+        let right_to_left = !grouped && matches!(op.as_rule(), Rule::And | Rule::Or);
+        let rh_precedence = if right_to_left {
+            precedence
+        } else {
+            precedence + 1
+        };
+        let rh = parse_operation(pairs, rh_precedence, options)?;
+        let condition = match op.as_rule() {
+            Rule::Or => Condition::Disjunction(into_condition(lh), into_condition(rh)),
+            Rule::And => Condition::Conjunction(into_condition(lh), into_condition(rh)),
+            _ => {
+                let comparison = ComparisonOperator::from_symbol(op.as_str())
+                    .expect("Grammar ensures comparison operators.");
+                Condition::Binary(lh, comparison, rh)
+            }
+        };
+        lh = Expression::Condition(Box::new(condition));
+    }
+    Ok(lh)
+}
+
+fn parse_operand(pairs: &mut std::iter::Peekable<Pairs>, options: &Language) -> Result<Expression> {
+    let grouped = options.grouped_conditions;
+    let operand = pairs.next().expect("Grammar ensures an operand.");
+    match operand.as_rule() {
+        Rule::Not | Rule::Condition if !grouped => Err(error_from_pair(
+            operand,
+            "`not` and `( )` require grouped conditions.".to_owned(),
+        )),
+        Rule::Not => {
+            let condition = Condition::Not(into_condition(parse_operand(pairs, options)?));
+            Ok(Expression::Condition(Box::new(condition)))
+        }
+        Rule::Condition => parse_operation(&mut operand.into_inner().peekable(), 0, options),
+        _ => Ok(parse_value(operand, options.string_escapes)),
+    }
+}
+
+fn precedence_of(rule: Rule, grouped: bool) -> Option<u8> {
+    match rule {
+        Rule::Or => Some(1),
+        Rule::And if grouped => Some(2),
+        Rule::And => Some(1),
+        Rule::Contains
+        | Rule::Equals
+        | Rule::NotEquals
+        | Rule::LesserThanGreaterThan
+        | Rule::GreaterThanEquals
+        | Rule::LesserThanEquals
+        | Rule::GreaterThan
+        | Rule::LesserThan => Some(3),
+        _ => None,
+    }
+}
+
+fn into_condition(expression: Expression) -> Box<Condition> {
+    match expression {
+        Expression::Condition(condition) => condition,
+        expression => Box::new(Condition::Existence(expression)),
+    }
+}
+
 /// Parses a `Variable` from a `Pair` with a variable.
 /// This `Pair` must be `Rule::Variable`.
-fn parse_variable_pair(variable: Pair) -> Variable {
+fn parse_variable_pair(variable: Pair, string_escapes: bool) -> Variable {
     if variable.as_rule() != Rule::Variable {
         panic!("Expected variable.");
     }
@@ -150,7 +546,7 @@ fn parse_variable_pair(variable: Pair) -> Variable {
 
     let indexes = indexes.map(|index| match index.as_rule() {
         Rule::Identifier => Expression::with_literal(index.as_str().to_owned()),
-        Rule::Value => parse_value(index),
+        Rule::Value => parse_value(index, string_escapes),
         _ => unreachable!(),
     });
 
@@ -164,7 +560,7 @@ fn parse_variable_pair(variable: Pair) -> Variable {
 /// In this runtime, value refers to either a literal value or a variable.
 ///
 /// This `Pair` must be `Rule::Value`.
-fn parse_value(value: Pair) -> Expression {
+fn parse_value(value: Pair, string_escapes: bool) -> Expression {
     if value.as_rule() != Rule::Value {
         panic!("Expected value.");
     }
@@ -172,20 +568,64 @@ fn parse_value(value: Pair) -> Expression {
     let value = value.into_inner().next().expect("Get inside the value.");
 
     match value.as_rule() {
-        Rule::Literal => Expression::Literal(parse_literal(value)),
-        Rule::Variable => Expression::Variable(parse_variable_pair(value)),
+        Rule::Literal => Expression::Literal(parse_literal(value, string_escapes)),
+        Rule::Variable => Expression::Variable(parse_variable_pair(value, string_escapes)),
+        Rule::ArrayLiteral => {
+            let array: Vec<_> = value
+                .into_inner()
+                .map(|value| parse_value(value, string_escapes))
+                .collect();
+            // Evaluate once if nothing needs the runtime.
+            if array.iter().all(|x| matches!(x, Expression::Literal(_))) {
+                let array = array.into_iter().filter_map(Expression::into_literal);
+                Expression::Literal(Value::array(array))
+            } else {
+                Expression::Array(array)
+            }
+        }
+        Rule::ObjectLiteral => {
+            let object: Vec<_> = value
+                .into_inner()
+                .map(|entry| {
+                    let mut entry = entry.into_inner();
+                    let key = entry.next().expect("An entry starts with its key.");
+                    let key = match key.as_rule() {
+                        Rule::Identifier => key.as_str().to_owned(),
+                        _ => parse_string(key, string_escapes),
+                    };
+                    let value = entry.next().expect("An entry ends with its value.");
+                    (
+                        KString::from_string(key),
+                        parse_value(value, string_escapes),
+                    )
+                })
+                .collect();
+            if object
+                .iter()
+                .all(|(_, x)| matches!(x, Expression::Literal(_)))
+            {
+                let object = object
+                    .into_iter()
+                    .filter_map(|(k, x)| Some((k, x.into_literal()?)))
+                    .collect();
+                Expression::Literal(Value::Object(object))
+            } else {
+                Expression::Object(object)
+            }
+        }
         _ => unreachable!(),
     }
 }
 
 /// Parses a `FilterCall` from a `Pair` with a filter.
 /// This `Pair` must be `Rule::Filter`.
-fn parse_filter(filter: Pair, options: &Language) -> Result<Box<dyn Filter>> {
+fn parse_filter(filter: Pair, options: &Language) -> Result<(Box<dyn Filter>, FilterCall)> {
     if filter.as_rule() != Rule::Filter {
         panic!("Expected a filter.");
     }
 
     let filter_str = filter.as_str();
+    let location = location_of(&filter);
     let mut filter = filter.into_inner();
     let name = filter.next().expect("A filter always has a name.").as_str();
 
@@ -196,60 +636,103 @@ fn parse_filter(filter: Pair, options: &Language) -> Result<Box<dyn Filter>> {
         match arg.as_rule() {
             Rule::PositionalFilterArgument => {
                 let value = arg.into_inner().next().expect("Rule ensures value.");
-                let value = parse_value(value);
+                let value = parse_condition(value, options)?;
                 positional_args.push(value);
             }
             Rule::KeywordFilterArgument => {
                 let mut arg = arg.into_inner();
                 let key = arg.next().expect("Rule ensures identifier.").as_str();
                 let value = arg.next().expect("Rule ensures value.");
-                let value = parse_value(value);
+                let value = parse_condition(value, options)?;
                 keyword_args.push((key, value));
             }
             _ => unreachable!(),
         }
     }
 
+    let call = FilterCall::new(
+        name.to_owned(),
+        positional_args.clone(),
+        keyword_args
+            .iter()
+            .map(|(key, value)| ((*key).to_owned().into(), value.clone()))
+            .collect(),
+    );
     let args = FilterArguments {
         positional: Box::new(positional_args.into_iter()),
         keyword: Box::new(keyword_args.into_iter()),
     };
 
-    let f = options.filters.get(name).ok_or_else(|| {
-        let mut available: Vec<_> = options.filters.plugin_names().collect();
-        available.sort_unstable();
-        let available = itertools::join(available, ", ");
-        Error::with_msg("Unknown filter")
-            .context("requested filter", name.to_owned())
-            .context("available filters", available)
-    })?;
+    let f = match options.filters.get(name) {
+        Some(f) => f,
+        None => {
+            let mut available: Vec<_> = options.filters.plugin_names().collect();
+            available.sort_unstable();
+            let available = itertools::join(available, ", ");
+            let error = Error::with_msg("Unknown filter")
+                .location(location)
+                .context("requested filter", name.to_owned())
+                .context("available filters", available);
+            if options.strict_filters {
+                return Err(error);
+            }
+            let f = UnknownFilter {
+                source: filter_str.to_owned(),
+                error,
+            };
+            return Ok((Box::new(f), call));
+        }
+    };
 
     let f = f
         .parse(args)
+        .map_err(|e| e.location(location))
         .trace("Filter parsing error")
         .context_key("filter")
         .value_with(|| filter_str.to_string().into())?;
 
-    Ok(f)
+    Ok((f, call))
+}
+
+/// Stands in for a filter that isn't registered, when filters aren't strict.
+#[derive(Debug)]
+struct UnknownFilter {
+    source: String,
+    error: Error,
+}
+
+impl Filter for UnknownFilter {
+    fn evaluate(&self, input: &dyn ValueView, runtime: &dyn Runtime) -> Result<Value> {
+        runtime.warnings().push(self.error.clone());
+        Ok(input.to_value())
+    }
+}
+
+impl std::fmt::Display for UnknownFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
 }
 
 /// Parses a `FilterChain` from a `Pair` with a filter chain.
 /// This `Pair` must be `Rule::FilterChain`.
 fn parse_filter_chain(chain: Pair, options: &Language) -> Result<FilterChain> {
-    if chain.as_rule() != Rule::FilterChain {
+    if chain.as_rule() != Rule::FilterChain && chain.as_rule() != Rule::ConditionChain {
         panic!("Expected an expression with filters.");
     }
 
     let mut chain = chain.into_inner();
-    let entry = parse_value(
-        chain
-            .next()
-            .expect("A filterchain always has starts by a value."),
-    );
+    let entry = chain
+        .next()
+        .expect("A filterchain always has starts by a value.");
+    let entry = match entry.as_rule() {
+        Rule::Condition => parse_condition(entry, options)?,
+        _ => parse_value(entry, options.string_escapes),
+    };
     let filters: Result<Vec<_>> = chain.map(|f| parse_filter(f, options)).collect();
-    let filters = filters?;
+    let (filters, calls) = filters?.into_iter().unzip();
 
-    let filters = FilterChain::new(entry, filters);
+    let filters = FilterChain::new(entry, filters).with_calls(calls);
     Ok(filters)
 }
 
@@ -258,7 +741,8 @@ pub struct TagBlock<'a: 'b, 'b> {
     start_tag: &'b str,
     end_tag: &'b str,
     iter: &'b mut dyn Iterator<Item = Pair<'a>>,
-    closed: bool,
+    closed: &'b Cell<bool>,
+    context: Context<'a>,
 }
 
 impl<'a, 'b> TagBlock<'a, 'b> {
@@ -266,12 +750,15 @@ impl<'a, 'b> TagBlock<'a, 'b> {
         start_tag: &'b str,
         end_tag: &'b str,
         next_elements: &'b mut dyn Iterator<Item = Pair<'a>>,
+        closed: &'b Cell<bool>,
+        context: Context<'a>,
     ) -> Self {
         TagBlock {
             start_tag,
             end_tag,
             iter: next_elements,
-            closed: false,
+            closed,
+            context,
         }
     }
 
@@ -281,7 +768,7 @@ impl<'a, 'b> TagBlock<'a, 'b> {
     /// an error is returned instead.
     #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Result<Option<BlockElement<'a>>> {
-        if self.closed {
+        if self.closed.get() {
             return Ok(None);
         }
 
@@ -298,11 +785,9 @@ impl<'a, 'b> TagBlock<'a, 'b> {
         // Tags are treated separately so as to check for a possible `{% endtag %}`
         if element.as_rule() == Rule::Tag {
             let as_str = element.as_str();
-            let mut tag = element
-                .into_inner()
-                .next()
-                .expect("Unwrapping TagInner")
-                .into_inner();
+            let inner = element.into_inner().next().expect("Unwrapping TagInner");
+            let code = inner.as_str().trim();
+            let mut tag = inner.into_inner();
             let name = tag.next().expect("Tags start by their identifier.");
             let name_str = name.as_str();
 
@@ -315,15 +800,19 @@ impl<'a, 'b> TagBlock<'a, 'b> {
                     return TagToken::from(token).raise_error().into_err();
                 }
 
-                self.closed = true;
+                self.closed.set(true);
                 return Ok(None);
             } else {
                 // Then this is a regular tag
+                let location = location_of(&name);
                 let tokens = TagTokenIter::new(&name, tag);
                 return Ok(Some(BlockElement::Tag(Tag {
                     name,
                     tokens,
                     as_str,
+                    code,
+                    location,
+                    recovered: false,
                 })));
             }
         }
@@ -345,7 +834,7 @@ impl<'a, 'b> TagBlock<'a, 'b> {
     ///
     /// Will panic if used in a closed block.
     pub fn escape_liquid(&mut self, allow_nesting: bool) -> Result<&'a str> {
-        if self.closed {
+        if self.closed.get() {
             panic!("`escape_liquid` must be used in an open tag.")
         }
 
@@ -387,7 +876,7 @@ impl<'a, 'b> TagBlock<'a, 'b> {
                     if tag.next().is_none() {
                         nesting_level -= 1;
                         if nesting_level == 0 {
-                            self.closed = true;
+                            self.closed.set(true);
                             let start_pos = start_pos.expect("Will be `Some` inside this loop.");
                             let output = match end_pos {
                                 Some(end_pos) => start_pos.span(&end_pos).as_str(),
@@ -428,13 +917,31 @@ impl<'a, 'b> TagBlock<'a, 'b> {
         }
     }
 
+    /// Skips the rest of the block, after recovering from malformed markup.
+    ///
+    /// The elements are still parsed, so malformed markup within them is reported too.
+    fn skip(mut self, options: &Language) -> Result<()> {
+        while let Some(element) = self.next()? {
+            match element {
+                // Tags only valid within the skipped block, like `else`.
+                BlockElement::Tag(tag)
+                    if options.tags.get(tag.name()).is_none()
+                        && options.blocks.get(tag.name()).is_none() => {}
+                element => {
+                    element.parse(&mut self, options)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Checks whether the block was fully parsed its elements.
     ///
     /// This must be added at the end of every block right before returning, so as
     /// to ensure that it doesn't leave any unparsed element by accident.
     pub fn assert_empty(self) {
         assert!(
-            self.closed,
+            self.closed.get(),
             "Block {{% {} %}} doesn't exhaust its iterator of elements.",
             self.start_tag
         )
@@ -444,6 +951,7 @@ impl<'a, 'b> TagBlock<'a, 'b> {
 /// An element that is raw text.
 pub struct Raw<'a> {
     text: &'a str,
+    span: ::pest::Span<'a>,
 }
 impl<'a> From<Pair<'a>> for Raw<'a> {
     fn from(element: Pair<'a>) -> Self {
@@ -452,6 +960,7 @@ impl<'a> From<Pair<'a>> for Raw<'a> {
         }
         Raw {
             text: element.as_str(),
+            span: element.as_span(),
         }
     }
 }
@@ -471,6 +980,46 @@ impl<'a> Raw<'a> {
     pub fn as_str(&self) -> &'a str {
         self.text
     }
+
+    /// The text without the whitespace around tags removed by [`Language::trim_blocks`] and
+    /// [`Language::lstrip_blocks`].
+    fn trimmed(&self, options: &Language) -> &'a str {
+        let input = self.span.get_input();
+        let before = &input[..self.span.start()];
+        let after = &input[self.span.end()..];
+        let mut text = self.text;
+        let (tag_start, tag_end) = options.delimiters.tag();
+
+        if options.trim_blocks
+            && before
+                .strip_suffix(tag_end)
+                .is_some_and(|before| !before.ends_with('+'))
+        {
+            text = text
+                .strip_prefix("\r\n")
+                .or_else(|| text.strip_prefix('\n'))
+                .unwrap_or(text);
+        }
+
+        if options.lstrip_blocks
+            && after
+                .strip_prefix(tag_start)
+                .is_some_and(|after| !after.starts_with('+'))
+        {
+            let indented = text.trim_end_matches([' ', '\t']);
+            let line_start = if indented.is_empty() {
+                // Only indentation is left, which starts a line if the text does.
+                self.text.len() != text.len() || before.is_empty() || before.ends_with('\n')
+            } else {
+                indented.ends_with('\n')
+            };
+            if line_start {
+                text = indented;
+            }
+        }
+
+        text
+    }
 }
 
 /// An element that is a tag.
@@ -478,6 +1027,10 @@ pub struct Tag<'a> {
     name: Pair<'a>,
     tokens: TagTokenIter<'a>,
     as_str: &'a str,
+    code: &'a str,
+    location: Location,
+    /// Rebuilt from malformed markup, which was already reported.
+    recovered: bool,
 }
 
 impl<'a> From<Pair<'a>> for Tag<'a> {
@@ -486,18 +1039,20 @@ impl<'a> From<Pair<'a>> for Tag<'a> {
             panic!("Only rule Tag can be converted to Tag.");
         }
         let as_str = element.as_str();
-        let mut tag = element
-            .into_inner()
-            .next()
-            .expect("Unwrapping TagInner.")
-            .into_inner();
+        let inner = element.into_inner().next().expect("Unwrapping TagInner.");
+        let code = inner.as_str().trim();
+        let mut tag = inner.into_inner();
         let name = tag.next().expect("A tag starts with an identifier.");
+        let location = location_of(&name);
         let tokens = TagTokenIter::new(&name, tag);
 
         Tag {
             name,
             tokens,
             as_str,
+            code,
+            location,
+            recovered: false,
         }
     }
 }
@@ -507,7 +1062,7 @@ impl<'a> Tag<'a> {
     ///
     /// This is used as a debug tool. It allows to easily build tags in unit tests.
     pub fn new(text: &'a str) -> Result<Self> {
-        let tag = LiquidParser::parse(Rule::Tag, text)
+        let tag = Pairs::parse(Rule::Tag, text)
             .map_err(convert_pest_error)?
             .next()
             .ok_or_else(|| Error::with_msg("Tried to create a Tag from an invalid string."))?;
@@ -538,31 +1093,82 @@ impl<'a> Tag<'a> {
     /// Parses the tag just as if it weren't inside any block.
     pub fn parse(
         self,
-        tag_block: &mut TagBlock,
+        tag_block: &mut TagBlock<'a, '_>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        self.parse_pair(&mut tag_block.iter, options)
+        self.parse_pair(&mut tag_block.iter, tag_block.context, options)
     }
 
     /// The same as `parse`, but directly takes an iterator over `Pair`s instead of a TagBlock.
-    fn parse_pair(
+    fn parse_pair<'n: 'a>(
         self,
-        next_elements: &mut dyn Iterator<Item = Pair>,
+        next_elements: &mut dyn Iterator<Item = Pair<'n>>,
+        context: Context<'n>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        let (name, tokens) = (self.name, self.tokens);
+        let (name, mut tokens, code, location) = (self.name, self.tokens, self.code, self.location);
+        tokens.context = Some(context);
         let position = name.as_span();
+        let rule = name.as_rule();
         let name = name.as_str();
+        let recover = |error: Error| {
+            if self.recovered {
+                Ok(())
+            } else {
+                context.recover(error.location(location.clone()), options)
+            }
+        };
 
-        if let Some(plugin) = options.tags.get(name) {
-            plugin.parse(tokens, options)
+        if rule == Rule::InlineCommentName {
+            // Every line of a multi-line comment is commented out on its own.
+            if code.lines().skip(1).any(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            }) {
+                recover(
+                    Error::with_msg("Each line of comments must be prefixed by the '#' character")
+                        .context("comment", code.to_owned()),
+                )?;
+            }
+            Ok(Box::new(Text::new("")))
+        } else if let Some(plugin) = options.tags.get(name) {
+            let renderable = match plugin.parse(tokens, options) {
+                Ok(renderable) => renderable,
+                Err(error) => {
+                    recover(error)?;
+                    return Ok(skipped());
+                }
+            };
+            Ok(Box::new(
+                Node::new(code, location, renderable).with_tag(name),
+            ))
         } else if let Some(plugin) = options.blocks.get(name) {
+            if options.max_depth <= context.depth {
+                return Error::with_msg("Nesting too deep")
+                    .context("limit", options.max_depth.to_string())
+                    .location(location)
+                    .into_err();
+            }
             let reflection = plugin.reflection();
-            let block = TagBlock::new(reflection.start_tag(), reflection.end_tag(), next_elements);
-            let renderables = plugin.parse(tokens, block, options)?;
-            Ok(renderables)
+            let (start_tag, end_tag) = (reflection.start_tag(), reflection.end_tag());
+            let closed = Cell::new(false);
+            let block = TagBlock::new(start_tag, end_tag, next_elements, &closed, context.nested());
+            let renderable = match plugin.parse(tokens, block, options) {
+                Ok(renderable) => renderable,
+                Err(error) => {
+                    recover(error)?;
+                    if !closed.get() {
+                        TagBlock::new(start_tag, end_tag, next_elements, &closed, context.nested())
+                            .skip(options)?;
+                    }
+                    return Ok(skipped());
+                }
+            };
+            Ok(Box::new(
+                Node::new(code, location, renderable).with_tag(name),
+            ))
         } else {
-            let pest_error = ::pest::error::Error::new_from_span(
+            let error = error_from_span(
                 ::pest::error::ErrorVariant::CustomError {
                     message: "Unknown tag.".to_string(),
                 },
@@ -574,7 +1180,7 @@ impl<'a> Tag<'a> {
             let mut all_blocks: Vec<_> = options.blocks.plugin_names().collect();
             all_blocks.sort_unstable();
             let all_blocks = itertools::join(all_blocks, ", ");
-            let error = convert_pest_error(pest_error)
+            let error = error
                 .context("requested", name.to_owned())
                 .context("available tags", all_tags)
                 .context("available blocks", all_blocks);
@@ -586,6 +1192,9 @@ impl<'a> Tag<'a> {
 /// An element that is an expression.
 pub struct Exp<'a> {
     element: Pair<'a>,
+    location: Location,
+    /// Rebuilt from malformed markup, which was already reported.
+    recovered: bool,
 }
 
 impl<'a> From<Pair<'a>> for Exp<'a> {
@@ -593,24 +1202,50 @@ impl<'a> From<Pair<'a>> for Exp<'a> {
         if element.as_rule() != Rule::Expression {
             panic!("Only rule Expression can be converted to Expression.");
         }
-        Exp { element }
+        let inner = element
+            .clone()
+            .into_inner()
+            .next()
+            .expect("Unwrapping ExpressionInner");
+        let location = location_of(&inner);
+        Exp {
+            element,
+            location,
+            recovered: false,
+        }
     }
 }
 
 impl Exp<'_> {
     /// Parses the expression just as if it weren't inside any block.
     pub fn parse(self, options: &Language) -> Result<Box<dyn Renderable>> {
-        let filter_chain = self
+        let inner = self
             .element
             .into_inner()
             .next()
-            .expect("Unwrapping ExpressionInner")
+            .expect("Unwrapping ExpressionInner");
+        let code = inner.as_str().trim();
+        let location = self.location;
+        let filter_chain = inner
             .into_inner()
             .next()
             .expect("An expression consists of one filterchain.");
 
         let filter_chain = parse_filter_chain(filter_chain, options)?;
-        Ok(Box::new(filter_chain))
+        Ok(Box::new(Node::new(code, location, Box::new(filter_chain))))
+    }
+
+    /// The same as `parse`, but recovering from errors as allowed by the error mode.
+    fn parse_in(self, context: Context<'_>, options: &Language) -> Result<Box<dyn Renderable>> {
+        let recovered = self.recovered;
+        match self.parse(options) {
+            Ok(renderable) => Ok(renderable),
+            Err(_) if recovered => Ok(skipped()),
+            Err(error) => {
+                context.recover(error, options)?;
+                Ok(skipped())
+            }
+        }
     }
 
     /// Returns the expression as a str.
@@ -624,7 +1259,7 @@ impl Exp<'_> {
 pub struct InvalidLiquidToken<'a> {
     element: Pair<'a>,
 }
-impl InvalidLiquidToken<'_> {
+impl<'a> InvalidLiquidToken<'a> {
     /// Returns the expression as a str.
     // TODO consider removing this
     pub fn as_str(&self) -> &str {
@@ -634,7 +1269,8 @@ impl InvalidLiquidToken<'_> {
     /// Tries to parse this as valid liquid, which will inevitably raise an error.
     /// This is needed in order to raise the right error message.
     pub fn parse(self, tag_block: &mut TagBlock) -> Result<Box<dyn Renderable>> {
-        self.parse_pair(&mut tag_block.iter)
+        let context = tag_block.context;
+        self.parse_pair(&mut tag_block.iter, context)
     }
 
     /// Tries to parse this as valid liquid, which will inevitably raise an error.
@@ -642,7 +1278,68 @@ impl InvalidLiquidToken<'_> {
     fn parse_pair(
         self,
         next_elements: &mut dyn Iterator<Item = Pair>,
+        context: Context<'_>,
+    ) -> Result<Box<dyn Renderable>> {
+        let end_position = next_elements
+            .last()
+            .map(|element| element.as_span().end_pos());
+        Err(self.error(end_position, context))
+    }
+
+    /// The same as `parse_pair`, but recovering from the malformed markup as allowed by the
+    /// error mode.
+    fn parse_in(
+        self,
+        next_elements: &mut dyn Iterator<Item = Pair<'a>>,
+        context: Context<'a>,
+        options: &Language,
     ) -> Result<Box<dyn Renderable>> {
+        if options.error_mode == ErrorMode::Strict {
+            return self.parse_pair(next_elements, context);
+        }
+
+        let input = self.element.as_span().get_input();
+        let end_position = pest::Position::new(input, input.len());
+        context.recover(self.error(end_position, context), options)?;
+
+        let location = location_of(&self.element);
+        let markup = self.element.as_str().trim();
+        let (tag_start, tag_end) = options.delimiters.tag();
+        let (output_start, output_end) = options.delimiters.output();
+        if let Some(markup) = strip_delimiters(markup, tag_start, tag_end) {
+            let Some(markup) = sanitize(markup, Rule::TagInner) else {
+                return Ok(skipped());
+            };
+            let text = format!("{{% {markup} %}}");
+            let pair = Pairs::parse(Rule::Tag, &text)
+                .expect("sanitized markup is valid")
+                .next()
+                .expect("Unwrapping Tag");
+            let mut tag = Tag::from(pair);
+            tag.location = location;
+            tag.recovered = true;
+            tag.parse_pair(next_elements, context, options)
+        } else if let Some(markup) = strip_delimiters(markup, output_start, output_end) {
+            let Some(markup) = sanitize(markup, Rule::ConditionChain) else {
+                return Ok(skipped());
+            };
+            let text = format!("{{{{ {markup} }}}}");
+            let pair = Pairs::parse(Rule::Expression, &text)
+                .expect("sanitized markup is valid")
+                .next()
+                .expect("Unwrapping Expression");
+            let mut exp = Exp::from(pair);
+            exp.location = location;
+            exp.recovered = true;
+            exp.parse_in(context, options)
+        } else {
+            // A stray delimiter, like the start of `{{ unclosed`
+            Ok(Box::new(Text::new(self.element.as_str())))
+        }
+    }
+
+    /// Reparse from this token until `end_position` (or the end of the token) to find the error.
+    fn error(&self, end_position: Option<pest::Position<'_>>, context: Context<'_>) -> Error {
         use pest::error::LineColLocation;
 
         let invalid_token_span = self.element.as_span();
@@ -654,17 +1351,15 @@ impl InvalidLiquidToken<'_> {
             .find(|i| invalid_token_position.line_of().is_char_boundary(*i))
             .unwrap_or(0);
 
-        let end_position = match next_elements.last() {
-            Some(element) => element.as_span().end_pos(),
-            None => invalid_token_span.end_pos(),
-        };
+        let end_position = end_position.unwrap_or_else(|| invalid_token_span.end_pos());
 
         let mut text = String::from(&invalid_token_position.line_of()[..offset_c]);
         text.push_str(invalid_token_position.span(&end_position).as_str());
+        let marked = context.delimiters.mark(&text);
 
         // Reparses from the line where invalid liquid started, in order
         // to raise the error.
-        let mut error = match LiquidParser::parse(Rule::LiquidFile, &text) {
+        let mut error = match Pairs::parse_template(Rule::LiquidFile, &text, marked.as_deref()) {
             Ok(_) => panic!("`LiquidParser::parse` should fail in InvalidLiquidTokens."),
             Err(error) => error,
         };
@@ -679,7 +1374,7 @@ impl InvalidLiquidToken<'_> {
             LineColLocation::Pos((ls, cs)) => LineColLocation::Pos((ls + offset_l, cs)),
         };
 
-        Err(convert_pest_error(error))
+        convert_pest_error(error)
     }
 }
 impl<'a> From<Pair<'a>> for InvalidLiquidToken<'a> {
@@ -722,25 +1417,22 @@ impl<'a> BlockElement<'a> {
         block: &mut TagBlock<'a, '_>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        match self {
-            BlockElement::Raw(raw) => Ok(raw.into_renderable()),
-            BlockElement::Tag(tag) => tag.parse(block, options),
-            BlockElement::Expression(exp) => exp.parse(options),
-            BlockElement::Invalid(invalid) => invalid.parse(block),
-        }
+        let context = block.context;
+        self.parse_pair(&mut block.iter, context, options)
     }
 
     /// The same as `parse`, but directly takes an iterator over `Pair`s instead of a TagBlock.
     fn parse_pair(
         self,
-        next_elements: &mut dyn Iterator<Item = Pair>,
+        next_elements: &mut dyn Iterator<Item = Pair<'a>>,
+        context: Context<'a>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
         match self {
-            BlockElement::Raw(raw) => Ok(raw.into_renderable()),
-            BlockElement::Tag(tag) => tag.parse_pair(next_elements, options),
-            BlockElement::Expression(exp) => exp.parse(options),
-            BlockElement::Invalid(invalid) => invalid.parse_pair(next_elements),
+            BlockElement::Raw(raw) => Ok(Box::new(Text::new(raw.trimmed(options)))),
+            BlockElement::Tag(tag) => tag.parse_pair(next_elements, context, options),
+            BlockElement::Expression(exp) => exp.parse_in(context, options),
+            BlockElement::Invalid(invalid) => invalid.parse_in(next_elements, context, options),
         }
     }
 
@@ -759,35 +1451,45 @@ impl<'a> BlockElement<'a> {
 ///
 /// The awareness of the position allows more precise error messages.
 pub struct TagTokenIter<'a> {
-    iter: Box<dyn Iterator<Item = TagToken<'a>> + 'a>,
+    iter: Pairs<'a>,
     position: ::pest::Position<'a>,
+    /// Where the tag is being parsed, unless it was created on its own with `Tag::new`.
+    context: Option<Context<'a>>,
 }
 impl<'a> Iterator for TagTokenIter<'a> {
     type Item = TagToken<'a>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().inspect(|next| {
-            self.position = next.token.as_span().end_pos();
+        let token = self.iter.next()?;
+        self.position = token.as_span().end_pos();
+        Some(TagToken {
+            context: self.context,
+            ..token.into()
         })
     }
 }
 impl<'a> TagTokenIter<'a> {
     fn new(name: &Pair<'a>, tokens: Pairs<'a>) -> Self {
         TagTokenIter {
-            iter: Box::new(tokens.map(TagToken::from)),
+            iter: tokens,
             position: name.as_span().end_pos(),
+            context: None,
         }
     }
 
     /// Creates an error with the given message pointing at the current
     /// position of the iterator.
     pub fn raise_error(&mut self, error_msg: &str) -> Error {
-        let pest_error = ::pest::error::Error::new_from_pos(
+        error_from_span(
             ::pest::error::ErrorVariant::CustomError {
                 message: error_msg.to_string(),
             },
-            self.position,
-        );
-        convert_pest_error(pest_error)
+            self.position.span(&self.position),
+        )
+    }
+
+    /// Returns `true` if there are no tokens left.
+    pub fn is_empty(&mut self) -> bool {
+        self.iter.peek().is_none()
     }
 
     /// Returns the next tag token or raises an error if there is none.
@@ -795,6 +1497,43 @@ impl<'a> TagTokenIter<'a> {
         self.next().ok_or_else(|| self.raise_error(error_msg))
     }
 
+    /// Parses the rest of the tokens as a `FilterChain` that may compare values, like
+    /// `price < compare_at | default: false`, the same as in `{{ }}`.
+    pub fn expect_condition_chain(&mut self, options: &Language) -> Result<FilterChain> {
+        let text = self.remaining_markup("FilterChain expected.")?;
+        let chain = Pairs::parse(Rule::TagConditionChain, &text)
+            .map_err(convert_pest_error)?
+            .next()
+            .expect("Unwrapping ConditionChain");
+        parse_filter_chain(chain, options)
+    }
+
+    /// Parses the rest of the tokens as a `Condition`, with `( )` grouping, `not`, and `and`
+    /// binding tighter than `or`.
+    pub fn expect_condition(&mut self, options: &Language) -> Result<Condition> {
+        let text = self.remaining_markup("Value expected.")?;
+        let condition = Pairs::parse(Rule::TagCondition, &text)
+            .map_err(convert_pest_error)?
+            .next()
+            .expect("Unwrapping Condition");
+        Ok(*into_condition(parse_condition(condition, options)?))
+    }
+
+    /// The markup of the rest of the tokens, laid out so errors keep their line and column
+    /// numbers.
+    fn remaining_markup(&mut self, error_msg: &str) -> Result<String> {
+        let spans: Vec<_> = self.by_ref().map(|token| token.token.as_span()).collect();
+        let (Some(first), Some(last)) = (spans.first(), spans.last()) else {
+            return Err(self.raise_error(error_msg));
+        };
+        let start = first.start_pos();
+        let (line, column) = start.line_col();
+        let mut text = "\n".repeat(line - 1);
+        text.push_str(&" ".repeat(column - 1));
+        text.push_str(start.span(&last.end_pos()).as_str());
+        Ok(text)
+    }
+
     /// Returns `Ok` if the iterator is empty, an error otherwise
     pub fn expect_nothing(&mut self) -> Result<()> {
         if let Some(token) = self.next() {
@@ -834,6 +1573,7 @@ impl<T> TryMatchToken<'_, T> {
 pub struct TagToken<'a> {
     token: Pair<'a>,
     expected: Vec<Rule>,
+    context: Option<Context<'a>>,
 }
 
 impl<'a> From<Pair<'a>> for TagToken<'a> {
@@ -841,6 +1581,7 @@ impl<'a> From<Pair<'a>> for TagToken<'a> {
         TagToken {
             token,
             expected: Vec::new(),
+            context: None,
         }
     }
 }
@@ -856,27 +1597,25 @@ impl<'a> TagToken<'a> {
     /// to give an `Ok` value, calling this would show `Expected Value`
     /// on the error message.
     pub fn raise_error(self) -> Error {
-        let pest_error = ::pest::error::Error::new_from_span(
+        error_from_span(
             ::pest::error::ErrorVariant::ParsingError {
                 positives: self.expected,
                 negatives: vec![self.token.as_rule()],
             },
             self.token.as_span(),
-        );
-        convert_pest_error(pest_error)
+        )
     }
 
     /// Raises an error from this TagToken.
     ///
     /// The error will have the given error message.
     pub fn raise_custom_error(self, msg: &str) -> Error {
-        let pest_error = ::pest::error::Error::new_from_span(
+        error_from_span(
             ::pest::error::ErrorVariant::CustomError {
                 message: msg.to_string(),
             },
             self.token.as_span(),
-        );
-        convert_pest_error(pest_error)
+        )
     }
 
     fn unwrap_filter_chain(&mut self) -> std::result::Result<Pair<'a>, ()> {
@@ -972,7 +1711,7 @@ impl<'a> TagToken<'a> {
     /// In this runtime, value refers to either a literal value or a variable.
     pub fn expect_value(mut self) -> TryMatchToken<'a, Expression> {
         match self.unwrap_value() {
-            Ok(t) => TryMatchToken::Matches(parse_value(t)),
+            Ok(t) => TryMatchToken::Matches(parse_value(t, self.string_escapes())),
             Err(_) => {
                 self.expected.push(Rule::Value);
                 TryMatchToken::Fails(self)
@@ -983,7 +1722,7 @@ impl<'a> TagToken<'a> {
     /// Tries to obtain a `Variable` from this token.
     pub fn expect_variable(mut self) -> TryMatchToken<'a, Variable> {
         match self.unwrap_variable() {
-            Ok(t) => TryMatchToken::Matches(parse_variable_pair(t)),
+            Ok(t) => TryMatchToken::Matches(parse_variable_pair(t, self.string_escapes())),
             Err(_) => {
                 self.expected.push(Rule::Variable);
                 TryMatchToken::Fails(self)
@@ -1009,7 +1748,7 @@ impl<'a> TagToken<'a> {
     /// The value is returned as a `Value`.
     pub fn expect_literal(mut self) -> TryMatchToken<'a, Value> {
         match self.unwrap_literal() {
-            Ok(t) => TryMatchToken::Matches(parse_literal(t)),
+            Ok(t) => TryMatchToken::Matches(parse_literal(t, self.string_escapes())),
             Err(_) => {
                 self.expected.push(Rule::Literal);
                 TryMatchToken::Fails(self)
@@ -1027,10 +1766,11 @@ impl<'a> TagToken<'a> {
             return TryMatchToken::Fails(self);
         }
 
+        let string_escapes = self.string_escapes();
         let mut range = token.into_inner();
         TryMatchToken::Matches((
-            parse_value(range.next().expect("start")),
-            parse_value(range.next().expect("end")),
+            parse_value(range.next().expect("start"), string_escapes),
+            parse_value(range.next().expect("end"), string_escapes),
         ))
     }
 
@@ -1044,10 +1784,61 @@ impl<'a> TagToken<'a> {
         }
     }
 
+    /// Parses the lines of a `{% liquid %}` tag, each line being a tag without its delimiters.
+    ///
+    /// The lines are parsed as part of the template the tag is in, nesting their blocks within
+    /// the tag's and recovering from errors like it.
+    pub fn parse_liquid_markup(self, options: &Language) -> Result<Vec<Box<dyn Renderable>>> {
+        if self.token.as_rule() != Rule::TagMarkup {
+            let mut token = self;
+            token.expected.push(Rule::TagMarkup);
+            return Err(token.raise_error());
+        }
+        let markup = self.token.as_str();
+        if markup.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let (line, column) = self.token.as_span().start_pos().line_col();
+        let text = liquid_markup_to_tags(line, column, markup, options.delimiters.tag());
+        match self.context {
+            Some(context) => parse_in(&text, context, options),
+            None => parse(&text, options),
+        }
+    }
+
     /// Returns token as a str.
     pub fn as_str(&self) -> &str {
         self.token.as_str().trim()
     }
+
+    /// Whether string literals are unescaped, see [`Language::string_escapes`].
+    fn string_escapes(&self) -> bool {
+        self.context.is_some_and(|context| context.string_escapes)
+    }
+}
+
+/// Wraps each line of `markup` in the `(start, end)` tag delimiters, laid out so the tags keep
+/// their line and column numbers.
+fn liquid_markup_to_tags(line: usize, column: usize, markup: &str, tag: (&str, &str)) -> String {
+    let mut text = "\n".repeat(line - 1);
+    text.push_str(&" ".repeat(column.saturating_sub(tag.0.chars().count() + 2)));
+    for (i, markup_line) in markup.split('\n').enumerate() {
+        if markup_line.trim().is_empty() {
+            text.push('\n');
+            text.push_str(markup_line);
+            continue;
+        }
+        // Opening the tag at the end of the previous line keeps the column as-is.
+        text.push_str(tag.0);
+        text.push('-');
+        if 0 < i {
+            text.push('\n');
+        }
+        text.push_str(markup_line);
+        text.push_str(" -");
+        text.push_str(tag.1);
+    }
+    text
 }
 
 #[cfg(test)]
@@ -1057,84 +1848,96 @@ mod test {
 
     #[test]
     fn test_parse_literal() {
-        let nil = LiquidParser::parse(Rule::Literal, "nil")
-            .unwrap()
-            .next()
-            .unwrap();
-        assert_eq!(parse_literal(nil), Value::Nil);
-        let nil = LiquidParser::parse(Rule::Literal, "null")
-            .unwrap()
-            .next()
-            .unwrap();
-        assert_eq!(parse_literal(nil), Value::Nil);
+        let nil = Pairs::parse(Rule::Literal, "nil").unwrap().next().unwrap();
+        assert_eq!(parse_literal(nil, false), Value::Nil);
+        let nil = Pairs::parse(Rule::Literal, "null").unwrap().next().unwrap();
+        assert_eq!(parse_literal(nil, false), Value::Nil);
 
-        let blank = LiquidParser::parse(Rule::Literal, "blank")
+        let blank = Pairs::parse(Rule::Literal, "blank")
             .unwrap()
             .next()
             .unwrap();
         assert_eq!(
-            parse_literal(blank),
+            parse_literal(blank, false),
             Value::State(crate::model::State::Blank)
         );
 
-        let empty = LiquidParser::parse(Rule::Literal, "empty")
+        let empty = Pairs::parse(Rule::Literal, "empty")
             .unwrap()
             .next()
             .unwrap();
         assert_eq!(
-            parse_literal(empty),
+            parse_literal(empty, false),
             Value::State(crate::model::State::Empty)
         );
 
-        let integer = LiquidParser::parse(Rule::Literal, "42")
-            .unwrap()
-            .next()
-            .unwrap();
-        assert_eq!(parse_literal(integer), Value::scalar(42));
+        let integer = Pairs::parse(Rule::Literal, "42").unwrap().next().unwrap();
+        assert_eq!(parse_literal(integer, false), Value::scalar(42));
 
-        let negative_int = LiquidParser::parse(Rule::Literal, "-42")
-            .unwrap()
-            .next()
-            .unwrap();
-        assert_eq!(parse_literal(negative_int), Value::scalar(-42));
+        let negative_int = Pairs::parse(Rule::Literal, "-42").unwrap().next().unwrap();
+        assert_eq!(parse_literal(negative_int, false), Value::scalar(-42));
 
-        let float = LiquidParser::parse(Rule::Literal, "4321.032")
+        let float = Pairs::parse(Rule::Literal, "4321.032")
             .unwrap()
             .next()
             .unwrap();
-        assert_eq!(parse_literal(float), Value::scalar(4321.032));
+        assert_eq!(parse_literal(float, false), Value::scalar(4321.032));
 
-        let negative_float = LiquidParser::parse(Rule::Literal, "-4321.032")
+        let negative_float = Pairs::parse(Rule::Literal, "-4321.032")
             .unwrap()
             .next()
             .unwrap();
-        assert_eq!(parse_literal(negative_float), Value::scalar(-4321.032));
+        assert_eq!(
+            parse_literal(negative_float, false),
+            Value::scalar(-4321.032)
+        );
 
-        let boolean = LiquidParser::parse(Rule::Literal, "true")
-            .unwrap()
-            .next()
-            .unwrap();
-        assert_eq!(parse_literal(boolean), Value::scalar(true));
+        let boolean = Pairs::parse(Rule::Literal, "true").unwrap().next().unwrap();
+        assert_eq!(parse_literal(boolean, false), Value::scalar(true));
 
-        let string_double_quotes = LiquidParser::parse(Rule::Literal, "\"Hello world!\"")
+        let string_double_quotes = Pairs::parse(Rule::Literal, "\"Hello world!\"")
             .unwrap()
             .next()
             .unwrap();
         assert_eq!(
-            parse_literal(string_double_quotes),
+            parse_literal(string_double_quotes, false),
             Value::scalar("Hello world!")
         );
 
-        let string_single_quotes = LiquidParser::parse(Rule::Literal, "'Liquid'")
+        let string_single_quotes = Pairs::parse(Rule::Literal, "'Liquid'")
             .unwrap()
             .next()
             .unwrap();
-        assert_eq!(parse_literal(string_single_quotes), Value::scalar("Liquid"));
+        assert_eq!(
+            parse_literal(string_single_quotes, false),
+            Value::scalar("Liquid")
+        );
+    }
+
+    #[test]
+    fn test_parse_escaped_string_literal() {
+        let render = |text: &str, string_escapes: bool| {
+            let options = Language {
+                string_escapes,
+                ..Default::default()
+            };
+            let template = parse(text, &options).map(Template::new)?;
+            template.render(&RuntimeBuilder::new().build())
+        };
+
+        let text = r#"{{ "a\"b\n\u{1F600}" }}{{ 'c\'\\\t' }}"#;
+        assert_eq!(render(text, true).unwrap(), "a\"b\n\u{1F600}c'\\\t");
+        assert_eq!(render(text, false).unwrap(), r#"a\"b\n\u{1F600}c\'\\\t"#);
+
+        assert_eq!(render(r#"{{ "C:\Users" }}"#, true).unwrap(), "C:\\Users");
+        assert!(render(r#"{{ "C:\" }}"#, false).is_err());
+        assert!(render(r#"{{ "C:\" }}"#, true).is_err());
+        assert_eq!(render(r#"{{ "\u{D800}" }}"#, true).unwrap(), "\u{FFFD}");
     }
 
     #[test]
     fn test_parse_variable_pair() {
-        let variable = LiquidParser::parse(Rule::Variable, "foo[0].bar.baz[foo.bar]")
+        let variable = Pairs::parse(Rule::Variable, "foo[0].bar.baz[foo.bar]")
             .unwrap()
             .next()
             .unwrap();
@@ -1149,7 +1952,126 @@ mod test {
         let mut expected = Variable::with_literal("foo");
         expected.extend(indexes);
 
-        assert_eq!(parse_variable_pair(variable), expected);
+        assert_eq!(parse_variable_pair(variable, false), expected);
+    }
+
+    #[test]
+    fn test_parse_collection_value() {
+        let parse = |text: &str| {
+            let value = Pairs::parse(Rule::Value, text).unwrap().next().unwrap();
+            parse_value(value, false)
+        };
+
+        assert_eq!(
+            parse("[1, 'a']"),
+            Expression::Literal(Value::array([Value::scalar(1), Value::scalar("a")]))
+        );
+        let array = parse("[ 1 , foo ]");
+        assert_eq!(
+            array,
+            Expression::Array(vec![
+                Expression::Literal(Value::scalar(1)),
+                Expression::Variable(Variable::with_literal("foo")),
+            ])
+        );
+        assert_eq!(array.to_string(), "[1, foo]");
+
+        let object = parse(r#"{"a b": foo, c: {}}"#);
+        assert_eq!(
+            object,
+            Expression::Object(vec![
+                (
+                    KString::from_static("a b"),
+                    Expression::Variable(Variable::with_literal("foo"))
+                ),
+                (
+                    KString::from_static("c"),
+                    Expression::Literal(Value::Object(Default::default()))
+                ),
+            ])
+        );
+        assert_eq!(object.to_string(), r#"{"a b": foo, "c": {}}"#);
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        let options = Language {
+            grouped_conditions: true,
+            ..Default::default()
+        };
+        let parse = |text: &str| {
+            let condition = Pairs::parse(Rule::Condition, text).unwrap().next().unwrap();
+            parse_condition(condition, &options).unwrap()
+        };
+
+        assert_eq!(
+            parse("foo"),
+            Expression::Variable(Variable::with_literal("foo"))
+        );
+        assert_eq!(parse("(foo)").to_string(), "foo");
+        assert_eq!(
+            parse("a or b and c == 1").to_string(),
+            "(a or b and c == 1)"
+        );
+        assert_eq!(
+            parse("(a or b) and not c == 1").to_string(),
+            "((a or b) and not c == 1)"
+        );
+        assert_eq!(parse("not not a").to_string(), "not not a");
+        assert_eq!(
+            parse("a contains 'b' or b"),
+            Expression::Condition(Box::new(Condition::Disjunction(
+                Box::new(Condition::Binary(
+                    Expression::Variable(Variable::with_literal("a")),
+                    ComparisonOperator::Contains,
+                    Expression::Literal(Value::scalar("b")),
+                )),
+                Box::new(Condition::Existence(Expression::Variable(
+                    Variable::with_literal("b")
+                ))),
+            )))
+        );
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            sanitize("if true && false", Rule::TagInner).as_deref(),
+            Some("if true  false")
+        );
+        assert_eq!(
+            sanitize("'hi' | split$$$:' ' | first", Rule::ConditionChain).as_deref(),
+            Some("'hi' | split:' ' | first")
+        );
+        assert_eq!(
+            sanitize("|test ||", Rule::ConditionChain).as_deref(),
+            Some("test ")
+        );
+        assert_eq!(sanitize("'%", Rule::TagInner), None);
+
+        let junk = "$".repeat(100_000);
+        assert_eq!(
+            sanitize(&format!("x {junk} | upcase {junk}"), Rule::ConditionChain).as_deref(),
+            Some("x  | upcase ")
+        );
+    }
+
+    #[test]
+    fn test_parse_ungrouped_condition() {
+        let options = Language::default();
+        let parse = |text: &str| {
+            let condition = Pairs::parse(Rule::Condition, text).unwrap().next().unwrap();
+            parse_condition(condition, &options).map(|c| c.to_string())
+        };
+
+        assert_eq!(parse("a and b or c").unwrap(), "(a and (b or c))");
+        assert_eq!(parse("a or b and c == 1").unwrap(), "(a or b and c == 1)");
+        assert_eq!(
+            parse("a == 1 and b or c and d").unwrap(),
+            "(a == 1 and (b or c and d))"
+        );
+        assert!(parse("not a").is_err());
+        assert!(parse("(a or b) and c").is_err());
     }
 
     #[test]
@@ -1184,6 +2106,96 @@ mod test {
         assert_eq!(output, "5");
     }
 
+    #[test]
+    fn test_trim_blocks() {
+        let options = Language {
+            trim_blocks: true,
+            ..Default::default()
+        };
+
+        let runtime = RuntimeBuilder::new().build();
+        runtime.set_global("exp".into(), Value::scalar(5));
+
+        let text = "a\n  {% # c %}\n  {{ exp }}\n{% # c %}\r\nb{% # c +%}\nc{% # c -%}\n\nd";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "a\n    5\nb\ncd");
+    }
+
+    #[test]
+    fn test_lstrip_blocks() {
+        let mut options = Language {
+            lstrip_blocks: true,
+            ..Default::default()
+        };
+
+        let runtime = RuntimeBuilder::new().build();
+        runtime.set_global("exp".into(), Value::scalar(5));
+
+        let text = "  {% # c %}a\n \t{% # c %}\n  {{ exp }}  {% # c %}\n b {% # c %}\n  {%+ # c %}";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "a\n\n  5  \n b \n  ");
+
+        options.trim_blocks = true;
+        let text = "<ul>\n  {% # c %}\n  <li>{{ exp }}</li>\n  {% # c %}\n</ul>";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "<ul>\n  <li>5</li>\n</ul>");
+    }
+
+    #[test]
+    fn test_delimiters() {
+        let options = Language {
+            delimiters: crate::parser::Delimiters::new("<%", "%>", "[[", "]]").unwrap(),
+            ..Default::default()
+        };
+
+        let runtime = RuntimeBuilder::new().build();
+        runtime.set_global("exp".into(), Value::scalar(5));
+
+        let text = "{{ exp }} {% # c %} [[ exp ]] <%# c %>  [[- exp -]]  <%- # c -%> .";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "{{ exp }} {% # c %} 5 5.");
+
+        // The template's own characters never pass for the marks of the delimiters.
+        let text = "\u{1}[[ exp ]]\u{5}\n<% # c %>\u{2}";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "\u{1}5\u{5}\n\u{2}");
+
+        let error = parse("\u{1}\n[[ exp | ]]", &options).unwrap_err();
+        assert_eq!(error.get_location(), Some(&Location::new(2, 10)));
+
+        let error = parse("[[ exp ]] <%", &options).unwrap_err();
+        assert_eq!(error.get_location(), Some(&Location::new(1, 13)));
+
+        runtime.set_global("list".into(), Value::array([Value::scalar(6)]));
+        let text = "[[ list[0]]]";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "6");
+
+        let options = Language {
+            delimiters: crate::parser::Delimiters::new("«%", "%»", "«{", "}»").unwrap(),
+            ..Default::default()
+        };
+        let text = "«%- # c -%» «{ exp }»";
+        let template = parse(text, &options).map(Template::new).unwrap();
+        let output = template.render(&runtime).unwrap();
+
+        assert_eq!(output, "5");
+        let error = parse("«{ exp }»\n«{ exp | }»", &options).unwrap_err();
+        assert_eq!(error.get_location(), Some(&Location::new(2, 10)));
+    }
+
     /// Macro implementation of custom block test.
     macro_rules! test_custom_block_tags_impl {
         ($start_tag:expr, $end_tag:expr) => {{
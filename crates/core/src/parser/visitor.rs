@@ -0,0 +1,40 @@
+use crate::runtime::Expression;
+use crate::runtime::Variable;
+
+use super::Doc;
+use super::FilterCall;
+use super::FilterChain;
+
+/// Callbacks for walking the parse tree of a template, see [`Renderable::accept`].
+///
+/// Nodes are visited in source order, parents before their children.  Every callback defaults
+/// to doing nothing, so implementations only override the nodes they care about.
+///
+/// [`Renderable::accept`]: crate::Renderable::accept
+pub trait ParseTreeVisitor {
+    /// Entering a tag or block, like `{% if ... %}`.
+    fn enter_tag(&mut self, _name: &str) {}
+
+    /// Leaving the most recently entered tag or block.
+    fn leave_tag(&mut self, _name: &str) {}
+
+    /// A filter chain, like `{{ ... }}`.
+    fn visit_filter_chain(&mut self, _chain: &FilterChain) {}
+
+    /// A filter called within a [`FilterChain`].
+    fn visit_filter(&mut self, _filter: &FilterCall) {}
+
+    /// A partial-template being included or rendered.
+    ///
+    /// The expression is visited again through [`ParseTreeVisitor::visit_expression`].
+    fn visit_partial(&mut self, _name: &Expression) {}
+
+    /// A literal or variable.
+    fn visit_expression(&mut self, _expression: &Expression) {}
+
+    /// A variable, including variables used to index into other variables.
+    fn visit_variable(&mut self, _variable: &Variable) {}
+
+    /// Documentation of the template, like `{% doc %}`.
+    fn visit_doc(&mut self, _doc: &Doc) {}
+}
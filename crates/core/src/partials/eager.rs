@@ -1,3 +1,4 @@
+use std::borrow;
 use std::collections::HashMap;
 use std::fmt;
 use std::sync;
@@ -87,15 +88,16 @@ where
             .names()
             .into_iter()
             .map(|name| {
-                let source = self.source.get(name).and_then(|s| {
+                let source = self.source.get(&name).and_then(|s| {
                     parser::parse(s.as_ref(), &language)
+                        .map_err(|e| e.template_name(name.clone().into_owned()))
                         .map(runtime::Template::new)
                         .map(|t| {
                             let t: sync::Arc<dyn runtime::Renderable> = sync::Arc::new(t);
                             t
                         })
                 });
-                (name.to_owned(), source)
+                (name.into_owned(), source)
             })
             .collect();
         let store = EagerStore { store };
@@ -116,8 +118,8 @@ impl PartialStore for EagerStore {
         self.store.contains_key(name)
     }
 
-    fn names(&self) -> Vec<&str> {
-        self.store.keys().map(|s| s.as_str()).collect()
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+        self.store.keys().map(|s| s.as_str().into()).collect()
     }
 
     fn try_get(&self, name: &str) -> Option<sync::Arc<dyn Renderable>> {
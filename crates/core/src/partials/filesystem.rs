@@ -0,0 +1,167 @@
+use std::borrow;
+use std::fs;
+use std::hash::{self, Hash, Hasher};
+use std::path;
+
+use super::PartialSource;
+
+/// Partial-template source code read from one or more directories.
+///
+/// A partial-template's name is a `/`-separated path relative to a root directory.  Its file
+/// name is looked up through each pattern in turn, with `{}` replaced by the last component of
+/// the name; without any pattern, the name is used as-is.  Roots are searched in the order
+/// they were added.
+///
+/// Names that would escape a root, like `../secrets`, are never found.
+///
+/// # Examples
+///
+/// Ruby-style `snippets/_product.liquid` for `{% include 'product' %}`:
+///
+/// ```no_run
+/// let mut source = liquid_core::partials::FileSystemSource::new("snippets");
+/// source.add_pattern("_{}.liquid");
+/// ```
+///
+/// Jekyll-style `_includes/footer.html` for `{% include footer.html %}`:
+///
+/// ```no_run
+/// let source = liquid_core::partials::FileSystemSource::new("_includes");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FileSystemSource {
+    roots: Vec<path::PathBuf>,
+    patterns: Vec<String>,
+}
+
+impl FileSystemSource {
+    /// Create a repository of partial-template source within `root`.
+    pub fn new<P: Into<path::PathBuf>>(root: P) -> Self {
+        let mut source = Self::default();
+        source.add_root(root);
+        source
+    }
+
+    /// Also look for partial-templates within `root`.
+    pub fn add_root<P: Into<path::PathBuf>>(&mut self, root: P) {
+        self.roots.push(root.into());
+    }
+
+    /// Also look for partial-templates named by `pattern`, like `_{}.liquid`.
+    pub fn add_pattern<S: Into<String>>(&mut self, pattern: S) {
+        self.patterns.push(pattern.into());
+    }
+
+    fn patterns(&self) -> impl Iterator<Item = &str> {
+        let default = self.patterns.is_empty().then_some("{}");
+        self.patterns.iter().map(String::as_str).chain(default)
+    }
+
+    fn find(&self, name: &str) -> Option<path::PathBuf> {
+        let name = path::Path::new(name);
+        let is_relative = name
+            .components()
+            .all(|c| matches!(c, path::Component::Normal(_)));
+        if !is_relative {
+            return None;
+        }
+        let file_name = name.file_name()?.to_str()?;
+        let dir = name.parent().unwrap_or_else(|| path::Path::new(""));
+
+        self.patterns()
+            .flat_map(|pattern| {
+                let file_name = pattern.replace("{}", file_name);
+                self.roots
+                    .iter()
+                    .map(move |root| (root, root.join(dir).join(&file_name)))
+            })
+            .find(|(root, path)| path.is_file() && is_within(root, path))
+            .map(|(_, path)| path)
+    }
+
+    fn scan(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for root in &self.roots {
+            let mut files = Vec::new();
+            list_files(root, path::Path::new(""), &mut files);
+            for file in files {
+                for pattern in self.patterns() {
+                    let Some(name) = name_of(&file, pattern) else {
+                        continue;
+                    };
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+            }
+        }
+        names.sort_unstable();
+        names
+    }
+}
+
+impl PartialSource for FileSystemSource {
+    fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+        // Files can be added and removed while the source is in use, so they are listed each time.
+        self.scan().into_iter().map(borrow::Cow::Owned).collect()
+    }
+
+    fn version(&self, name: &str) -> Option<u64> {
+        let metadata = fs::metadata(self.find(name)?).ok()?;
+        let modified = metadata.modified().ok()?;
+        // Include the length for file systems with coarse modification times.
+        let mut hasher = hash::DefaultHasher::new();
+        (modified, metadata.len()).hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+        let path = self.find(name)?;
+        fs::read_to_string(path).ok().map(borrow::Cow::Owned)
+    }
+}
+
+/// Whether `path` stays within `root`, even after following symlinks.
+fn is_within(root: &path::Path, path: &path::Path) -> bool {
+    match (root.canonicalize(), path.canonicalize()) {
+        (Ok(root), Ok(path)) => path.starts_with(root),
+        _ => false,
+    }
+}
+
+/// Collect every file under `root.join(dir)`, relative to `root`.
+fn list_files(root: &path::Path, dir: &path::Path, files: &mut Vec<path::PathBuf>) {
+    let Ok(entries) = fs::read_dir(root.join(dir)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let relative = dir.join(entry.file_name());
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => list_files(root, &relative, files),
+            Ok(file_type) if file_type.is_file() => files.push(relative),
+            _ => {}
+        }
+    }
+}
+
+/// The partial-template name for a `file` relative to a root, if it matches `pattern`.
+fn name_of(file: &path::Path, pattern: &str) -> Option<String> {
+    let (prefix, suffix) = pattern.split_once("{}")?;
+    let file_name = file.file_name()?.to_str()?;
+    let stem = file_name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+    if stem.is_empty() {
+        return None;
+    }
+    let mut components: Vec<_> = file
+        .parent()
+        .into_iter()
+        .flat_map(|dir| dir.components())
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    components.push(stem);
+    Some(components.join("/"))
+}
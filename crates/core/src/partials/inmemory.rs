@@ -30,8 +30,13 @@ impl PartialSource for InMemorySource {
         self.data.contains_key(name)
     }
 
-    fn names(&self) -> Vec<&str> {
-        self.data.keys().map(|s| s.as_str()).collect()
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+        self.data.keys().map(|s| s.as_str().into()).collect()
+    }
+
+    fn version(&self, name: &str) -> Option<u64> {
+        // The source can't be modified once it is compiled.
+        self.contains(name).then_some(0)
     }
 
     fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
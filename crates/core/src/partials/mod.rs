@@ -8,14 +8,18 @@ use crate::parser::Language;
 use crate::runtime::PartialStore;
 
 mod eager;
+mod filesystem;
 mod inmemory;
 mod lazy;
 mod ondemand;
+mod refreshing;
 
 pub use self::eager::*;
+pub use self::filesystem::*;
 pub use self::inmemory::*;
 pub use self::lazy::*;
 pub use self::ondemand::*;
+pub use self::refreshing::*;
 
 /// Compile a `PartialSource` into a `PartialStore` of `Renderable`s.
 ///
@@ -38,7 +42,15 @@ pub trait PartialSource: fmt::Debug {
     fn contains(&self, name: &str) -> bool;
 
     /// Enumerate all partial-templates.
-    fn names(&self) -> Vec<&str>;
+    fn names(&self) -> Vec<borrow::Cow<'_, str>>;
+
+    /// A token that changes whenever a partial-template's source does, like its modification
+    /// time, for [`RefreshingCompiler`] to know when to recompile it.
+    ///
+    /// `None` when unknown, which is the default.
+    fn version(&self, _name: &str) -> Option<u64> {
+        None
+    }
 
     /// Access a partial-template.
     fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>>;
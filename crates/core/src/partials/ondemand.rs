@@ -1,3 +1,4 @@
+use std::borrow;
 use std::fmt;
 use std::sync;
 
@@ -104,7 +105,7 @@ where
         self.source.contains(name)
     }
 
-    fn names(&self) -> Vec<&str> {
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
         self.source.names()
     }
 
@@ -112,6 +113,7 @@ where
         let s = self.source.try_get(name)?;
         let s = s.as_ref();
         let template = parser::parse(s, &self.language)
+            .map_err(|e| e.template_name(name.to_owned()))
             .map(runtime::Template::new)
             .map(sync::Arc::new)
             .ok()?;
@@ -122,6 +124,7 @@ where
         let s = self.source.get(name)?;
         let s = s.as_ref();
         let template = parser::parse(s, &self.language)
+            .map_err(|e| e.template_name(name.to_owned()))
             .map(runtime::Template::new)
             .map(sync::Arc::new)?;
         Ok(template)
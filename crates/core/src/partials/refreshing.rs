@@ -0,0 +1,278 @@
+use std::borrow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync;
+
+use crate::error::Result;
+use crate::parser;
+use crate::parser::Language;
+use crate::runtime;
+use crate::runtime::PartialStore;
+use crate::runtime::Renderable;
+
+use super::PartialCompiler;
+use super::PartialSource;
+
+/// A lazily-caching compiler for `PartialSource` that recompiles partial-templates whose
+/// [`PartialSource::version`] changed.
+///
+/// This would be useful in cases where:
+/// - Partial-templates are edited while the templates using them stay loaded, like in a
+///   development server.
+///
+/// Partial-templates without a version are recompiled on every use.
+///
+/// Note: partial-compilation error reporting is deferred to render-time so content can still be
+/// generated even when the content is in an intermediate-state.
+#[derive(Debug)]
+pub struct RefreshingCompiler<S: PartialSource> {
+    source: S,
+}
+
+impl<S> RefreshingCompiler<S>
+where
+    S: PartialSource,
+{
+    /// Create a refreshing compiler for `PartialSource`.
+    pub fn new(source: S) -> Self {
+        RefreshingCompiler { source }
+    }
+}
+
+impl<S> RefreshingCompiler<S>
+where
+    S: PartialSource + Default,
+{
+    /// Create an empty compiler for `PartialSource`.
+    pub fn empty() -> Self {
+        Default::default()
+    }
+}
+
+impl<S> Default for RefreshingCompiler<S>
+where
+    S: PartialSource + Default,
+{
+    fn default() -> Self {
+        Self {
+            source: Default::default(),
+        }
+    }
+}
+
+impl<S> ::std::ops::Deref for RefreshingCompiler<S>
+where
+    S: PartialSource,
+{
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.source
+    }
+}
+
+impl<S> ::std::ops::DerefMut for RefreshingCompiler<S>
+where
+    S: PartialSource,
+{
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+}
+
+impl<S> PartialCompiler for RefreshingCompiler<S>
+where
+    S: PartialSource + Send + Sync + 'static,
+{
+    fn compile(self, language: sync::Arc<Language>) -> Result<Box<dyn PartialStore + Send + Sync>> {
+        let store = RefreshingStore {
+            language,
+            source: self.source,
+            cache: sync::Mutex::new(Default::default()),
+        };
+        Ok(Box::new(store))
+    }
+
+    fn source(&self) -> &dyn PartialSource {
+        &self.source
+    }
+}
+
+type CacheEntry = (u64, Result<sync::Arc<dyn runtime::Renderable>>);
+
+struct RefreshingStore<S: PartialSource> {
+    language: sync::Arc<Language>,
+    source: S,
+    cache: sync::Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl<S> RefreshingStore<S>
+where
+    S: PartialSource,
+{
+    fn try_get_or_create(&self, name: &str) -> Option<sync::Arc<dyn Renderable>> {
+        // Read the version first so a change racing with the read is caught next time.
+        let version = self.source.version(name);
+        if let Some(result) = self.cached(name, version) {
+            return result.ok();
+        }
+        let s = self.source.try_get(name)?;
+        let template = self.compile(name, s.as_ref());
+        self.insert(name, version, &template);
+        template.ok()
+    }
+
+    fn get_or_create(&self, name: &str) -> Result<sync::Arc<dyn Renderable>> {
+        let version = self.source.version(name);
+        if let Some(result) = self.cached(name, version) {
+            return result;
+        }
+        let s = self.source.get(name)?;
+        let template = self.compile(name, s.as_ref());
+        self.insert(name, version, &template);
+        template
+    }
+
+    /// The cached compilation of `name`, if it is still at `version`.
+    ///
+    /// The cache is only locked to look it up, not while the partial is read and compiled, so
+    /// other partials can be fetched in the meantime.
+    fn cached(
+        &self,
+        name: &str,
+        version: Option<u64>,
+    ) -> Option<Result<sync::Arc<dyn Renderable>>> {
+        let cache = self.cache.lock().expect("not to be poisoned and reused");
+        match cache.get(name) {
+            Some((cached, result)) if Some(*cached) == version => Some(result.clone()),
+            _ => None,
+        }
+    }
+
+    fn insert(
+        &self,
+        name: &str,
+        version: Option<u64>,
+        template: &Result<sync::Arc<dyn Renderable>>,
+    ) {
+        // Unversioned partials are recompiled on every use.
+        if let Some(version) = version {
+            let mut cache = self.cache.lock().expect("not to be poisoned and reused");
+            cache.insert(name.to_owned(), (version, template.clone()));
+        }
+    }
+
+    fn compile(&self, name: &str, s: &str) -> Result<sync::Arc<dyn Renderable>> {
+        parser::parse(s, &self.language)
+            .map_err(|e| e.template_name(name.to_owned()))
+            .map(runtime::Template::new)
+            .map(sync::Arc::new)
+            .map(|t| t as sync::Arc<dyn Renderable>)
+    }
+}
+
+impl<S> PartialStore for RefreshingStore<S>
+where
+    S: PartialSource,
+{
+    fn contains(&self, name: &str) -> bool {
+        self.source.contains(name)
+    }
+
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+        self.source.names()
+    }
+
+    fn try_get(&self, name: &str) -> Option<sync::Arc<dyn Renderable>> {
+        self.try_get_or_create(name)
+    }
+
+    fn get(&self, name: &str) -> Result<sync::Arc<dyn Renderable>> {
+        self.get_or_create(name)
+    }
+}
+
+impl<S> fmt::Debug for RefreshingStore<S>
+where
+    S: PartialSource,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::borrow;
+
+    use crate::runtime::RuntimeBuilder;
+
+    #[derive(Default, Debug)]
+    struct TestSource {
+        version: sync::Mutex<Option<u64>>,
+    }
+
+    impl PartialSource for TestSource {
+        fn contains(&self, _name: &str) -> bool {
+            true
+        }
+
+        fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+            vec![]
+        }
+
+        fn try_get<'a>(&'a self, _name: &str) -> Option<borrow::Cow<'a, str>> {
+            let version = *self.version.lock().unwrap();
+            Some(format!("v{}", version.unwrap_or_default()).into())
+        }
+
+        fn version(&self, _name: &str) -> Option<u64> {
+            *self.version.lock().unwrap()
+        }
+    }
+
+    fn store(version: Option<u64>) -> RefreshingStore<TestSource> {
+        RefreshingStore {
+            language: sync::Arc::new(Language::empty()),
+            source: TestSource {
+                version: sync::Mutex::new(version),
+            },
+            cache: sync::Mutex::new(Default::default()),
+        }
+    }
+
+    fn render(store: &RefreshingStore<TestSource>) -> String {
+        let runtime = RuntimeBuilder::new().build();
+        store.get("example.txt").unwrap().render(&runtime).unwrap()
+    }
+
+    #[test]
+    fn test_store_recompiles_changed_version() {
+        let store = store(Some(1));
+        assert_eq!(render(&store), "v1");
+        let cached = store.get("example.txt").unwrap();
+        assert!(sync::Arc::ptr_eq(
+            &cached,
+            &store.try_get("example.txt").unwrap()
+        ));
+
+        *store.source.version.lock().unwrap() = Some(2);
+        assert_eq!(render(&store), "v2");
+        assert!(!sync::Arc::ptr_eq(
+            &cached,
+            &store.get("example.txt").unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_store_skips_cache_without_version() {
+        let store = store(None);
+        assert_eq!(render(&store), "v0");
+        assert!(
+            store.cache.lock().unwrap().is_empty(),
+            "The store cache should not contain unversioned partials."
+        );
+    }
+}
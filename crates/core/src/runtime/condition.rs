@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::model::{State, ValueView, ValueViewCmp};
+use crate::parser::ParseTreeVisitor;
+
+use super::Expression;
+use super::Runtime;
+
+/// A boolean condition, like `a == b and c`, as evaluated by `{% if %}`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    /// Comparison of two expressions.
+    Binary(Expression, ComparisonOperator, Expression),
+    /// Whether the expression is truthy, unknown variables being falsy.
+    Existence(Expression),
+    /// Negation of the condition.
+    Not(Box<Condition>),
+    /// Both conditions hold.
+    Conjunction(Box<Condition>, Box<Condition>),
+    /// Either condition holds.
+    Disjunction(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    /// Evaluate the condition within `runtime`.
+    pub fn evaluate(&self, runtime: &dyn Runtime) -> Result<bool> {
+        match *self {
+            Condition::Binary(ref lh, ref comparison, ref rh) => {
+                let a = lh.evaluate(runtime)?;
+                let b = rh.evaluate(runtime)?;
+                comparison.compare(a.as_view(), b.as_view())
+            }
+            Condition::Existence(ref lh) => {
+                let a = lh.try_evaluate(runtime);
+                let a = a.unwrap_or_default();
+                Ok(a.query_state(State::Truthy))
+            }
+            Condition::Not(ref c) => Ok(!c.evaluate(runtime)?),
+            Condition::Conjunction(ref left, ref right) => {
+                Ok(left.evaluate(runtime)? && right.evaluate(runtime)?)
+            }
+            Condition::Disjunction(ref left, ref right) => {
+                Ok(left.evaluate(runtime)? || right.evaluate(runtime)?)
+            }
+        }
+    }
+
+    /// Walks the condition, reporting each node to `visitor`.
+    pub fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        match *self {
+            Condition::Binary(ref lh, _, ref rh) => {
+                lh.accept(visitor);
+                rh.accept(visitor);
+            }
+            Condition::Existence(ref lh) => lh.accept(visitor),
+            Condition::Not(ref c) => c.accept(visitor),
+            Condition::Conjunction(ref left, ref right)
+            | Condition::Disjunction(ref left, ref right) => {
+                left.accept(visitor);
+                right.accept(visitor);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Condition::Binary(ref lh, ref comparison, ref rh) => {
+                write!(f, "{lh} {comparison} {rh}")
+            }
+            Condition::Existence(ref lh) => write!(f, "{lh}"),
+            Condition::Not(ref c) => match **c {
+                Condition::Existence(_) | Condition::Not(_) => write!(f, "not {c}"),
+                _ => write!(f, "not ({c})"),
+            },
+            Condition::Conjunction(ref left, ref right) => {
+                fmt_grouped(left, f)?;
+                write!(f, " and ")?;
+                fmt_grouped(right, f)
+            }
+            Condition::Disjunction(ref left, ref right) => write!(f, "{left} or {right}"),
+        }
+    }
+}
+
+/// Formats `c` as an operand of `and`, which binds tighter than `or`.
+fn fmt_grouped(c: &Condition, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match *c {
+        Condition::Disjunction(..) => write!(f, "({c})"),
+        _ => write!(f, "{c}"),
+    }
+}
+
+/// How [`Condition::Binary`] compares its expressions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ComparisonOperator {
+    /// `==`
+    Equals,
+    /// `!=` or `<>`
+    NotEquals,
+    /// `<`
+    LessThan,
+    /// `>`
+    GreaterThan,
+    /// `<=`
+    LessThanEquals,
+    /// `>=`
+    GreaterThanEquals,
+    /// `contains`, for substrings, array elements and object keys.
+    Contains,
+}
+
+impl ComparisonOperator {
+    /// The operator spelled `s`, if any.
+    pub fn from_symbol(s: &str) -> Option<Self> {
+        match s {
+            "==" => Some(ComparisonOperator::Equals),
+            "!=" | "<>" => Some(ComparisonOperator::NotEquals),
+            "<" => Some(ComparisonOperator::LessThan),
+            ">" => Some(ComparisonOperator::GreaterThan),
+            "<=" => Some(ComparisonOperator::LessThanEquals),
+            ">=" => Some(ComparisonOperator::GreaterThanEquals),
+            "contains" => Some(ComparisonOperator::Contains),
+            _ => None,
+        }
+    }
+
+    /// Compare `a` to `b`.
+    pub fn compare(&self, a: &dyn ValueView, b: &dyn ValueView) -> Result<bool> {
+        let ca = ValueViewCmp::new(a);
+        let cb = ValueViewCmp::new(b);
+
+        let result = match *self {
+            ComparisonOperator::Equals => ca == cb,
+            ComparisonOperator::NotEquals => ca != cb,
+            ComparisonOperator::LessThan => ca < cb,
+            ComparisonOperator::GreaterThan => ca > cb,
+            ComparisonOperator::LessThanEquals => ca <= cb,
+            ComparisonOperator::GreaterThanEquals => ca >= cb,
+            ComparisonOperator::Contains => contains_check(a, b)?,
+        };
+
+        Ok(result)
+    }
+}
+
+impl fmt::Display for ComparisonOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let out = match *self {
+            ComparisonOperator::Equals => "==",
+            ComparisonOperator::NotEquals => "!=",
+            ComparisonOperator::LessThanEquals => "<=",
+            ComparisonOperator::GreaterThanEquals => ">=",
+            ComparisonOperator::LessThan => "<",
+            ComparisonOperator::GreaterThan => ">",
+            ComparisonOperator::Contains => "contains",
+        };
+        write!(f, "{out}")
+    }
+}
+
+fn contains_check(a: &dyn ValueView, b: &dyn ValueView) -> Result<bool> {
+    if let Some(a) = a.as_scalar() {
+        let b = b.to_kstr();
+        Ok(a.to_kstr().contains(b.as_str()))
+    } else if let Some(a) = a.as_object() {
+        let b = b.as_scalar();
+        let check = b
+            .map(|b| a.contains_key(b.to_kstr().as_str()))
+            .unwrap_or(false);
+        Ok(check)
+    } else if let Some(a) = a.as_array() {
+        for elem in a.values() {
+            if ValueViewCmp::new(elem) == ValueViewCmp::new(b) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    } else {
+        Err(Error::with_msg(format!(
+            "Expected string | array | object, found `{}`",
+            a.type_name()
+        )))
+    }
+}
@@ -1,11 +1,15 @@
 use std::fmt;
 
 use crate::error::Result;
+use crate::model::KString;
+use crate::model::Object;
 use crate::model::Scalar;
 use crate::model::Value;
 use crate::model::ValueCow;
 use crate::model::ValueView;
+use crate::parser::ParseTreeVisitor;
 
+use super::condition::Condition;
 use super::variable::Variable;
 use super::Runtime;
 
@@ -16,6 +20,12 @@ pub enum Expression {
     Variable(Variable),
     /// Evaluated.
     Literal(Value),
+    /// An array of un-evaluated elements, like `[1, var]`.
+    Array(Vec<Expression>),
+    /// An object of un-evaluated values, like `{"key": var}`.
+    Object(Vec<(KString, Expression)>),
+    /// A comparison or boolean logic, like `a < b`, evaluating to `true` or `false`.
+    Condition(Box<Condition>),
 }
 
 impl Expression {
@@ -28,15 +38,15 @@ impl Expression {
     pub fn into_literal(self) -> Option<Value> {
         match self {
             Expression::Literal(x) => Some(x),
-            Expression::Variable(_) => None,
+            _ => None,
         }
     }
 
     /// Convert into a variable, if possible.
     pub fn into_variable(self) -> Option<Variable> {
         match self {
-            Expression::Literal(_) => None,
             Expression::Variable(x) => Some(x),
+            _ => None,
         }
     }
 
@@ -48,20 +58,88 @@ impl Expression {
                 let path = x.try_evaluate(runtime)?;
                 runtime.try_get(&path)
             }
+            Expression::Array(ref x) => {
+                let array = x
+                    .iter()
+                    .map(|x| x.try_evaluate(runtime).map(|v| v.into_owned()))
+                    .collect::<Option<_>>()?;
+                Some(ValueCow::Owned(Value::Array(array)))
+            }
+            Expression::Object(ref x) => {
+                let object = x
+                    .iter()
+                    .map(|(k, x)| Some((k.clone(), x.try_evaluate(runtime)?.into_owned())))
+                    .collect::<Option<Object>>()?;
+                Some(ValueCow::Owned(Value::Object(object)))
+            }
+            Expression::Condition(ref x) => {
+                let result = x.evaluate(runtime).ok()?;
+                Some(ValueCow::Owned(Value::scalar(result)))
+            }
         }
     }
 
     /// Convert to a `Value`.
+    ///
+    /// Unknown variables evaluate to `nil`, with a warning, when [`Runtime::strict_variables`]
+    /// is off.
     pub fn evaluate<'c>(&'c self, runtime: &'c dyn Runtime) -> Result<ValueCow<'c>> {
         let val = match self {
             Expression::Literal(ref x) => ValueCow::Borrowed(x),
             Expression::Variable(ref x) => {
                 let path = x.evaluate(runtime)?;
-                runtime.get(&path)?
+                match runtime.get(&path) {
+                    Ok(val) => {
+                        if let Some(lazy) = val.as_lazy() {
+                            lazy.try_get()?;
+                        }
+                        val
+                    }
+                    Err(err) if !runtime.strict_variables() => {
+                        runtime.warnings().push(err);
+                        ValueCow::Owned(Value::Nil)
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            Expression::Array(ref x) => {
+                let array = x
+                    .iter()
+                    .map(|x| x.evaluate(runtime).map(|v| v.into_owned()))
+                    .collect::<Result<_>>()?;
+                ValueCow::Owned(Value::Array(array))
             }
+            Expression::Object(ref x) => {
+                let object = x
+                    .iter()
+                    .map(|(k, x)| Ok((k.clone(), x.evaluate(runtime)?.into_owned())))
+                    .collect::<Result<Object>>()?;
+                ValueCow::Owned(Value::Object(object))
+            }
+            Expression::Condition(ref x) => ValueCow::Owned(Value::scalar(x.evaluate(runtime)?)),
         };
         Ok(val)
     }
+
+    /// Walks this expression, reporting each node to `visitor`.
+    pub fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_expression(self);
+        match self {
+            Expression::Variable(x) => x.accept(visitor),
+            Expression::Array(x) => {
+                for x in x {
+                    x.accept(visitor);
+                }
+            }
+            Expression::Object(x) => {
+                for (_, x) in x {
+                    x.accept(visitor);
+                }
+            }
+            Expression::Condition(x) => x.accept(visitor),
+            Expression::Literal(_) => {}
+        }
+    }
 }
 
 impl fmt::Display for Expression {
@@ -69,6 +147,17 @@ impl fmt::Display for Expression {
         match self {
             Expression::Literal(ref x) => write!(f, "{}", x.source()),
             Expression::Variable(ref x) => write!(f, "{}", x),
+            Expression::Array(ref x) => {
+                write!(f, "[{}]", itertools::join(x, ", "))
+            }
+            Expression::Object(ref x) => {
+                let entries = x.iter().map(|(k, x)| format!("{k:?}: {x}"));
+                write!(f, "{{{}}}", itertools::join(entries, ", "))
+            }
+            Expression::Condition(ref x) => match **x {
+                Condition::Existence(_) | Condition::Not(_) => write!(f, "{x}"),
+                _ => write!(f, "({x})"),
+            },
         }
     }
 }
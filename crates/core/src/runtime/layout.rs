@@ -0,0 +1,34 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::model::KString;
+
+/// The layout a template is rendered in, set by tags like `layout`.
+///
+/// Once a layout is set, the rest of the template setting it still renders, so it can fill in the
+/// blocks of the layout, but its output is dropped.  When the template finishes, the layout is
+/// looked up among the partials and rendered in its place.  A layout can set a layout of its own.
+///
+/// Rendering the layouts is up to whoever renders the template, like `liquid::Template`.  Clones
+/// share the layout, so they can keep one to check on it while the template writes its output.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LayoutRegister {
+    layout: Rc<RefCell<Option<KString>>>,
+}
+
+impl LayoutRegister {
+    /// A layout is set.
+    pub fn is_set(&self) -> bool {
+        self.layout.borrow().is_some()
+    }
+
+    /// Sets the layout to render in place of the template. Any previous layout is obliterated.
+    pub fn set(&mut self, layout: KString) {
+        self.layout.borrow_mut().replace(layout);
+    }
+
+    /// Fetches and clears the layout.
+    pub fn reset(&mut self) -> Option<KString> {
+        self.layout.borrow_mut().take()
+    }
+}
@@ -0,0 +1,264 @@
+use std::cell::Cell;
+use std::io::Write;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::model::ValueView;
+
+/// Caps on how much work rendering a template may do.
+///
+/// Every limit is disabled by default, except for [`ResourceLimits::max_depth`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResourceLimits {
+    render_length_limit: Option<usize>,
+    render_score_limit: Option<usize>,
+    assign_score_limit: Option<usize>,
+    max_depth: usize,
+}
+
+impl ResourceLimits {
+    /// Create the default limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of bytes rendered.
+    pub fn with_render_length_limit(mut self, limit: usize) -> Self {
+        self.render_length_limit = Some(limit);
+        self
+    }
+
+    /// Maximum number of nodes rendered, with loop bodies counting once per iteration.
+    pub fn with_render_score_limit(mut self, limit: usize) -> Self {
+        self.render_score_limit = Some(limit);
+        self
+    }
+
+    /// Maximum total size of the values stored by `assign` and `capture`.
+    ///
+    /// Strings count their length in bytes, arrays and objects count 1 plus the size of their
+    /// contents, and everything else counts 1.
+    pub fn with_assign_score_limit(mut self, limit: usize) -> Self {
+        self.assign_score_limit = Some(limit);
+        self
+    }
+
+    /// Maximum nesting of blocks when parsing, and of partial-templates when rendering.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Maximum number of bytes rendered.
+    pub fn render_length_limit(&self) -> Option<usize> {
+        self.render_length_limit
+    }
+
+    /// Maximum number of nodes rendered.
+    pub fn render_score_limit(&self) -> Option<usize> {
+        self.render_score_limit
+    }
+
+    /// Maximum total size of the values stored by `assign` and `capture`.
+    pub fn assign_score_limit(&self) -> Option<usize> {
+        self.assign_score_limit
+    }
+
+    /// Maximum nesting of blocks when parsing, and of partial-templates when rendering.
+    ///
+    /// Defaults to 100.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            render_length_limit: None,
+            render_score_limit: None,
+            assign_score_limit: None,
+            max_depth: 100,
+        }
+    }
+}
+
+/// Resources used so far by a render, checked against its [`ResourceLimits`].
+#[derive(Debug, Default)]
+pub struct ResourceUsage {
+    limits: ResourceLimits,
+    render_length: Cell<usize>,
+    render_score: Cell<usize>,
+    assign_score: Cell<usize>,
+    exceeded: Cell<bool>,
+}
+
+impl ResourceUsage {
+    /// Start tracking a render against `limits`.
+    pub fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// The limits being enforced.
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// Bytes written through [`ResourceUsage::meter`].
+    pub fn render_length(&self) -> usize {
+        self.render_length.get()
+    }
+
+    /// Nodes rendered.
+    pub fn render_score(&self) -> usize {
+        self.render_score.get()
+    }
+
+    /// Total size of the values stored by `assign` and `capture`.
+    pub fn assign_score(&self) -> usize {
+        self.assign_score.get()
+    }
+
+    /// Whether any limit was exceeded, which always stops rendering.
+    pub fn exceeded(&self) -> bool {
+        self.exceeded.get()
+    }
+
+    /// Count the bytes written to `writer` towards the render length.
+    ///
+    /// The limit is enforced by [`ResourceUsage::check_render_length`].
+    pub fn meter<'w>(&'w self, writer: &'w mut dyn Write) -> impl Write + 'w {
+        Meter {
+            writer,
+            render_length: &self.render_length,
+        }
+    }
+
+    /// Fail if more bytes were rendered than allowed.
+    pub fn check_render_length(&self) -> Result<()> {
+        self.check(
+            "Render length limit exceeded",
+            self.render_length.get(),
+            self.limits.render_length_limit,
+        )
+    }
+
+    /// Record `nodes` more nodes being rendered.
+    pub fn increment_render_score(&self, nodes: usize) -> Result<()> {
+        let score = self.render_score.get() + nodes;
+        self.render_score.set(score);
+        self.check(
+            "Render score limit exceeded",
+            score,
+            self.limits.render_score_limit,
+        )
+    }
+
+    /// Fail if rendering `nodes` more nodes would exceed the limit, without recording them.
+    ///
+    /// Lets loops give up before evaluating more iterations than they could render.
+    pub fn check_render_score(&self, nodes: usize) -> Result<()> {
+        self.check(
+            "Render score limit exceeded",
+            self.render_score.get().saturating_add(nodes),
+            self.limits.render_score_limit,
+        )
+    }
+
+    /// Record `value` being stored by `assign` or `capture`.
+    pub fn increment_assign_score(&self, value: &dyn ValueView) -> Result<()> {
+        let score = self.assign_score.get() + assign_score_of(value);
+        self.assign_score.set(score);
+        self.check(
+            "Assign score limit exceeded",
+            score,
+            self.limits.assign_score_limit,
+        )
+    }
+
+    /// Fail if partial-templates are nested `depth` deep, see [`Runtime::depth`].
+    ///
+    /// [`Runtime::depth`]: super::Runtime::depth
+    pub fn check_depth(&self, depth: usize) -> Result<()> {
+        self.check("Nesting too deep", depth, Some(self.limits.max_depth))
+    }
+
+    fn check(&self, msg: &'static str, used: usize, limit: Option<usize>) -> Result<()> {
+        match limit {
+            Some(limit) if limit < used => {
+                self.exceeded.set(true);
+                Error::with_msg(msg)
+                    .context("limit", limit.to_string())
+                    .into_err()
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn assign_score_of(value: &dyn ValueView) -> usize {
+    if let Some(array) = value.as_array() {
+        1 + array.values().map(assign_score_of).sum::<usize>()
+    } else if let Some(object) = value.as_object() {
+        1 + object
+            .iter()
+            .map(|(k, v)| k.len() + assign_score_of(v))
+            .sum::<usize>()
+    } else if value.type_name() == "string" {
+        value.to_kstr().len()
+    } else {
+        1
+    }
+}
+
+struct Meter<'w> {
+    writer: &'w mut dyn Write,
+    render_length: &'w Cell<usize>,
+}
+
+impl Write for Meter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.render_length.set(self.render_length.get() + written);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::model::Value;
+
+    #[test]
+    fn assign_score_counts_nested_values() {
+        let usage = ResourceUsage::new(ResourceLimits::new().with_assign_score_limit(5));
+        let value = Value::Array(vec![
+            Value::scalar("a"),
+            Value::scalar("bb"),
+            Value::scalar(42),
+        ]);
+        usage.increment_assign_score(&value).unwrap();
+        assert_eq!(usage.assign_score(), 5);
+        assert!(usage.increment_assign_score(&Value::Nil).is_err());
+    }
+
+    #[test]
+    fn meter_counts_bytes() {
+        let usage = ResourceUsage::new(ResourceLimits::new().with_render_length_limit(3));
+        let mut buffer = Vec::new();
+        write!(usage.meter(&mut buffer), "abc").unwrap();
+        assert_eq!(usage.render_length(), 3);
+        usage.check_render_length().unwrap();
+        write!(usage.meter(&mut buffer), "d").unwrap();
+        assert!(usage.check_render_length().is_err());
+        assert_eq!(buffer, b"abcd");
+    }
+}
@@ -3,16 +3,28 @@
 #![warn(missing_docs)]
 #![warn(unused_extern_crates)]
 
+mod condition;
 mod expression;
+mod layout;
+mod limits;
 mod partials;
+mod profiler;
+#[cfg(feature = "async")]
+mod render_async;
 mod renderable;
 mod runtime;
 mod stack;
 mod template;
 mod variable;
 
+pub use self::condition::*;
 pub use self::expression::*;
+pub use self::layout::LayoutRegister;
+pub use self::limits::*;
 pub use self::partials::*;
+pub use self::profiler::*;
+#[cfg(feature = "async")]
+pub use self::render_async::*;
 pub use self::renderable::*;
 pub use self::runtime::*;
 pub use self::stack::*;
@@ -1,3 +1,4 @@
+use std::borrow;
 use std::fmt;
 use std::sync;
 
@@ -11,7 +12,7 @@ pub trait PartialStore: fmt::Debug {
     fn contains(&self, name: &str) -> bool;
 
     /// Enumerate all partial-templates.
-    fn names(&self) -> Vec<&str>;
+    fn names(&self) -> Vec<borrow::Cow<'_, str>>;
 
     /// Access a partial-template.
     fn try_get(&self, name: &str) -> Option<sync::Arc<dyn Renderable>>;
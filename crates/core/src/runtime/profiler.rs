@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use crate::model::KString;
+
+/// Render timing for a single node (tag, block, expression) and everything it rendered.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timing {
+    code: Option<KString>,
+    partial: Option<KString>,
+    line_number: Option<usize>,
+    total_time: Duration,
+    children: Vec<Timing>,
+}
+
+impl Timing {
+    /// The source of the node, without delimiters.
+    ///
+    /// `None` for the root of a profile.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Name of the partial-template the node was rendered from.
+    ///
+    /// `None` when rendered from the top-level template.
+    pub fn partial(&self) -> Option<&str> {
+        self.partial.as_deref()
+    }
+
+    /// Line the node starts on within its template, starting at 1.
+    pub fn line_number(&self) -> Option<usize> {
+        self.line_number
+    }
+
+    /// Time spent rendering the node, including its children.
+    pub fn total_time(&self) -> Duration {
+        self.total_time
+    }
+
+    /// Time spent rendering the node, excluding its children.
+    pub fn self_time(&self) -> Duration {
+        let children: Duration = self.children.iter().map(|c| c.total_time).sum();
+        self.total_time.saturating_sub(children)
+    }
+
+    /// Nodes rendered by this node, in render order.
+    pub fn children(&self) -> &[Timing] {
+        &self.children
+    }
+
+    /// Iterate over the nodes rendered by this node.
+    pub fn iter(&self) -> std::slice::Iter<'_, Timing> {
+        self.children.iter()
+    }
+
+    /// Number of nodes rendered by this node.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Whether this node rendered any other nodes.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+impl std::ops::Index<usize> for Timing {
+    type Output = Timing;
+
+    fn index(&self, index: usize) -> &Timing {
+        &self.children[index]
+    }
+}
+
+impl<'t> IntoIterator for &'t Timing {
+    type Item = &'t Timing;
+    type IntoIter = std::slice::Iter<'t, Timing>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Records a tree of [`Timing`]s while rendering.
+#[derive(Debug)]
+pub struct Profiler {
+    started: Instant,
+    stack: RefCell<Vec<(Instant, Timing)>>,
+}
+
+impl Profiler {
+    /// Start profiling a render.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            stack: RefCell::new(vec![(Instant::now(), Timing::default())]),
+        }
+    }
+
+    /// Time `render`, recording it as a child of the node currently being rendered.
+    pub fn profile<T>(
+        &self,
+        code: &str,
+        partial: Option<&str>,
+        line_number: usize,
+        render: impl FnOnce() -> T,
+    ) -> T {
+        self.enter(code, partial, line_number);
+        let result = render();
+        self.leave();
+        result
+    }
+
+    /// Start timing a node, for when [`Profiler::profile`] can't wrap its rendering.
+    pub(crate) fn enter(&self, code: &str, partial: Option<&str>, line_number: usize) {
+        let timing = Timing {
+            code: Some(KString::from_ref(code)),
+            partial: partial.map(KString::from_ref),
+            line_number: Some(line_number),
+            ..Default::default()
+        };
+        self.stack.borrow_mut().push((Instant::now(), timing));
+    }
+
+    /// Stop timing the node last passed to [`Profiler::enter`].
+    pub(crate) fn leave(&self) {
+        let mut stack = self.stack.borrow_mut();
+        let (start, mut timing) = stack.pop().expect("pushed by `enter`");
+        timing.total_time = start.elapsed();
+        stack
+            .last_mut()
+            .expect("root is never popped")
+            .1
+            .children
+            .push(timing);
+    }
+
+    /// Take the timings recorded so far, with the root spanning the entire render.
+    pub fn finish(&self) -> Timing {
+        let mut stack = self.stack.borrow_mut();
+        stack.truncate(1);
+        let (_, root) = &mut stack[0];
+        let mut root = std::mem::take(root);
+        root.total_time = self.started.elapsed();
+        root
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nests_timings() {
+        let profiler = Profiler::new();
+        profiler.profile("outer", None, 1, || {
+            profiler.profile("inner", Some("partial"), 2, || {});
+        });
+        profiler.profile("sibling", None, 3, || {});
+
+        let root = profiler.finish();
+        assert_eq!(root.code(), None);
+        assert_eq!(root.len(), 2);
+        assert_eq!(root[0].code(), Some("outer"));
+        assert_eq!(root[0].len(), 1);
+        assert_eq!(root[0][0].partial(), Some("partial"));
+        assert_eq!(root[0][0].line_number(), Some(2));
+        assert_eq!(root[1].code(), Some("sibling"));
+        assert!(root.total_time() >= root[0].total_time());
+        assert!(root[0].self_time() <= root[0].total_time());
+    }
+}
@@ -0,0 +1,79 @@
+use std::future::Future;
+use std::pin::Pin;
+
+pub use futures_io::AsyncWrite;
+
+use crate::error::{Error, Result, ResultLiquidReplaceExt};
+use crate::model::ValueView;
+use crate::parser::ParseTreeVisitor;
+
+use super::Runtime;
+use super::Variable;
+
+/// The future returned by [`Renderable::render_to_async`].
+///
+/// It isn't `Send` as the [`Runtime`] can't be shared between threads.  Await it on the thread
+/// that created it, for example within a `tokio::task::LocalSet`.
+///
+/// [`Renderable::render_to_async`]: super::Renderable::render_to_async
+pub type RenderFuture<'a> = Pin<Box<dyn Future<Output = Result<()>> + 'a>>;
+
+/// Write all of `buf` to `writer`.
+pub async fn write_all_async(writer: &mut (dyn AsyncWrite + Unpin), mut buf: &[u8]) -> Result<()> {
+    while !buf.is_empty() {
+        let written = std::future::poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, buf))
+            .await
+            .replace("Failed to render")?;
+        if written == 0 {
+            return Error::with_msg("Failed to render")
+                .context("cause", "the writer stopped accepting data")
+                .into_err();
+        }
+        buf = &buf[written..];
+    }
+    Ok(())
+}
+
+/// Await the [`LazyValue`]s a node reads, so rendering it doesn't block on them.
+///
+/// `accept` reports the variables of the node, like [`Renderable::accept`].  Variables that don't
+/// exist yet, like those of a `for` loop, are skipped, as are the keys of drops.
+///
+/// [`LazyValue`]: crate::model::LazyValue
+/// [`Renderable::accept`]: super::Renderable::accept
+pub async fn resolve_lazy_values(
+    accept: impl FnOnce(&mut dyn ParseTreeVisitor),
+    runtime: &dyn Runtime,
+) {
+    let mut variables = VariableCollector::default();
+    accept(&mut variables);
+    // Indexes are reported after the variable they index, and have to be resolved first.
+    for variable in variables.variables.iter().rev() {
+        let Some(path) = variable.try_evaluate(runtime) else {
+            continue;
+        };
+        for end in 1..=path.len() {
+            let Some(value) = runtime.try_get(&path[..end]) else {
+                break;
+            };
+            let value = match value.as_lazy() {
+                Some(lazy) => lazy.resolve().await,
+                None => value.as_view(),
+            };
+            if value.is_drop() {
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct VariableCollector {
+    variables: Vec<Variable>,
+}
+
+impl ParseTreeVisitor for VariableCollector {
+    fn visit_variable(&mut self, variable: &Variable) {
+        self.variables.push(variable.clone());
+    }
+}
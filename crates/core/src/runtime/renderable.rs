@@ -2,8 +2,11 @@ use std::fmt::Debug;
 use std::io::Write;
 
 use crate::error::Result;
+use crate::parser::ParseTreeVisitor;
 
 use super::Runtime;
+#[cfg(feature = "async")]
+use super::{resolve_lazy_values, write_all_async, AsyncWrite, RenderFuture};
 
 /// Any object (tag/block) that can be rendered by liquid must implement this trait.
 pub trait Renderable: Send + Sync + Debug {
@@ -16,4 +19,32 @@ pub trait Renderable: Send + Sync + Debug {
 
     /// Renders the Renderable instance given a Liquid runtime.
     fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()>;
+
+    /// Renders the Renderable instance to an asynchronous `writer`.
+    ///
+    /// By default, the lazy values read by the instance are awaited, see
+    /// [`LazyValue::from_future`], and it is then rendered with [`Renderable::render_to`].
+    /// Blocks override this to render their contents asynchronously, and tags override it to
+    /// await their own data.
+    ///
+    /// [`LazyValue::from_future`]: crate::model::LazyValue::from_future
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.accept(visitor), runtime).await;
+            let mut buffer = Vec::new();
+            self.render_to(&mut runtime.resource_usage().meter(&mut buffer), runtime)?;
+            write_all_async(writer, &buffer).await
+        })
+    }
+
+    /// Walks the parse tree of this Renderable, reporting each node to `visitor`.
+    ///
+    /// Tags and blocks should report the expressions they evaluate and the templates they
+    /// contain.  The default reports nothing.
+    fn accept(&self, _visitor: &mut dyn ParseTreeVisitor) {}
 }
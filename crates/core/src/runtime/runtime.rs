@@ -5,7 +5,9 @@ use crate::error::Result;
 use crate::model::{Object, ObjectView, Scalar, ScalarCow, Value, ValueCow, ValueView};
 
 use super::PartialStore;
+use super::Profiler;
 use super::Renderable;
+use super::{ResourceLimits, ResourceUsage};
 
 /// State for rendering a template
 pub trait Runtime {
@@ -15,12 +17,35 @@ pub trait Runtime {
     /// The name of the currently active template.
     fn name(&self) -> Option<crate::model::KStringRef<'_>>;
 
+    /// Number of named frames, one per partial-template being rendered.
+    fn depth(&self) -> usize {
+        0
+    }
+
     /// All available values
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>>;
     /// Recursively index into the stack.
     fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>>;
     /// Recursively index into the stack.
     fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>>;
+    /// Recursively index into the stack, calling the drops on the way with `runtime`.
+    ///
+    /// `runtime` is the innermost frame the lookup started from, for drops to see its variables.
+    ///
+    /// Defaults to [`Runtime::try_get`], ignoring `runtime`.
+    fn try_get_with(&self, path: &[ScalarCow<'_>], runtime: &dyn Runtime) -> Option<ValueCow<'_>> {
+        let _ = runtime;
+        self.try_get(path)
+    }
+    /// Recursively index into the stack, calling the drops on the way with `runtime`.
+    ///
+    /// `runtime` is the innermost frame the lookup started from, for drops to see its variables.
+    ///
+    /// Defaults to [`Runtime::get`], ignoring `runtime`.
+    fn get_with(&self, path: &[ScalarCow<'_>], runtime: &dyn Runtime) -> Result<ValueCow<'_>> {
+        let _ = runtime;
+        self.get(path)
+    }
 
     /// Sets a value in the global runtime.
     fn set_global(
@@ -36,6 +61,45 @@ pub trait Runtime {
 
     /// Unnamed state for plugins during rendering
     fn registers(&self) -> &Registers;
+
+    /// Unnamed state for plugins shared by the whole render, unlike [`Runtime::registers`] which
+    /// partials rendered in a sandbox don't share.
+    ///
+    /// Defaults to [`Runtime::registers`].
+    fn shared_registers(&self) -> &Registers {
+        self.registers()
+    }
+
+    /// Render timings, when profiling is enabled.
+    fn profiler(&self) -> Option<&Profiler> {
+        None
+    }
+
+    /// Resources used so far, checked against the render's limits.
+    fn resource_usage(&self) -> &ResourceUsage;
+
+    /// Whether an unknown variable is an error, rather than `nil` and a warning.
+    fn strict_variables(&self) -> bool {
+        true
+    }
+
+    /// Non-fatal problems found so far while rendering.
+    fn warnings(&self) -> &Warnings;
+
+    /// Text rendered in place of a tag or expression that fails, recording the error as a
+    /// warning instead of stopping the render.
+    fn error_placeholder(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether each tag and expression has to be tracked while rendering, to profile it, locate
+    /// its warnings or replace its errors with [`Runtime::error_placeholder`].
+    ///
+    /// Defaults to checking [`Runtime::profiler`], [`Runtime::strict_variables`] and
+    /// [`Runtime::error_placeholder`], which implementations can resolve once per render instead.
+    fn tracks_nodes(&self) -> bool {
+        self.profiler().is_some() || !self.strict_variables() || self.error_placeholder().is_some()
+    }
 }
 
 impl<R: Runtime + ?Sized> Runtime for &R {
@@ -47,6 +111,10 @@ impl<R: Runtime + ?Sized> Runtime for &R {
         <R as Runtime>::name(self)
     }
 
+    fn depth(&self) -> usize {
+        <R as Runtime>::depth(self)
+    }
+
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
         <R as Runtime>::roots(self)
     }
@@ -59,6 +127,14 @@ impl<R: Runtime + ?Sized> Runtime for &R {
         <R as Runtime>::get(self, path)
     }
 
+    fn try_get_with(&self, path: &[ScalarCow<'_>], runtime: &dyn Runtime) -> Option<ValueCow<'_>> {
+        <R as Runtime>::try_get_with(self, path, runtime)
+    }
+
+    fn get_with(&self, path: &[ScalarCow<'_>], runtime: &dyn Runtime) -> Result<ValueCow<'_>> {
+        <R as Runtime>::get_with(self, path, runtime)
+    }
+
     fn set_global(
         &self,
         name: crate::model::KString,
@@ -78,12 +154,44 @@ impl<R: Runtime + ?Sized> Runtime for &R {
     fn registers(&self) -> &super::Registers {
         <R as Runtime>::registers(self)
     }
+
+    fn shared_registers(&self) -> &super::Registers {
+        <R as Runtime>::shared_registers(self)
+    }
+
+    fn profiler(&self) -> Option<&Profiler> {
+        <R as Runtime>::profiler(self)
+    }
+
+    fn resource_usage(&self) -> &ResourceUsage {
+        <R as Runtime>::resource_usage(self)
+    }
+
+    fn strict_variables(&self) -> bool {
+        <R as Runtime>::strict_variables(self)
+    }
+
+    fn warnings(&self) -> &Warnings {
+        <R as Runtime>::warnings(self)
+    }
+
+    fn error_placeholder(&self) -> Option<&str> {
+        <R as Runtime>::error_placeholder(self)
+    }
+
+    fn tracks_nodes(&self) -> bool {
+        <R as Runtime>::tracks_nodes(self)
+    }
 }
 
 /// Create processing runtime for a template.
 pub struct RuntimeBuilder<'g, 'p> {
     globals: Option<&'g dyn ObjectView>,
     partials: Option<&'p dyn PartialStore>,
+    profiling: bool,
+    resource_limits: ResourceLimits,
+    strict_variables: bool,
+    error_placeholder: Option<crate::model::KString>,
 }
 
 impl<'c, 'g: 'c, 'p: 'c> RuntimeBuilder<'g, 'p> {
@@ -92,6 +200,10 @@ impl<'c, 'g: 'c, 'p: 'c> RuntimeBuilder<'g, 'p> {
         Self {
             globals: None,
             partials: None,
+            profiling: false,
+            resource_limits: ResourceLimits::new(),
+            strict_variables: true,
+            error_placeholder: None,
         }
     }
 
@@ -100,6 +212,10 @@ impl<'c, 'g: 'c, 'p: 'c> RuntimeBuilder<'g, 'p> {
         RuntimeBuilder {
             globals: Some(values),
             partials: self.partials,
+            profiling: self.profiling,
+            resource_limits: self.resource_limits,
+            strict_variables: self.strict_variables,
+            error_placeholder: self.error_placeholder,
         }
     }
 
@@ -108,14 +224,52 @@ impl<'c, 'g: 'c, 'p: 'c> RuntimeBuilder<'g, 'p> {
         RuntimeBuilder {
             globals: self.globals,
             partials: Some(values),
+            profiling: self.profiling,
+            resource_limits: self.resource_limits,
+            strict_variables: self.strict_variables,
+            error_placeholder: self.error_placeholder,
         }
     }
 
+    /// Record render timings, available through [`Runtime::profiler`].
+    pub fn set_profiling(mut self, profiling: bool) -> Self {
+        self.profiling = profiling;
+        self
+    }
+
+    /// Stop rendering with an error once any of `limits` is exceeded.
+    pub fn set_resource_limits(mut self, limits: ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Render unknown variables as `nil`, recording a warning, instead of failing.
+    ///
+    /// Variables are strict by default.
+    pub fn set_strict_variables(mut self, strict: bool) -> Self {
+        self.strict_variables = strict;
+        self
+    }
+
+    /// Render `placeholder` in place of any tag or expression that fails, recording the error in
+    /// [`Runtime::warnings`] instead of stopping the render.
+    ///
+    /// Exceeding a [`ResourceLimits`] still stops the render.
+    pub fn set_error_placeholder<S: Into<crate::model::KString>>(mut self, placeholder: S) -> Self {
+        self.error_placeholder = Some(placeholder.into());
+        self
+    }
+
     /// Create the `Runtime`.
     pub fn build(self) -> impl Runtime + 'c {
         let partials = self.partials.unwrap_or(&NullPartials);
+        let profiler = self.profiling.then(Profiler::new);
         let runtime = RuntimeCore {
             partials,
+            profiler,
+            resource_usage: ResourceUsage::new(self.resource_limits),
+            strict_variables: self.strict_variables,
+            error_placeholder: self.error_placeholder,
             ..Default::default()
         };
         let runtime = super::IndexFrame::new(runtime);
@@ -208,6 +362,16 @@ pub struct RuntimeCore<'g> {
     partials: &'g dyn PartialStore,
 
     registers: Registers,
+
+    profiler: Option<Profiler>,
+
+    resource_usage: ResourceUsage,
+
+    strict_variables: bool,
+
+    warnings: Warnings,
+
+    error_placeholder: Option<crate::model::KString>,
 }
 
 impl RuntimeCore<'_> {
@@ -233,16 +397,32 @@ impl Runtime for RuntimeCore<'_> {
         None
     }
 
+    fn depth(&self) -> usize {
+        0
+    }
+
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
         // Indexes don't count
         std::collections::BTreeSet::new()
     }
 
-    fn try_get(&self, _path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
-        None
+    fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
+        self.try_get_with(path, self)
     }
 
     fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>> {
+        self.get_with(path, self)
+    }
+
+    fn try_get_with(
+        &self,
+        _path: &[ScalarCow<'_>],
+        _runtime: &dyn Runtime,
+    ) -> Option<ValueCow<'_>> {
+        None
+    }
+
+    fn get_with(&self, path: &[ScalarCow<'_>], _runtime: &dyn Runtime) -> Result<ValueCow<'_>> {
         let key = path.first().cloned().unwrap_or_else(|| Scalar::new("nil"));
         Error::with_msg("Unknown variable")
             .context("requested variable", key.to_kstr())
@@ -268,6 +448,34 @@ impl Runtime for RuntimeCore<'_> {
     fn registers(&self) -> &Registers {
         &self.registers
     }
+
+    fn shared_registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    fn resource_usage(&self) -> &ResourceUsage {
+        &self.resource_usage
+    }
+
+    fn strict_variables(&self) -> bool {
+        self.strict_variables
+    }
+
+    fn warnings(&self) -> &Warnings {
+        &self.warnings
+    }
+
+    fn error_placeholder(&self) -> Option<&str> {
+        self.error_placeholder.as_deref()
+    }
+
+    fn tracks_nodes(&self) -> bool {
+        self.profiler.is_some() || !self.strict_variables || self.error_placeholder.is_some()
+    }
 }
 
 impl Default for RuntimeCore<'_> {
@@ -275,6 +483,11 @@ impl Default for RuntimeCore<'_> {
         Self {
             partials: &NullPartials,
             registers: Default::default(),
+            profiler: None,
+            resource_usage: Default::default(),
+            strict_variables: true,
+            warnings: Default::default(),
+            error_placeholder: None,
         }
     }
 }
@@ -304,6 +517,46 @@ impl Default for Registers {
     }
 }
 
+/// Non-fatal problems found while rendering, like unknown variables when
+/// [`Runtime::strict_variables`] is off, or errors replaced by [`Runtime::error_placeholder`].
+#[derive(Debug, Default)]
+pub struct Warnings {
+    warnings: std::cell::RefCell<Vec<Error>>,
+}
+
+impl Warnings {
+    /// Record a problem and keep rendering.
+    pub fn push(&self, warning: Error) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    /// Number of problems recorded so far.
+    pub fn len(&self) -> usize {
+        self.warnings.borrow().len()
+    }
+
+    /// Whether no problems were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.borrow().is_empty()
+    }
+
+    /// Attribute the problems recorded since the first `start` to `location`, unless their
+    /// location is already known.
+    pub fn locate(&self, start: usize, location: &crate::error::Location) {
+        let mut warnings = self.warnings.borrow_mut();
+        for warning in warnings.iter_mut().skip(start) {
+            if warning.get_location().is_none() {
+                *warning = warning.clone().location(location.clone());
+            }
+        }
+    }
+
+    /// Remove and return every recorded problem.
+    pub fn take(&self) -> Vec<Error> {
+        self.warnings.take()
+    }
+}
+
 /// The current interrupt state. The interrupt state is used by
 /// the `break` and `continue` tags to halt template rendering
 /// at a given point and unwind the `render` call stack until
@@ -348,7 +601,7 @@ impl PartialStore for NullPartials {
         false
     }
 
-    fn names(&self) -> Vec<&str> {
+    fn names(&self) -> Vec<std::borrow::Cow<'_, str>> {
         Vec::new()
     }
 
@@ -407,4 +660,71 @@ mod test {
             &ValueViewCmp::new(&"some value")
         );
     }
+
+    /// Only implements the methods without a default.
+    struct MinimalRuntime<R>(R);
+
+    impl<R: Runtime> Runtime for MinimalRuntime<R> {
+        fn partials(&self) -> &dyn PartialStore {
+            self.0.partials()
+        }
+
+        fn name(&self) -> Option<crate::model::KStringRef<'_>> {
+            self.0.name()
+        }
+
+        fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
+            self.0.roots()
+        }
+
+        fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
+            self.0.try_get(path)
+        }
+
+        fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>> {
+            self.0.get(path)
+        }
+
+        fn set_global(&self, name: crate::model::KString, val: Value) -> Option<Value> {
+            self.0.set_global(name, val)
+        }
+
+        fn set_index(&self, name: crate::model::KString, val: Value) -> Option<Value> {
+            self.0.set_index(name, val)
+        }
+
+        fn get_index<'a>(&'a self, name: &str) -> Option<ValueCow<'a>> {
+            self.0.get_index(name)
+        }
+
+        fn registers(&self) -> &Registers {
+            self.0.registers()
+        }
+
+        fn resource_usage(&self) -> &ResourceUsage {
+            self.0.resource_usage()
+        }
+
+        fn warnings(&self) -> &Warnings {
+            self.0.warnings()
+        }
+    }
+
+    #[test]
+    fn default_methods() {
+        let test_path = [Scalar::new("test")];
+
+        let rt = MinimalRuntime(RuntimeBuilder::new().build());
+        rt.set_global("test".into(), Value::scalar(42f64));
+        assert_eq!(
+            &rt.get_with(&test_path, &rt).unwrap(),
+            &ValueViewCmp::new(&42f64)
+        );
+        assert!(rt.try_get_with(&[Scalar::new("missing")], &rt).is_none());
+        assert_eq!(rt.depth(), 0);
+        assert!(std::ptr::eq(rt.shared_registers(), rt.registers()));
+        assert!(rt.profiler().is_none());
+        assert!(rt.strict_variables());
+        assert_eq!(rt.error_placeholder(), None);
+    }
 }
@@ -40,6 +40,10 @@ impl<P: super::Runtime, O: ObjectView> super::Runtime for StackFrame<P, O> {
             .or_else(|| self.parent.name())
     }
 
+    fn depth(&self) -> usize {
+        self.parent.depth() + usize::from(self.name.is_some())
+    }
+
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
         let mut roots = self.parent.roots();
         roots.extend(self.data.keys());
@@ -47,26 +51,42 @@ impl<P: super::Runtime, O: ObjectView> super::Runtime for StackFrame<P, O> {
     }
 
     fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
+        self.try_get_with(path, self)
+    }
+
+    fn try_get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Option<ValueCow<'_>> {
         let key = path.first()?;
         let key = key.to_kstr();
         let data = &self.data;
         if data.contains_key(key.as_str()) {
-            crate::model::try_find(data.as_value(), path)
+            crate::model::try_find_with(data.as_value(), path, runtime)
         } else {
-            self.parent.try_get(path)
+            self.parent.try_get_with(path, runtime)
         }
     }
 
     fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>> {
+        self.get_with(path, self)
+    }
+
+    fn get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Result<ValueCow<'_>> {
         let key = path.first().ok_or_else(|| {
             Error::with_msg("Unknown variable").context("requested variable", "nil")
         })?;
         let key = key.to_kstr();
         let data = &self.data;
         if data.contains_key(key.as_str()) {
-            crate::model::find(data.as_value(), path).map(|v| v.into_owned().into())
+            crate::model::find_with(data.as_value(), path, runtime).map(|v| v.into_owned().into())
         } else {
-            self.parent.get(path)
+            self.parent.get_with(path, runtime)
         }
     }
 
@@ -89,6 +109,34 @@ impl<P: super::Runtime, O: ObjectView> super::Runtime for StackFrame<P, O> {
     fn registers(&self) -> &super::Registers {
         self.parent.registers()
     }
+
+    fn shared_registers(&self) -> &super::Registers {
+        self.parent.shared_registers()
+    }
+
+    fn profiler(&self) -> Option<&super::Profiler> {
+        self.parent.profiler()
+    }
+
+    fn resource_usage(&self) -> &super::ResourceUsage {
+        self.parent.resource_usage()
+    }
+
+    fn strict_variables(&self) -> bool {
+        self.parent.strict_variables()
+    }
+
+    fn warnings(&self) -> &super::Warnings {
+        self.parent.warnings()
+    }
+
+    fn error_placeholder(&self) -> Option<&str> {
+        self.parent.error_placeholder()
+    }
+
+    fn tracks_nodes(&self) -> bool {
+        self.parent.tracks_nodes()
+    }
 }
 
 /// A stack frame that only provides a sandboxed set of globals
@@ -116,6 +164,10 @@ impl<P: super::Runtime> super::Runtime for GlobalFrame<P> {
         self.parent.name()
     }
 
+    fn depth(&self) -> usize {
+        self.parent.depth()
+    }
+
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
         let mut roots = self.parent.roots();
         roots.extend(self.data.borrow().keys().map(|k| k.clone().into()));
@@ -123,26 +175,43 @@ impl<P: super::Runtime> super::Runtime for GlobalFrame<P> {
     }
 
     fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
+        self.try_get_with(path, self)
+    }
+
+    fn try_get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Option<ValueCow<'_>> {
         let key = path.first()?;
         let key = key.to_kstr();
         let data = self.data.borrow();
         if data.contains_key(key.as_str()) {
-            crate::model::try_find(data.as_value(), path).map(|v| v.into_owned().into())
+            crate::model::try_find_with(data.as_value(), path, runtime)
+                .map(|v| v.into_owned().into())
         } else {
-            self.parent.try_get(path)
+            self.parent.try_get_with(path, runtime)
         }
     }
 
     fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>> {
+        self.get_with(path, self)
+    }
+
+    fn get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Result<ValueCow<'_>> {
         let key = path.first().ok_or_else(|| {
             Error::with_msg("Unknown variable").context("requested variable", "nil")
         })?;
         let key = key.to_kstr();
         let data = self.data.borrow();
         if data.contains_key(key.as_str()) {
-            crate::model::find(data.as_value(), path).map(|v| v.into_owned().into())
+            crate::model::find_with(data.as_value(), path, runtime).map(|v| v.into_owned().into())
         } else {
-            self.parent.get(path)
+            self.parent.get_with(path, runtime)
         }
     }
 
@@ -166,6 +235,34 @@ impl<P: super::Runtime> super::Runtime for GlobalFrame<P> {
     fn registers(&self) -> &super::Registers {
         self.parent.registers()
     }
+
+    fn shared_registers(&self) -> &super::Registers {
+        self.parent.shared_registers()
+    }
+
+    fn profiler(&self) -> Option<&super::Profiler> {
+        self.parent.profiler()
+    }
+
+    fn resource_usage(&self) -> &super::ResourceUsage {
+        self.parent.resource_usage()
+    }
+
+    fn strict_variables(&self) -> bool {
+        self.parent.strict_variables()
+    }
+
+    fn warnings(&self) -> &super::Warnings {
+        self.parent.warnings()
+    }
+
+    fn error_placeholder(&self) -> Option<&str> {
+        self.parent.error_placeholder()
+    }
+
+    fn tracks_nodes(&self) -> bool {
+        self.parent.tracks_nodes()
+    }
 }
 
 pub(crate) struct IndexFrame<P> {
@@ -191,6 +288,10 @@ impl<P: super::Runtime> super::Runtime for IndexFrame<P> {
         self.parent.name()
     }
 
+    fn depth(&self) -> usize {
+        self.parent.depth()
+    }
+
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
         let mut roots = self.parent.roots();
         roots.extend(self.data.borrow().keys().map(|k| k.clone().into()));
@@ -198,26 +299,43 @@ impl<P: super::Runtime> super::Runtime for IndexFrame<P> {
     }
 
     fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
+        self.try_get_with(path, self)
+    }
+
+    fn try_get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Option<ValueCow<'_>> {
         let key = path.first()?;
         let key = key.to_kstr();
         let data = self.data.borrow();
         if data.contains_key(key.as_str()) {
-            crate::model::try_find(data.as_value(), path).map(|v| v.into_owned().into())
+            crate::model::try_find_with(data.as_value(), path, runtime)
+                .map(|v| v.into_owned().into())
         } else {
-            self.parent.try_get(path)
+            self.parent.try_get_with(path, runtime)
         }
     }
 
     fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>> {
+        self.get_with(path, self)
+    }
+
+    fn get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Result<ValueCow<'_>> {
         let key = path.first().ok_or_else(|| {
             Error::with_msg("Unknown variable").context("requested variable", "nil")
         })?;
         let key = key.to_kstr();
         let data = self.data.borrow();
         if data.contains_key(key.as_str()) {
-            crate::model::find(data.as_value(), path).map(|v| v.into_owned().into())
+            crate::model::find_with(data.as_value(), path, runtime).map(|v| v.into_owned().into())
         } else {
-            self.parent.get(path)
+            self.parent.get_with(path, runtime)
         }
     }
 
@@ -241,6 +359,34 @@ impl<P: super::Runtime> super::Runtime for IndexFrame<P> {
     fn registers(&self) -> &super::Registers {
         self.parent.registers()
     }
+
+    fn shared_registers(&self) -> &super::Registers {
+        self.parent.shared_registers()
+    }
+
+    fn profiler(&self) -> Option<&super::Profiler> {
+        self.parent.profiler()
+    }
+
+    fn resource_usage(&self) -> &super::ResourceUsage {
+        self.parent.resource_usage()
+    }
+
+    fn strict_variables(&self) -> bool {
+        self.parent.strict_variables()
+    }
+
+    fn warnings(&self) -> &super::Warnings {
+        self.parent.warnings()
+    }
+
+    fn error_placeholder(&self) -> Option<&str> {
+        self.parent.error_placeholder()
+    }
+
+    fn tracks_nodes(&self) -> bool {
+        self.parent.tracks_nodes()
+    }
 }
 
 /// A [`StackFrame`] where variables are not recursively searched for,
@@ -282,6 +428,10 @@ impl<P: super::Runtime, O: ObjectView> super::Runtime for SandboxedStackFrame<P,
             .or_else(|| self.parent.name())
     }
 
+    fn depth(&self) -> usize {
+        self.parent.depth() + usize::from(self.name.is_some())
+    }
+
     fn roots(&self) -> std::collections::BTreeSet<crate::model::KStringCow<'_>> {
         let mut roots = std::collections::BTreeSet::new();
         roots.extend(self.data.keys());
@@ -289,21 +439,37 @@ impl<P: super::Runtime, O: ObjectView> super::Runtime for SandboxedStackFrame<P,
     }
 
     fn try_get(&self, path: &[ScalarCow<'_>]) -> Option<ValueCow<'_>> {
+        self.try_get_with(path, self)
+    }
+
+    fn try_get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Option<ValueCow<'_>> {
         let key = path.first()?;
         let key = key.to_kstr();
         let data = &self.data;
         data.get(key.as_str())
-            .and_then(|_| crate::model::try_find(data.as_value(), path))
+            .and_then(|_| crate::model::try_find_with(data.as_value(), path, runtime))
     }
 
     fn get(&self, path: &[ScalarCow<'_>]) -> Result<ValueCow<'_>> {
+        self.get_with(path, self)
+    }
+
+    fn get_with(
+        &self,
+        path: &[ScalarCow<'_>],
+        runtime: &dyn super::Runtime,
+    ) -> Result<ValueCow<'_>> {
         let key = path.first().ok_or_else(|| {
             Error::with_msg("Unknown variable").context("requested variable", "nil")
         })?;
         let key = key.to_kstr();
         let data = &self.data;
         data.get(key.as_str())
-            .and_then(|_| crate::model::try_find(data.as_value(), path))
+            .and_then(|_| crate::model::try_find_with(data.as_value(), path, runtime))
             .map(|v| v.into_owned().into())
             .ok_or_else(|| Error::with_msg("Unknown variable").context("requested variable", key))
     }
@@ -327,6 +493,34 @@ impl<P: super::Runtime, O: ObjectView> super::Runtime for SandboxedStackFrame<P,
     fn registers(&self) -> &super::Registers {
         &self.registers
     }
+
+    fn shared_registers(&self) -> &super::Registers {
+        self.parent.shared_registers()
+    }
+
+    fn profiler(&self) -> Option<&super::Profiler> {
+        self.parent.profiler()
+    }
+
+    fn resource_usage(&self) -> &super::ResourceUsage {
+        self.parent.resource_usage()
+    }
+
+    fn strict_variables(&self) -> bool {
+        self.parent.strict_variables()
+    }
+
+    fn warnings(&self) -> &super::Warnings {
+        self.parent.warnings()
+    }
+
+    fn error_placeholder(&self) -> Option<&str> {
+        self.parent.error_placeholder()
+    }
+
+    fn tracks_nodes(&self) -> bool {
+        self.parent.tracks_nodes()
+    }
 }
 
 #[cfg(test)]
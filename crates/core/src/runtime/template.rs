@@ -1,9 +1,12 @@
 use std::io::Write;
 
-use crate::error::Result;
+use crate::error::{Result, ResultLiquidReplaceExt};
+use crate::parser::ParseTreeVisitor;
 
 use super::Renderable;
 use super::Runtime;
+#[cfg(feature = "async")]
+use super::{AsyncWrite, RenderFuture};
 
 /// An executable template block.
 #[derive(Debug)]
@@ -16,12 +19,34 @@ impl Template {
     pub fn new(elements: Vec<Box<dyn Renderable>>) -> Template {
         Template { elements }
     }
-}
 
-impl Renderable for Template {
-    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+    /// Whether the block has no elements to render.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Renders the template like [`Renderable::render_to`], flushing `writer` as each element
+    /// finishes so the output can be streamed.
+    ///
+    /// Tags can also flush `writer` to pass on what they rendered so far.
+    pub fn stream_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        self.render_elements(writer, runtime, true)
+    }
+
+    fn render_elements(
+        &self,
+        writer: &mut dyn Write,
+        runtime: &dyn Runtime,
+        flush: bool,
+    ) -> Result<()> {
+        let resource_usage = runtime.resource_usage();
+        resource_usage.increment_render_score(self.elements.len())?;
         for el in &self.elements {
             el.render_to(writer, runtime)?;
+            resource_usage.check_render_length()?;
+            if flush {
+                writer.flush().replace("Failed to render")?;
+            }
 
             // Did the last element we processed set an interrupt? If so, we
             // need to abandon the rest of our child elements and just
@@ -38,3 +63,41 @@ impl Renderable for Template {
         Ok(())
     }
 }
+
+impl Renderable for Template {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        self.render_elements(writer, runtime, false)
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let resource_usage = runtime.resource_usage();
+            resource_usage.increment_render_score(self.elements.len())?;
+            for el in &self.elements {
+                el.render_to_async(&mut *writer, runtime).await?;
+                resource_usage.check_render_length()?;
+
+                // See `render_elements` for `break` and `continue`.
+                if runtime
+                    .registers()
+                    .get_mut::<super::InterruptRegister>()
+                    .interrupted()
+                {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        for el in &self.elements {
+            el.accept(visitor);
+        }
+    }
+}
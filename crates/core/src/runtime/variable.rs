@@ -4,6 +4,7 @@ use crate::error::{Error, Result};
 use crate::model::Path;
 use crate::model::Scalar;
 use crate::model::{ValueCow, ValueView};
+use crate::parser::ParseTreeVisitor;
 
 use super::Expression;
 use super::Runtime;
@@ -24,6 +25,16 @@ impl Variable {
         }
     }
 
+    /// The variable being indexed into.
+    pub fn name(&self) -> &Scalar {
+        &self.variable
+    }
+
+    /// Indexes into the variable, like `[0]` or `.name`.
+    pub fn indexes(&self) -> &[Expression] {
+        &self.indexes
+    }
+
     /// Append a literal.
     pub fn push_literal<S: Into<Scalar>>(mut self, value: S) -> Self {
         self.indexes.push(Expression::with_literal(value));
@@ -65,6 +76,14 @@ impl Variable {
         }
         Ok(path)
     }
+
+    /// Walks this variable, reporting each node to `visitor`.
+    pub fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_variable(self);
+        for index in &self.indexes {
+            index.accept(visitor);
+        }
+    }
 }
 
 impl Extend<Scalar> for Variable {
@@ -0,0 +1,236 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned as _;
+use syn::{Attribute, Data, DeriveInput, Error, Path, Result};
+
+use crate::helpers::{assign_path, AssignOnce};
+
+/// A method listed in `#[drop(methods(...))]`.
+struct DropMethod {
+    name: Ident,
+    runtime: bool,
+    cache: bool,
+}
+
+/// Struct that contains information parsed in `#[drop(...)]` attributes.
+struct DropMeta {
+    methods: Vec<DropMethod>,
+    method_missing: Option<Path>,
+    cache: Option<Ident>,
+}
+
+impl DropMeta {
+    /// Searches for `#[drop(...)]` on the struct and its fields.
+    fn from_input(input: &DeriveInput) -> Result<Self> {
+        let fields = match &input.data {
+            Data::Struct(data) => &data.fields,
+            Data::Enum(data) => {
+                return Err(Error::new_spanned(
+                    data.enum_token,
+                    "Enums cannot be DropView.",
+                ))
+            }
+            Data::Union(data) => {
+                return Err(Error::new_spanned(
+                    data.union_token,
+                    "Unions cannot be DropView.",
+                ))
+            }
+        };
+
+        let mut methods = Vec::new();
+        let mut method_missing = AssignOnce::Unset;
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("drop"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("methods") {
+                    meta.parse_nested_meta(|meta| {
+                        methods.push(parse_method(attr, &meta)?);
+                        Ok(())
+                    })
+                } else if meta.path.is_ident("method_missing") {
+                    assign_path(&mut method_missing, attr, "method_missing", &meta)
+                } else {
+                    Err(unknown_attribute(attr, &meta))
+                }
+            })?;
+        }
+
+        let mut cache = AssignOnce::Unset;
+        for field in fields {
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("drop"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("cache") {
+                        let ident = field.ident.clone().ok_or_else(|| {
+                            Error::new(field.span(), "the cache must be a named field.")
+                        })?;
+                        cache.set(ident, || {
+                            Error::new(attr.span(), "attribute `cache` was already specified.")
+                        })
+                    } else {
+                        Err(unknown_attribute(attr, &meta))
+                    }
+                })?;
+            }
+        }
+        let cache = cache.into_option();
+
+        if let Some(method) = methods.iter().find(|method| method.cache) {
+            if cache.is_none() {
+                return Err(Error::new(
+                    method.name.span(),
+                    "caching needs a `DropCache` field marked with `#[drop(cache)]`.",
+                ));
+            }
+        }
+
+        Ok(Self {
+            methods,
+            method_missing: method_missing.into_option(),
+            cache,
+        })
+    }
+}
+
+/// Parses `name` or `name(runtime, cache)` within `methods(...)`.
+fn parse_method(attr: &Attribute, meta: &syn::meta::ParseNestedMeta<'_>) -> Result<DropMethod> {
+    let name = meta
+        .path
+        .get_ident()
+        .cloned()
+        .ok_or_else(|| Error::new(meta.path.span(), "expected a method name."))?;
+    let mut method = DropMethod {
+        name,
+        runtime: false,
+        cache: false,
+    };
+    if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("runtime") {
+                method.runtime = true;
+            } else if meta.path.is_ident("cache") {
+                method.cache = true;
+            } else {
+                return Err(unknown_attribute(attr, &meta));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(method)
+}
+
+fn unknown_attribute(attr: &Attribute, meta: &syn::meta::ParseNestedMeta<'_>) -> Error {
+    Error::new(
+        attr.span(),
+        format!("unknown `{}` drop attribute", meta.path.to_token_stream()),
+    )
+}
+
+pub(crate) fn derive(input: &DeriveInput) -> TokenStream {
+    let meta = match DropMeta::from_input(input) {
+        Ok(meta) => meta,
+        Err(err) => return err.to_compile_error(),
+    };
+    let DeriveInput {
+        ident, generics, ..
+    } = input;
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = meta.methods.iter().map(|method| {
+        let name = &method.name;
+        let key = name.to_string();
+        let call = if method.runtime {
+            quote! { self.#name(runtime) }
+        } else {
+            quote! { self.#name() }
+        };
+        let value = quote! { ::liquid::ValueView::to_value(&#call) };
+        match (method.cache, &meta.cache) {
+            (true, Some(cache)) => quote! {
+                #key => self.#cache.get_or_try_insert_with(#key, || Ok(#value)).map(Some),
+            },
+            _ => quote! {
+                #key => Ok(Some(#value)),
+            },
+        }
+    });
+
+    let method_missing = meta.method_missing.map(|method_missing| {
+        quote! {
+            fn liquid_method_missing(
+                &self,
+                name: &str,
+                runtime: &dyn ::liquid::Runtime,
+            ) -> ::std::result::Result<Option<::liquid::model::Value>, ::liquid::Error> {
+                Ok(self
+                    .#method_missing(name, runtime)
+                    .map(|value| ::liquid::ValueView::to_value(&value)))
+            }
+        }
+    });
+
+    quote! {
+        impl #impl_generics ::liquid::ValueView for #ident #ty_generics #where_clause {
+            fn as_debug(&self) -> &dyn ::std::fmt::Debug {
+                self
+            }
+
+            fn render(&self) -> ::liquid::model::DisplayCow<'_> {
+                ::liquid::model::DisplayCow::Borrowed(&stringify!(#ident))
+            }
+            fn source(&self) -> ::liquid::model::DisplayCow<'_> {
+                ::liquid::model::DisplayCow::Borrowed(&stringify!(#ident))
+            }
+            fn type_name(&self) -> &'static str {
+                "drop"
+            }
+            fn query_state(&self, state: ::liquid::model::State) -> bool {
+                match state {
+                    ::liquid::model::State::Truthy => true,
+                    ::liquid::model::State::DefaultValue |
+                    ::liquid::model::State::Empty |
+                    ::liquid::model::State::Blank => false,
+                }
+            }
+
+            fn to_kstr(&self) -> ::liquid::model::KStringCow<'_> {
+                ::liquid::model::KStringCow::from_static(stringify!(#ident))
+            }
+            fn to_value(&self) -> ::liquid::model::Value {
+                ::liquid::model::Value::from_drop(::std::clone::Clone::clone(self))
+            }
+
+            fn as_drop(&self) -> Option<&dyn ::liquid::model::DropView> {
+                Some(self)
+            }
+        }
+
+        impl #impl_generics ::liquid::model::DropView for #ident #ty_generics #where_clause {
+            fn as_value(&self) -> &dyn ::liquid::ValueView {
+                self
+            }
+
+            fn invoke(
+                &self,
+                name: &str,
+                runtime: &dyn ::liquid::Runtime,
+            ) -> ::std::result::Result<Option<::liquid::model::Value>, ::liquid::Error> {
+                let _ = runtime;
+                match name {
+                    #(#arms)*
+                    _ => Ok(None),
+                }
+            }
+
+            #method_missing
+        }
+    }
+}
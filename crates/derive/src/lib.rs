@@ -1,5 +1,6 @@
 //! Derive macros to aid in filter creation.
 
+mod drop_view;
 mod filter;
 mod filter_parameters;
 pub(crate) mod helpers;
@@ -271,3 +272,54 @@ pub fn derive_object_view(item: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(item as syn::DeriveInput);
     object_view::derive(&input).into()
 }
+
+/// Implements `DropView`, along with `ValueView`, exposing the listed methods to templates.
+///
+/// The struct must be `Clone + Send + Sync + 'static`, as it is cloned into a `Value` when
+/// assigned.
+///
+/// Methods are listed in `#[drop(methods(...))]`, each one called when a template reads the key
+/// of the same name.  They take `&self` and return any `ValueView`, like `String`, `Vec<i64>` or
+/// another drop.  Options may follow a method's name:
+///     - `runtime` -> the method also takes the `&dyn Runtime` rendering the template.
+///     - `cache` -> the method runs at most once, its result kept in the `DropCache` field
+/// marked with `#[drop(cache)]`.
+///
+/// `#[drop(method_missing(NAME))]` answers the other keys with the method `NAME`, taking the key
+/// and the `&dyn Runtime`, and returning `Option<impl ValueView>`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Clone, Debug, DropView)]
+/// #[drop(methods(title, related(runtime), inventory(cache)))]
+/// #[drop(method_missing(metafield))]
+/// struct ProductDrop {
+///     product: Arc<Product>,
+///     #[drop(cache)]
+///     cache: DropCache,
+/// }
+///
+/// impl ProductDrop {
+///     fn title(&self) -> String {
+///         // ...
+///     }
+///
+///     fn related(&self, runtime: &dyn Runtime) -> Vec<ProductDrop> {
+///         // ...
+///     }
+///
+///     fn inventory(&self) -> i64 {
+///         // ...
+///     }
+///
+///     fn metafield(&self, key: &str, runtime: &dyn Runtime) -> Option<String> {
+///         // ...
+///     }
+/// }
+/// ```
+#[proc_macro_derive(DropView, attributes(drop))]
+pub fn derive_drop_view(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    drop_view::derive(&input).into()
+}
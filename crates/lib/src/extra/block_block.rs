@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use liquid_core::error::ResultLiquidExt;
+use liquid_core::model::{DisplayCow, DropView, KString, KStringCow, State, Value, ValueView};
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
+use liquid_core::runtime::{LayoutRegister, StackFrame};
+use liquid_core::Language;
+use liquid_core::Object;
+use liquid_core::Renderable;
+use liquid_core::Result;
+use liquid_core::Runtime;
+use liquid_core::Template;
+use liquid_core::{BlockReflection, ParseBlock, TagBlock, TagTokenIter};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BlockBlock;
+
+impl BlockBlock {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl BlockReflection for BlockBlock {
+    fn start_tag(&self) -> &str {
+        "block"
+    }
+
+    fn end_tag(&self) -> &str {
+        "endblock"
+    }
+
+    fn description(&self) -> &str {
+        "mark a section that templates rendered in this layout can replace, see `layout`"
+    }
+}
+
+impl ParseBlock for BlockBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        mut tokens: TagBlock<'_, '_>,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let name: KString = arguments
+            .expect_next("Identifier expected")?
+            .expect_identifier()
+            .into_result()?
+            .to_owned()
+            .into();
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let template = Template::new(
+            tokens
+                .parse_all(options)
+                .trace_with(|| format!("{{% block {name} %}}").into())?,
+        );
+
+        tokens.assert_empty();
+        Ok(Box::new(Block {
+            name,
+            template: Arc::new(template),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+/// The blocks of the templates rendered in a layout, the most derived first.
+#[derive(Debug, Default)]
+struct BlockRegister {
+    overrides: HashMap<KString, Vec<Arc<Template>>>,
+}
+
+#[derive(Debug)]
+struct Block {
+    name: KString,
+    template: Arc<Template>,
+}
+
+impl Block {
+    /// Returns the block to render, unless the template is rendered in a layout.
+    fn block(&self, runtime: &dyn Runtime) -> Option<BlockObject> {
+        if runtime.registers().get_mut::<LayoutRegister>().is_set() {
+            // The template is rendered in a layout, which renders the block in its place.
+            let mut register = runtime.registers().get_mut::<BlockRegister>();
+            let overrides = register.overrides.entry(self.name.clone()).or_default();
+            if !overrides.iter().any(|o| Arc::ptr_eq(o, &self.template)) {
+                overrides.push(self.template.clone());
+            }
+            return None;
+        }
+
+        let mut templates = runtime
+            .registers()
+            .get_mut::<BlockRegister>()
+            .overrides
+            .get(&self.name)
+            .cloned()
+            .unwrap_or_default();
+        templates.push(self.template.clone());
+        Some(BlockObject {
+            name: self.name.clone(),
+            templates,
+        })
+    }
+}
+
+impl Renderable for Block {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        match self.block(runtime) {
+            Some(block) => block.render_to(writer, runtime),
+            None => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let Some(block) = self.block(runtime) else {
+                return Ok(());
+            };
+            // `block.super` renders the replaced blocks synchronously.
+            resolve_lazy_values(
+                |visitor| {
+                    for template in &block.templates[1..] {
+                        template.accept(visitor);
+                    }
+                },
+                runtime,
+            )
+            .await;
+            let scope = block.scope();
+            let scope = StackFrame::new(runtime, &scope);
+            block.templates[0]
+                .render_to_async(writer, &scope)
+                .await
+                .trace_with(|| block.trace().into())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.template.accept(visitor);
+    }
+}
+
+/// The `block` variable within a block, rendering the block it replaced as `block.super`.
+#[derive(Clone, Debug)]
+struct BlockObject {
+    name: KString,
+    /// The block being rendered, followed by those it replaced.
+    templates: Vec<Arc<Template>>,
+}
+
+impl BlockObject {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let scope = self.scope();
+        let scope = StackFrame::new(runtime, &scope);
+        self.templates[0]
+            .render_to(writer, &scope)
+            .trace_with(|| self.trace().into())
+    }
+
+    fn scope(&self) -> Object {
+        let mut scope = Object::new();
+        scope.insert("block".into(), Value::from_drop(self.clone()));
+        scope
+    }
+
+    fn trace(&self) -> String {
+        format!("{{% block {} %}}", self.name)
+    }
+
+    fn parent(&self) -> Option<Self> {
+        (self.templates.len() > 1).then(|| Self {
+            name: self.name.clone(),
+            templates: self.templates[1..].to_vec(),
+        })
+    }
+}
+
+impl ValueView for BlockObject {
+    fn as_debug(&self) -> &dyn std::fmt::Debug {
+        self
+    }
+
+    fn render(&self) -> DisplayCow<'_> {
+        DisplayCow::Owned(Box::new(self.name.clone()))
+    }
+    fn source(&self) -> DisplayCow<'_> {
+        DisplayCow::Owned(Box::new(self.name.clone()))
+    }
+    fn type_name(&self) -> &'static str {
+        "drop"
+    }
+    fn query_state(&self, state: State) -> bool {
+        match state {
+            State::Truthy => true,
+            State::DefaultValue | State::Empty | State::Blank => false,
+        }
+    }
+
+    fn to_kstr(&self) -> KStringCow<'_> {
+        KStringCow::from_ref(self.name.as_str())
+    }
+    fn to_value(&self) -> Value {
+        Value::from_drop(self.clone())
+    }
+
+    fn as_drop(&self) -> Option<&dyn DropView> {
+        Some(self)
+    }
+}
+
+impl DropView for BlockObject {
+    fn as_value(&self) -> &dyn ValueView {
+        self
+    }
+
+    fn invoke(&self, name: &str, runtime: &dyn Runtime) -> Result<Option<Value>> {
+        match name {
+            "name" => Ok(Some(Value::scalar(self.name.clone()))),
+            "super" => {
+                let Some(parent) = self.parent() else {
+                    return Ok(Some(Value::Nil));
+                };
+                let mut rendered = Vec::new();
+                parent.render_to(&mut rendered, runtime)?;
+                let rendered = String::from_utf8(rendered).expect("render only writes UTF-8");
+                Ok(Some(Value::scalar(rendered)))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options
+            .blocks
+            .register("block".to_owned(), BlockBlock.into());
+        options
+    }
+
+    #[test]
+    fn test_block_renders_in_place() {
+        let text = "<h1>{% block title %}{{ title }}{% endblock %}</h1>";
+        let options = options();
+        let template = parser::parse(text, &options).map(Template::new).unwrap();
+
+        let rt = RuntimeBuilder::new().build();
+        rt.set_global("title".into(), Value::scalar("Home"));
+
+        let output = template.render(&rt).unwrap();
+        assert_eq!(output, "<h1>Home</h1>");
+    }
+
+    #[test]
+    fn test_block_without_super() {
+        let text = "{% block title %}[{{ block.super }}]{{ block.name }}{% endblock %}";
+        let options = options();
+        let template = parser::parse(text, &options).map(Template::new).unwrap();
+
+        let rt = RuntimeBuilder::new().build();
+        let output = template.render(&rt).unwrap();
+        assert_eq!(output, "[]title");
+    }
+
+    #[test]
+    fn test_block_name_required() {
+        let options = options();
+        parser::parse("{% block %}{% endblock %}", &options).unwrap_err();
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use liquid_core::error::ResultLiquidExt;
+use liquid_core::model::{KString, Value, ValueView};
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{AsyncWrite, RenderFuture};
+use liquid_core::Language;
+use liquid_core::Renderable;
+use liquid_core::Result;
+use liquid_core::Runtime;
+use liquid_core::Template;
+use liquid_core::{BlockReflection, ParseBlock, TagBlock, TagTokenIter};
+use liquid_core::{Display_filter, Filter, FilterReflection, ParseFilter};
+
+/// Append the rendered content to a named slot, for `{% yield %}` to output.
+///
+/// Slots are shared by the whole render, including partials rendered with `render` and the
+/// layout of the page.  `{% yield %}` only outputs what was appended before it.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ContentForBlock;
+
+impl ContentForBlock {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl BlockReflection for ContentForBlock {
+    fn start_tag(&self) -> &str {
+        "content_for"
+    }
+
+    fn end_tag(&self) -> &str {
+        "endcontent_for"
+    }
+
+    fn description(&self) -> &str {
+        "append the rendered content to a named slot, output with `yield`"
+    }
+}
+
+impl ParseBlock for ContentForBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        mut tokens: TagBlock<'_, '_>,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let slot = arguments
+            .expect_next("Identifier expected")?
+            .expect_identifier()
+            .into_result()?
+            .to_owned()
+            .into();
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let template = Template::new(
+            tokens
+                .parse_all(options)
+                .trace_with(|| format!("{{% content_for {slot} %}}").into())?,
+        );
+
+        tokens.assert_empty();
+        Ok(Box::new(ContentForSlot { slot, template }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+/// The content appended to each slot during a render, shared by all frames and partials.
+#[derive(Debug, Default)]
+pub(crate) struct ContentForRegister {
+    slots: HashMap<KString, String>,
+}
+
+impl ContentForRegister {
+    /// The content of `slot`, `None` if nothing was appended to it.
+    pub(crate) fn get(&self, slot: &str) -> Option<&str> {
+        self.slots.get(slot).map(String::as_str)
+    }
+
+    fn append(&mut self, slot: &KString, content: &str) {
+        self.slots
+            .entry(slot.clone())
+            .or_default()
+            .push_str(content);
+    }
+}
+
+#[derive(Debug)]
+struct ContentForSlot {
+    slot: KString,
+    template: Template,
+}
+
+impl ContentForSlot {
+    fn trace(&self) -> String {
+        format!("{{% content_for {} %}}", self.slot)
+    }
+
+    fn append(&self, captured: Vec<u8>, runtime: &dyn Runtime) -> Result<()> {
+        let output = String::from_utf8(captured).expect("render only writes UTF-8");
+        runtime
+            .resource_usage()
+            .increment_assign_score(&output)
+            .trace_with(|| self.trace().into())?;
+        runtime
+            .shared_registers()
+            .get_mut::<ContentForRegister>()
+            .append(&self.slot, &output);
+        Ok(())
+    }
+}
+
+impl Renderable for ContentForSlot {
+    fn render_to(&self, _writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let mut captured = Vec::new();
+        self.template
+            .render_to(&mut captured, runtime)
+            .trace_with(|| self.trace().into())?;
+        self.append(captured, runtime)
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        _writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let mut captured = Vec::new();
+            self.template
+                .render_to_async(&mut captured, runtime)
+                .await
+                .trace_with(|| self.trace().into())?;
+            self.append(captured, runtime)
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.template.accept(visitor);
+    }
+}
+
+#[derive(Clone, ParseFilter, FilterReflection)]
+#[filter(
+    name = "content_for",
+    description = "Returns the content appended to the named slot with `content_for`, or nil if there is none.",
+    parsed(ContentForFilter)
+)]
+pub struct ContentFor;
+
+#[derive(Debug, Default, Display_filter)]
+#[name = "content_for"]
+struct ContentForFilter;
+
+impl Filter for ContentForFilter {
+    fn evaluate(&self, input: &dyn ValueView, runtime: &dyn Runtime) -> Result<Value> {
+        let slot = input.to_kstr();
+        let register = runtime.shared_registers().get_mut::<ContentForRegister>();
+        let content = register
+            .get(slot.as_str())
+            .map(|content| Value::scalar(content.to_owned()));
+        Ok(content.unwrap_or(Value::Nil))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    use crate::stdlib;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options
+            .blocks
+            .register("content_for".to_owned(), ContentForBlock.into());
+        options
+            .blocks
+            .register("for".to_owned(), stdlib::ForBlock.into());
+        options
+    }
+
+    #[test]
+    fn test_content_for_appends() {
+        let text = concat!(
+            "{% content_for scripts %}<script src=\"a.js\">{% endcontent_for %}",
+            "body",
+            "{% for i in (1..2) %}{% content_for scripts %}<script src=\"{{ i }}.js\">{% endcontent_for %}{% endfor %}",
+        );
+        let options = options();
+        let template = parser::parse(text, &options).map(Template::new).unwrap();
+
+        let rt = RuntimeBuilder::new().build();
+        let output = template.render(&rt).unwrap();
+        assert_eq!(output, "body");
+        assert_eq!(
+            rt.shared_registers()
+                .get_mut::<ContentForRegister>()
+                .get("scripts"),
+            Some("<script src=\"a.js\"><script src=\"1.js\"><script src=\"2.js\">")
+        );
+        assert_eq!(
+            rt.shared_registers()
+                .get_mut::<ContentForRegister>()
+                .get("head"),
+            None
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::io::Write;
+
+use liquid_core::parser::ParseTreeVisitor;
+use liquid_core::runtime::LayoutRegister;
+use liquid_core::Expression;
+use liquid_core::Language;
+use liquid_core::Renderable;
+use liquid_core::ValueView;
+use liquid_core::{Error, Result, Runtime};
+use liquid_core::{ParseTag, TagReflection, TagTokenIter};
+
+/// Render the template in a layout partial, replacing the layout's `{% block %}`s with its own.
+///
+/// The template's output after the tag is dropped, so it goes first.  Within a block,
+/// `{{ block.super }}` renders the block it replaces.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LayoutTag;
+
+impl LayoutTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for LayoutTag {
+    fn tag(&self) -> &'static str {
+        "layout"
+    }
+
+    fn description(&self) -> &'static str {
+        "render this template in another, filling in its blocks"
+    }
+}
+
+impl ParseTag for LayoutTag {
+    fn parse(
+        &self,
+        arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        parse_layout(arguments, "layout")
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ExtendsTag;
+
+impl ExtendsTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for ExtendsTag {
+    fn tag(&self) -> &'static str {
+        "extends"
+    }
+
+    fn description(&self) -> &'static str {
+        "render this template in another, filling in its blocks, like `layout`"
+    }
+}
+
+impl ParseTag for ExtendsTag {
+    fn parse(
+        &self,
+        arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        parse_layout(arguments, "extends")
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+fn parse_layout(mut arguments: TagTokenIter<'_>, tag: &'static str) -> Result<Box<dyn Renderable>> {
+    let layout = arguments
+        .expect_next("Identifier or literal expected.")?
+        .expect_value()
+        .into_result()?;
+
+    // no more arguments should be supplied, trying to supply them is an error
+    arguments.expect_nothing()?;
+
+    Ok(Box::new(Layout { tag, layout }))
+}
+
+#[derive(Debug)]
+struct Layout {
+    tag: &'static str,
+    layout: Expression,
+}
+
+impl Renderable for Layout {
+    fn render_to(&self, _writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let value = self.layout.evaluate(runtime)?;
+        if !value.is_scalar() {
+            return Error::with_msg(format!("Can only `{}` strings", self.tag))
+                .context("partial", format!("{}", value.source()))
+                .into_err();
+        }
+        let name = value.to_kstr().into_owned();
+        runtime.registers().get_mut::<LayoutRegister>().set(name);
+        Ok(())
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_partial(&self.layout);
+        self.layout.accept(visitor);
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+use liquid_core::error::ResultLiquidExt;
+use liquid_core::model::{KString, Value};
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::AsyncWrite;
+use liquid_core::runtime::{GlobalFrame, SandboxedStackFrame};
+use liquid_core::Expression;
+use liquid_core::Language;
+use liquid_core::Object;
+use liquid_core::Renderable;
+use liquid_core::Runtime;
+use liquid_core::Template;
+use liquid_core::{BlockReflection, ParseBlock, TagBlock, TagTokenIter};
+use liquid_core::{Error, Result};
+
+/// Define a macro, a template rendered by `{% call %}` with the arguments as its only variables.
+///
+/// Macros can be called once defined, including from the partials rendered afterwards and, with
+/// `{% import %}`, from the templates importing the partial defining them.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MacroBlock;
+
+impl MacroBlock {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl BlockReflection for MacroBlock {
+    fn start_tag(&self) -> &str {
+        "macro"
+    }
+
+    fn end_tag(&self) -> &str {
+        "endmacro"
+    }
+
+    fn description(&self) -> &str {
+        "define a template to render with `call`, like `{% macro badge(price, label: 'Sale') %}`"
+    }
+}
+
+impl ParseBlock for MacroBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        mut tokens: TagBlock<'_, '_>,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let name: KString = arguments
+            .expect_next("Identifier expected")?
+            .expect_identifier()
+            .into_result()?
+            .to_owned()
+            .into();
+
+        let mut params: Vec<(KString, Option<Expression>)> = Vec::new();
+        if let Some(open) = arguments.next() {
+            open.expect_str("(")
+                .into_result_custom_msg("expected \"(\" to start the parameters")?;
+            loop {
+                let token = arguments.expect_next("\")\" expected.")?;
+                if token.as_str() == ")" {
+                    break;
+                }
+                let param: KString = token.expect_identifier().into_result()?.to_owned().into();
+                if params.iter().any(|(p, _)| *p == param) {
+                    return Error::with_msg("Duplicate parameter")
+                        .context("parameter", param)
+                        .into_err();
+                }
+
+                let mut token = arguments.expect_next("\")\" expected.")?;
+                let default = if token.as_str() == ":" {
+                    let default = arguments
+                        .expect_next("expected value")?
+                        .expect_value()
+                        .into_result()?;
+                    token = arguments.expect_next("\")\" expected.")?;
+                    Some(default)
+                } else {
+                    None
+                };
+                params.push((param, default));
+
+                if token.as_str() == ")" {
+                    break;
+                }
+                token
+                    .expect_str(",")
+                    .into_result_custom_msg("expected \",\" or \")\" after a parameter")?;
+            }
+        }
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let template = Template::new(
+            tokens
+                .parse_all(options)
+                .trace_with(|| format!("{{% macro {name} %}}").into())?,
+        );
+
+        tokens.assert_empty();
+        Ok(Box::new(DefineMacro {
+            definition: Arc::new(Macro {
+                name,
+                params,
+                template,
+            }),
+        }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+/// The macros defined so far in a render, shared by all frames and partials.
+#[derive(Debug, Default)]
+pub(crate) struct MacroRegister {
+    macros: HashMap<KString, Arc<Macro>>,
+}
+
+impl MacroRegister {
+    pub(crate) fn get(&self, name: &str) -> Option<Arc<Macro>> {
+        self.macros.get(name).cloned()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Macro {
+    name: KString,
+    params: Vec<(KString, Option<Expression>)>,
+    template: Template,
+}
+
+impl Macro {
+    /// Render the macro with `args`, evaluated in the caller's `runtime`.
+    pub(crate) fn call(
+        &self,
+        args: &[Expression],
+        named_args: &[(KString, Expression)],
+        writer: &mut dyn Write,
+        runtime: &dyn Runtime,
+    ) -> Result<()> {
+        let scope = self.scope(args, named_args, runtime)?;
+        let scope = GlobalFrame::new(
+            SandboxedStackFrame::new(runtime, &scope).with_name(self.name.clone()),
+        );
+        scope.resource_usage().check_depth(scope.depth())?;
+        self.template.render_to(writer, &scope)
+    }
+
+    /// Render the macro with `args` to an asynchronous `writer`, see [`Macro::call`].
+    #[cfg(feature = "async")]
+    pub(crate) async fn call_async(
+        &self,
+        args: &[Expression],
+        named_args: &[(KString, Expression)],
+        writer: &mut (dyn AsyncWrite + Unpin),
+        runtime: &dyn Runtime,
+    ) -> Result<()> {
+        let scope = self.scope(args, named_args, runtime)?;
+        let scope = GlobalFrame::new(
+            SandboxedStackFrame::new(runtime, &scope).with_name(self.name.clone()),
+        );
+        scope.resource_usage().check_depth(scope.depth())?;
+        self.template.render_to_async(writer, &scope).await
+    }
+
+    /// The variables of the macro, its parameters set to `args`.
+    fn scope(
+        &self,
+        args: &[Expression],
+        named_args: &[(KString, Expression)],
+        runtime: &dyn Runtime,
+    ) -> Result<Object> {
+        if self.params.len() < args.len() {
+            return Error::with_msg("Too many arguments")
+                .context("macro", self.name.clone())
+                .into_err();
+        }
+        if let Some((name, _)) = named_args
+            .iter()
+            .find(|(name, _)| !self.params.iter().any(|(param, _)| param == name))
+        {
+            return Error::with_msg("Unknown argument")
+                .context("macro", self.name.clone())
+                .context("argument", name.clone())
+                .into_err();
+        }
+
+        let mut scope = Object::new();
+        for (i, (param, default)) in self.params.iter().enumerate() {
+            let arg = args.get(i).or_else(|| {
+                named_args
+                    .iter()
+                    .find(|(name, _)| name == param)
+                    .map(|(_, arg)| arg)
+            });
+            let value = match arg.or(default.as_ref()) {
+                Some(arg) => arg.evaluate(runtime)?.into_owned(),
+                None => Value::Nil,
+            };
+            scope.insert(param.clone(), value);
+        }
+        Ok(scope)
+    }
+}
+
+#[derive(Debug)]
+struct DefineMacro {
+    definition: Arc<Macro>,
+}
+
+impl Renderable for DefineMacro {
+    fn render_to(&self, _writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        runtime
+            .shared_registers()
+            .get_mut::<MacroRegister>()
+            .macros
+            .insert(self.definition.name.clone(), self.definition.clone());
+        Ok(())
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        for (_, default) in &self.definition.params {
+            if let Some(default) = default {
+                default.accept(visitor);
+            }
+        }
+        self.definition.template.accept(visitor);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options
+            .blocks
+            .register("macro".to_owned(), MacroBlock.into());
+        options
+    }
+
+    #[test]
+    fn test_macro_is_not_rendered() {
+        let text = "a{% macro badge(price, label: 'Sale') %}{{ label }}{% endmacro %}b";
+        let options = options();
+        let template = parser::parse(text, &options).map(Template::new).unwrap();
+
+        let rt = RuntimeBuilder::new().build();
+        let output = template.render(&rt).unwrap();
+        assert_eq!(output, "ab");
+        assert!(rt
+            .shared_registers()
+            .get_mut::<MacroRegister>()
+            .get("badge")
+            .is_some());
+    }
+
+    #[test]
+    fn test_macro_without_params() {
+        let options = options();
+        parser::parse("{% macro a %}{% endmacro %}", &options).unwrap();
+        parser::parse("{% macro a() %}{% endmacro %}", &options).unwrap();
+    }
+
+    #[test]
+    fn test_macro_params_errors() {
+        let options = options();
+        parser::parse("{% macro %}{% endmacro %}", &options).unwrap_err();
+        parser::parse("{% macro a(b %}{% endmacro %}", &options).unwrap_err();
+        parser::parse("{% macro a(b c) %}{% endmacro %}", &options).unwrap_err();
+        parser::parse("{% macro a(b, b) %}{% endmacro %}", &options).unwrap_err();
+        parser::parse("{% macro a(b:) %}{% endmacro %}", &options).unwrap_err();
+        parser::parse("{% macro a(b) c %}{% endmacro %}", &options).unwrap_err();
+    }
+}
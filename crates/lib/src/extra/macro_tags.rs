@@ -0,0 +1,403 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use liquid_core::error::ResultLiquidExt;
+use liquid_core::model::KString;
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
+use liquid_core::runtime::{GlobalFrame, SandboxedStackFrame};
+use liquid_core::Expression;
+use liquid_core::Language;
+use liquid_core::Object;
+use liquid_core::Renderable;
+use liquid_core::Runtime;
+use liquid_core::ValueView;
+use liquid_core::{Error, Result};
+use liquid_core::{ParseTag, TagReflection, TagTokenIter};
+
+use super::macro_block::{Macro, MacroRegister};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CallTag;
+
+impl CallTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for CallTag {
+    fn tag(&self) -> &'static str {
+        "call"
+    }
+
+    fn description(&self) -> &'static str {
+        "render a macro, like `{% call badge(product.price, label: 'New') %}`"
+    }
+}
+
+impl ParseTag for CallTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let name: KString = arguments
+            .expect_next("Identifier expected")?
+            .expect_identifier()
+            .into_result()?
+            .to_owned()
+            .into();
+
+        let mut args = Vec::new();
+        let mut named_args: Vec<(KString, Expression)> = Vec::new();
+        if let Some(open) = arguments.next() {
+            open.expect_str("(")
+                .into_result_custom_msg("expected \"(\" to start the arguments")?;
+            loop {
+                let token = arguments.expect_next("\")\" expected.")?;
+                if token.as_str() == ")" {
+                    break;
+                }
+
+                // Either `value` or `name: value`, told apart by the token following it.
+                let arg = token.as_str().to_owned();
+                let value = token.expect_value();
+                let mut token = arguments.expect_next("\")\" expected.")?;
+                if token.as_str() == ":" {
+                    let value = arguments
+                        .expect_next("expected value")?
+                        .expect_value()
+                        .into_result()?;
+                    named_args.push((arg.into(), value));
+                    token = arguments.expect_next("\")\" expected.")?;
+                } else if named_args.is_empty() {
+                    args.push(value.into_result()?);
+                } else {
+                    return Error::with_msg("Positional arguments must come first")
+                        .context("argument", arg)
+                        .into_err();
+                }
+
+                if token.as_str() == ")" {
+                    break;
+                }
+                token
+                    .expect_str(",")
+                    .into_result_custom_msg("expected \",\" or \")\" after an argument")?;
+            }
+        }
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(Call {
+            name,
+            args,
+            named_args,
+        }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Call {
+    name: KString,
+    args: Vec<Expression>,
+    named_args: Vec<(KString, Expression)>,
+}
+
+impl Call {
+    fn trace(&self) -> String {
+        format!("{{% call {} %}}", self.name)
+    }
+}
+
+impl Call {
+    fn definition(&self, runtime: &dyn Runtime) -> Result<Arc<Macro>> {
+        let definition = runtime
+            .shared_registers()
+            .get_mut::<MacroRegister>()
+            .get(&self.name);
+        definition
+            .ok_or_else(|| Error::with_msg("Unknown macro").context("macro", self.name.clone()))
+    }
+}
+
+impl Renderable for Call {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        self.definition(runtime)?
+            .call(&self.args, &self.named_args, writer, runtime)
+            .trace_with(|| self.trace().into())
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.accept(visitor), runtime).await;
+            self.definition(runtime)?
+                .call_async(&self.args, &self.named_args, writer, runtime)
+                .await
+                .trace_with(|| self.trace().into())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        for arg in &self.args {
+            arg.accept(visitor);
+        }
+        for (_, arg) in &self.named_args {
+            arg.accept(visitor);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ImportTag;
+
+impl ImportTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for ImportTag {
+    fn tag(&self) -> &'static str {
+        "import"
+    }
+
+    fn description(&self) -> &'static str {
+        "define the macros of a partial-template, without rendering its output"
+    }
+}
+
+impl ParseTag for ImportTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let partial = arguments
+            .expect_next("Identifier or literal expected.")?
+            .expect_value()
+            .into_result()?;
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(Import { partial }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Import {
+    partial: Expression,
+}
+
+impl Import {
+    fn name(&self, runtime: &dyn Runtime) -> Result<KString> {
+        let value = self.partial.evaluate(runtime)?;
+        if !value.is_scalar() {
+            return Error::with_msg("Can only `import` strings")
+                .context("partial", format!("{}", value.source()))
+                .into_err();
+        }
+        Ok(value.to_kstr().into_owned())
+    }
+
+    fn partial(&self, scope: &dyn Runtime, name: &str) -> Result<Arc<dyn Renderable>> {
+        scope
+            .resource_usage()
+            .check_depth(scope.depth())
+            .trace_with(|| self.trace().into())?;
+
+        scope
+            .partials()
+            .get(name)
+            .trace_with(|| self.trace().into())
+    }
+
+    fn trace(&self) -> String {
+        format!("{{% import {} %}}", self.partial)
+    }
+}
+
+impl Renderable for Import {
+    fn render_to(&self, _writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let name = self.name(runtime)?;
+        let root = Object::new();
+        let scope =
+            GlobalFrame::new(SandboxedStackFrame::new(runtime, &root).with_name(name.clone()));
+
+        // Rendering defines the macros, the rest of the output is dropped.
+        self.partial(&scope, &name)?
+            .render_to(&mut std::io::sink(), &scope)
+            .trace_with(|| self.trace().into())
+            .context_key_with(|| self.partial.to_string().into())
+            .value_with(|| name.to_string().into())
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        _writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.accept(visitor), runtime).await;
+            let name = self.name(runtime)?;
+            let root = Object::new();
+            let scope =
+                GlobalFrame::new(SandboxedStackFrame::new(runtime, &root).with_name(name.clone()));
+
+            // See `render_to`, the output is dropped.
+            self.partial(&scope, &name)?
+                .render_to_async(&mut Vec::new(), &scope)
+                .await
+                .trace_with(|| self.trace().into())
+                .context_key_with(|| self.partial.to_string().into())
+                .value_with(|| name.to_string().into())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_partial(&self.partial);
+        self.partial.accept(visitor);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::borrow;
+
+    use liquid_core::parser;
+    use liquid_core::partials;
+    use liquid_core::partials::PartialCompiler;
+    use liquid_core::runtime;
+    use liquid_core::runtime::RuntimeBuilder;
+    use liquid_core::Value;
+
+    use crate::stdlib;
+
+    use super::*;
+
+    #[derive(Default, Debug, Clone, Copy)]
+    struct TestSource;
+
+    impl partials::PartialSource for TestSource {
+        fn contains(&self, _name: &str) -> bool {
+            true
+        }
+
+        fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+            vec![]
+        }
+
+        fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+            match name {
+                "badges" => Some(
+                    "ignored{% macro badge(price, label: 'Sale') %}<b>{{ label }} {{ price }}</b>{% endmacro %}"
+                        .into(),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("call".to_owned(), CallTag.into());
+        options.tags.register("import".to_owned(), ImportTag.into());
+        options
+            .tags
+            .register("assign".to_owned(), stdlib::AssignTag.into());
+        options
+            .blocks
+            .register("macro".to_owned(), crate::extra::MacroBlock.into());
+        options
+    }
+
+    fn render(text: &str) -> Result<String> {
+        let options = options();
+        let template = parser::parse(text, &options)
+            .map(runtime::Template::new)
+            .unwrap();
+
+        let partials = partials::OnDemandCompiler::<TestSource>::empty()
+            .compile(::std::sync::Arc::new(options))
+            .unwrap();
+        let runtime = RuntimeBuilder::new()
+            .set_partials(partials.as_ref())
+            .build();
+        runtime.set_global("price".into(), Value::scalar(10));
+        template.render(&runtime)
+    }
+
+    #[test]
+    fn call_binds_arguments() {
+        let output = render(concat!(
+            "{% macro pair(a, b: 'default') %}[{{ a }}, {{ b }}]{% endmacro %}",
+            "{% call pair(1) %}{% call pair(1, 2) %}{% call pair(b: 3) %}{% call pair(price, b: price) %}{% call pair %}",
+        ))
+        .unwrap();
+        assert_eq!(output, "[1, default][1, 2][, 3][10, 10][, default]");
+    }
+
+    #[test]
+    fn call_is_sandboxed() {
+        let output = render(concat!(
+            "{% macro leak() %}{% assign price = 1 %}{% endmacro %}",
+            "[{% call leak() %}]{{ price }}",
+        ))
+        .unwrap();
+        assert_eq!(output, "[]10");
+
+        render("{% macro peek() %}{{ price }}{% endmacro %}{% call peek() %}").unwrap_err();
+    }
+
+    #[test]
+    fn call_errors() {
+        let prelude = "{% macro pair(a, b) %}{% endmacro %}";
+        render("{% call missing() %}").unwrap_err();
+        render(&format!("{prelude}{{% call pair(1, 2, 3) %}}")).unwrap_err();
+        render(&format!("{prelude}{{% call pair(c: 3) %}}")).unwrap_err();
+    }
+
+    #[test]
+    fn call_parse_errors() {
+        let options = options();
+        parser::parse("{% call %}", &options).unwrap_err();
+        parser::parse("{% call pair(1 %}", &options).unwrap_err();
+        parser::parse("{% call pair(1 2) %}", &options).unwrap_err();
+        parser::parse("{% call pair(a: 1, 2) %}", &options).unwrap_err();
+        parser::parse("{% call pair(1) 2 %}", &options).unwrap_err();
+    }
+
+    #[test]
+    fn call_recursion_is_stopped() {
+        render("{% macro loop() %}{% call loop() %}{% endmacro %}{% call loop() %}").unwrap_err();
+    }
+
+    #[test]
+    fn import_defines_macros() {
+        let output = render("{% import 'badges' %}{% call badge(price) %}").unwrap();
+        assert_eq!(output, "<b>Sale 10</b>");
+    }
+
+    #[test]
+    fn import_requires_partial() {
+        render("{% import 'missing' %}").unwrap_err();
+    }
+}
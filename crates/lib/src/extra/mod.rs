@@ -1,3 +1,15 @@
+mod block_block;
+mod content_for_block;
 mod date;
+mod layout_tag;
+mod macro_block;
+mod macro_tags;
+mod yield_tag;
 
+pub use self::block_block::BlockBlock;
+pub use self::content_for_block::{ContentFor, ContentForBlock};
 pub use self::date::*;
+pub use self::layout_tag::{ExtendsTag, LayoutTag};
+pub use self::macro_block::MacroBlock;
+pub use self::macro_tags::{CallTag, ImportTag};
+pub use self::yield_tag::YieldTag;
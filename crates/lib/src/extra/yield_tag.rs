@@ -0,0 +1,114 @@
+use std::io::Write;
+
+use liquid_core::error::ResultLiquidReplaceExt;
+use liquid_core::model::KString;
+use liquid_core::Language;
+use liquid_core::Renderable;
+use liquid_core::Result;
+use liquid_core::Runtime;
+use liquid_core::{ParseTag, TagReflection, TagTokenIter};
+
+use super::content_for_block::ContentForRegister;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct YieldTag;
+
+impl YieldTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for YieldTag {
+    fn tag(&self) -> &'static str {
+        "yield"
+    }
+
+    fn description(&self) -> &'static str {
+        "output the content appended to a slot with `content_for`"
+    }
+}
+
+impl ParseTag for YieldTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let slot = arguments
+            .expect_next("Identifier expected")?
+            .expect_identifier()
+            .into_result()?
+            .to_owned()
+            .into();
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        Ok(Box::new(Yield { slot }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Yield {
+    slot: KString,
+}
+
+impl Renderable for Yield {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let register = runtime.shared_registers().get_mut::<ContentForRegister>();
+        if let Some(content) = register.get(&self.slot) {
+            write!(writer, "{content}").replace("Failed to render")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    use crate::extra;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("yield".to_owned(), YieldTag.into());
+        options
+            .blocks
+            .register("content_for".to_owned(), extra::ContentForBlock.into());
+        options
+    }
+
+    #[test]
+    fn yield_outputs_slot() {
+        let text = concat!(
+            "{% content_for head %}<style>{% endcontent_for %}",
+            "[{% yield head %}][{% yield missing %}]",
+            "{% content_for head %}<meta>{% endcontent_for %}",
+            "[{% yield head %}]",
+        );
+        let options = options();
+        let template = parser::parse(text, &options)
+            .map(runtime::Template::new)
+            .unwrap();
+
+        let rt = RuntimeBuilder::new().build();
+        let output = template.render(&rt).unwrap();
+        assert_eq!(output, "[<style>][][<style><meta>]");
+    }
+
+    #[test]
+    fn yield_requires_slot() {
+        let options = options();
+        parser::parse("{% yield %}", &options).unwrap_err();
+        parser::parse("{% yield a b %}", &options).unwrap_err();
+    }
+}
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
 use std::io::Write;
+use std::sync::Arc;
 
 use liquid_core::error::ResultLiquidExt;
-use liquid_core::model::KString;
+use liquid_core::model::{KString, KStringRef, Value};
+use liquid_core::parser::ParseTreeVisitor;
 use liquid_core::parser::TryMatchToken;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
 use liquid_core::Expression;
 use liquid_core::Language;
 use liquid_core::Renderable;
@@ -82,43 +87,84 @@ struct Include {
     vars: Vec<(KString, Expression)>,
 }
 
+impl Include {
+    fn helper_vars(&self, runtime: &dyn Runtime) -> Result<HashMap<KStringRef<'_>, Value>> {
+        let mut helper_vars = HashMap::new();
+        for (id, val) in &self.vars {
+            let value = val
+                .try_evaluate(runtime)
+                .ok_or_else(|| Error::with_msg("failed to evaluate value"))?
+                .into_owned();
+
+            helper_vars.insert(id.as_ref(), value);
+        }
+        Ok(helper_vars)
+    }
+
+    fn partial(&self, scope: &dyn Runtime, name: &str) -> Result<Arc<dyn Renderable>> {
+        scope
+            .resource_usage()
+            .check_depth(scope.depth())
+            .trace_with(|| self.trace().into())?;
+        scope
+            .partials()
+            .get(name)
+            .trace_with(|| self.trace().into())
+    }
+
+    fn trace(&self) -> String {
+        format!("{{% include {} %}}", self.partial)
+    }
+}
+
 impl Renderable for Include {
     fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
         let name = self.partial.evaluate(runtime)?.render().to_string();
+        let helper_vars = self.helper_vars(runtime)?;
+        let mut pass_through = HashMap::<KStringRef<'_>, &dyn ValueView>::new();
+        if !self.vars.is_empty() {
+            pass_through.insert("include".into(), &helper_vars);
+        }
 
-        {
-            let mut pass_through = std::collections::HashMap::<
-                liquid_core::model::KStringRef<'_>,
-                &dyn ValueView,
-            >::new();
-            let mut helper_vars = std::collections::HashMap::new();
-            if !self.vars.is_empty() {
-                for (id, val) in &self.vars {
-                    let value = val
-                        .try_evaluate(runtime)
-                        .ok_or_else(|| Error::with_msg("failed to evaluate value"))?
-                        .into_owned();
-
-                    helper_vars.insert(id.as_ref(), value);
-                }
+        let scope = StackFrame::new(runtime, &pass_through).with_name(name.clone());
+        self.partial(&scope, &name)?
+            .render_to(writer, &scope)
+            .trace_with(|| self.trace().into())
+            .context_key_with(|| self.partial.to_string().into())
+            .value_with(|| name.clone().into())
+    }
 
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.accept(visitor), runtime).await;
+            let name = self.partial.evaluate(runtime)?.render().to_string();
+            let helper_vars = self.helper_vars(runtime)?;
+            let mut pass_through = HashMap::<KStringRef<'_>, &dyn ValueView>::new();
+            if !self.vars.is_empty() {
                 pass_through.insert("include".into(), &helper_vars);
             }
 
-            let scope = StackFrame::new(runtime, &pass_through);
-            let partial = scope
-                .partials()
-                .get(&name)
-                .trace_with(|| format!("{{% include {} %}}", self.partial).into())?;
-
-            partial
-                .render_to(writer, &scope)
-                .trace_with(|| format!("{{% include {} %}}", self.partial).into())
+            let scope = StackFrame::new(runtime, &pass_through).with_name(name.clone());
+            self.partial(&scope, &name)?
+                .render_to_async(writer, &scope)
+                .await
+                .trace_with(|| self.trace().into())
                 .context_key_with(|| self.partial.to_string().into())
-                .value_with(|| name.clone().into())?;
-        }
+                .value_with(|| name.clone().into())
+        })
+    }
 
-        Ok(())
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_partial(&self.partial);
+        self.partial.accept(visitor);
+        for (_, val) in &self.vars {
+            val.accept(visitor);
+        }
     }
 }
 
@@ -146,7 +192,7 @@ mod test {
             true
         }
 
-        fn names(&self) -> Vec<&str> {
+        fn names(&self) -> Vec<borrow::Cow<'_, str>> {
             vec![]
         }
 
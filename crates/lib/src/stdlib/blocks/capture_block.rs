@@ -2,6 +2,9 @@ use std::io::Write;
 
 use liquid_core::error::ResultLiquidExt;
 use liquid_core::model::Value;
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{AsyncWrite, RenderFuture};
 use liquid_core::Language;
 use liquid_core::Renderable;
 use liquid_core::Result;
@@ -76,16 +79,46 @@ impl Capture {
     }
 }
 
+impl Capture {
+    fn assign(&self, captured: Vec<u8>, runtime: &dyn Runtime) -> Result<()> {
+        let output = String::from_utf8(captured).expect("render only writes UTF-8");
+        let output = Value::scalar(output);
+        runtime
+            .resource_usage()
+            .increment_assign_score(&output)
+            .trace_with(|| self.trace().into())?;
+        runtime.set_global(self.id.clone(), output);
+        Ok(())
+    }
+}
+
 impl Renderable for Capture {
     fn render_to(&self, _writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
         let mut captured = Vec::new();
         self.template
             .render_to(&mut captured, runtime)
             .trace_with(|| self.trace().into())?;
+        self.assign(captured, runtime)
+    }
 
-        let output = String::from_utf8(captured).expect("render only writes UTF-8");
-        runtime.set_global(self.id.clone(), Value::scalar(output));
-        Ok(())
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        _writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let mut captured = Vec::new();
+            self.template
+                .render_to_async(&mut captured, runtime)
+                .await
+                .trace_with(|| self.trace().into())?;
+            self.assign(captured, runtime)
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.template.accept(visitor);
     }
 }
 
@@ -3,7 +3,10 @@ use std::io::Write;
 use liquid_core::error::ResultLiquidExt;
 use liquid_core::model::{ValueView, ValueViewCmp};
 use liquid_core::parser::BlockElement;
+use liquid_core::parser::ParseTreeVisitor;
 use liquid_core::parser::TryMatchToken;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
 use liquid_core::Expression;
 use liquid_core::Language;
 use liquid_core::Renderable;
@@ -163,6 +166,61 @@ impl Renderable for Case {
 
         Ok(())
     }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let conditions = |visitor: &mut dyn ParseTreeVisitor| {
+                self.target.accept(visitor);
+                for arg in self.cases.iter().flat_map(|case| &case.args) {
+                    arg.accept(visitor);
+                }
+            };
+            resolve_lazy_values(conditions, runtime).await;
+            let value = self.target.evaluate(runtime)?.to_value();
+            for case in &self.cases {
+                if case.evaluate(&value, runtime)? {
+                    return case
+                        .template
+                        .render_to_async(writer, runtime)
+                        .await
+                        .trace_with(|| case.trace().into())
+                        .trace_with(|| self.trace().into())
+                        .context_key_with(|| self.target.to_string().into())
+                        .value_with(|| value.to_kstr().into_owned());
+                }
+            }
+
+            if let Some(ref t) = self.else_block {
+                return t
+                    .render_to_async(writer, runtime)
+                    .await
+                    .trace("{{% else %}}")
+                    .trace_with(|| self.trace().into())
+                    .context_key_with(|| self.target.to_string().into())
+                    .value_with(|| value.to_kstr().into_owned());
+            }
+
+            Ok(())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.target.accept(visitor);
+        for case in &self.cases {
+            for arg in &case.args {
+                arg.accept(visitor);
+            }
+            case.template.accept(visitor);
+        }
+        if let Some(ref t) = self.else_block {
+            t.accept(visitor);
+        }
+    }
 }
 
 #[derive(Debug)]
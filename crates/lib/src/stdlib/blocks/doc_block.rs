@@ -0,0 +1,115 @@
+use std::io::Write;
+
+use liquid_core::parser::Doc;
+use liquid_core::parser::ParseTreeVisitor;
+use liquid_core::Language;
+use liquid_core::Renderable;
+use liquid_core::Result;
+use liquid_core::Runtime;
+use liquid_core::{BlockReflection, ParseBlock, TagBlock, TagTokenIter};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DocBlock;
+
+impl DocBlock {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl BlockReflection for DocBlock {
+    fn start_tag(&self) -> &str {
+        "doc"
+    }
+
+    fn end_tag(&self) -> &str {
+        "enddoc"
+    }
+
+    fn description(&self) -> &str {
+        ""
+    }
+}
+
+impl ParseBlock for DocBlock {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        mut tokens: TagBlock<'_, '_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        // no arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let doc = Doc::parse(tokens.escape_liquid(false)?);
+
+        tokens.assert_empty();
+        Ok(Box::new(DocT { doc }))
+    }
+
+    fn reflection(&self) -> &dyn BlockReflection {
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+struct DocT {
+    doc: Doc,
+}
+
+impl Renderable for DocT {
+    fn render_to(&self, _writer: &mut dyn Write, _runtime: &dyn Runtime) -> Result<()> {
+        Ok(())
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_doc(&self.doc);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.blocks.register("doc".to_owned(), DocBlock.into());
+        options
+    }
+
+    fn unit_parse(text: &str) -> runtime::Template {
+        parser::parse(text, &options())
+            .map(runtime::Template::new)
+            .unwrap()
+    }
+
+    #[derive(Default)]
+    struct Docs(Vec<Doc>);
+
+    impl ParseTreeVisitor for Docs {
+        fn visit_doc(&mut self, doc: &Doc) {
+            self.0.push(doc.clone());
+        }
+    }
+
+    #[test]
+    fn test_doc_renders_nothing() {
+        let template = unit_parse("a{% doc %} @param {{ b }} {% if %}{% enddoc %}c");
+        let runtime = RuntimeBuilder::new().build();
+        assert_eq!(template.render(&runtime).unwrap(), "ac");
+    }
+
+    #[test]
+    fn test_doc_is_visited() {
+        let template = unit_parse("{% doc %}\n  Greets.\n  @param {string} name\n{% enddoc %}");
+        let mut docs = Docs::default();
+        template.accept(&mut docs);
+        assert_eq!(docs.0.len(), 1);
+        assert_eq!(docs.0[0].description(), "Greets.");
+        assert_eq!(docs.0[0].params()[0].name(), "name");
+    }
+}
@@ -4,7 +4,10 @@ use std::io::Write;
 use liquid_core::error::{ResultLiquidExt, ResultLiquidReplaceExt};
 use liquid_core::model::{Object, ObjectView, Value, ValueCow, ValueView};
 use liquid_core::parser::BlockElement;
+use liquid_core::parser::ParseTreeVisitor;
 use liquid_core::parser::TryMatchToken;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
 use liquid_core::runtime::{Interrupt, InterruptRegister};
 use liquid_core::Expression;
 use liquid_core::Language;
@@ -176,10 +179,9 @@ impl Renderable for For {
             .range
             .evaluate(runtime)
             .trace_with(|| self.trace().into())?;
-        let array = range.evaluate()?;
         let limit = evaluate_attr(&self.limit, runtime)?;
         let offset = evaluate_attr(&self.offset, runtime)?.unwrap_or(0);
-        let array = iter_array(array, limit, offset, self.reversed);
+        let array = loop_items(&range, limit, offset, self.reversed, runtime)?;
 
         match array.len() {
             0 => {
@@ -194,6 +196,7 @@ impl Renderable for For {
                 let parentloop = runtime.try_get(&[liquid_core::model::Scalar::new("forloop")]);
                 let parentloop_ref = parentloop.as_ref().map(|v| v.as_view());
                 for (i, v) in array.into_iter().enumerate() {
+                    charge_iteration(&self.item_template, runtime)?;
                     let forloop = ForloopObject::new(i, range_len).parentloop(parentloop_ref);
                     let mut root = std::collections::HashMap::<
                         liquid_core::model::KStringRef<'_>,
@@ -223,6 +226,83 @@ impl Renderable for For {
         }
         Ok(())
     }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let header = |visitor: &mut dyn ParseTreeVisitor| {
+                self.range.accept(visitor);
+                for attr in [&self.limit, &self.offset].into_iter().flatten() {
+                    attr.accept(visitor);
+                }
+            };
+            resolve_lazy_values(header, runtime).await;
+            let range = self
+                .range
+                .evaluate(runtime)
+                .trace_with(|| self.trace().into())?;
+            let limit = evaluate_attr(&self.limit, runtime)?;
+            let offset = evaluate_attr(&self.offset, runtime)?.unwrap_or(0);
+            let array = loop_items(&range, limit, offset, self.reversed, runtime)?;
+
+            match array.len() {
+                0 => {
+                    if let Some(ref t) = self.else_template {
+                        t.render_to_async(writer, runtime)
+                            .await
+                            .trace("{{% else %}}")
+                            .trace_with(|| self.trace().into())?;
+                    }
+                }
+
+                range_len => {
+                    let parentloop = runtime.try_get(&[liquid_core::model::Scalar::new("forloop")]);
+                    let parentloop_ref = parentloop.as_ref().map(|v| v.as_view());
+                    for (i, v) in array.into_iter().enumerate() {
+                        charge_iteration(&self.item_template, runtime)?;
+                        let forloop = ForloopObject::new(i, range_len).parentloop(parentloop_ref);
+                        let mut root = std::collections::HashMap::<
+                            liquid_core::model::KStringRef<'_>,
+                            &dyn ValueView,
+                        >::new();
+                        root.insert("forloop".into(), &forloop);
+                        root.insert(self.var_name.as_ref(), &v);
+
+                        let scope = StackFrame::new(runtime, &root);
+                        self.item_template
+                            .render_to_async(&mut *writer, &scope)
+                            .await
+                            .trace_with(|| self.trace().into())
+                            .context_key("index")
+                            .value_with(|| format!("{}", i + 1).into())?;
+
+                        // See `render_to` for `break` and `continue`.
+                        let current_interrupt =
+                            scope.registers().get_mut::<InterruptRegister>().reset();
+                        if let Some(Interrupt::Break) = current_interrupt {
+                            break;
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.range.accept(visitor);
+        for attr in [&self.limit, &self.offset].into_iter().flatten() {
+            attr.accept(visitor);
+        }
+        self.item_template.accept(visitor);
+        if let Some(ref t) = self.else_template {
+            t.accept(visitor);
+        }
+    }
 }
 
 #[derive(Debug, Clone, ValueView, ObjectView)]
@@ -402,11 +482,10 @@ impl Renderable for TableRow {
             .range
             .evaluate(runtime)
             .trace_with(|| self.trace().into())?;
-        let array = range.evaluate()?;
         let cols = evaluate_attr(&self.cols, runtime)?;
         let limit = evaluate_attr(&self.limit, runtime)?;
         let offset = evaluate_attr(&self.offset, runtime)?.unwrap_or(0);
-        let array = iter_array(array, limit, offset, false);
+        let array = loop_items(&range, limit, offset, false, runtime)?;
 
         let mut helper_vars = Object::new();
 
@@ -414,6 +493,7 @@ impl Renderable for TableRow {
         helper_vars.insert("length".into(), Value::scalar(range_len as i64));
 
         for (i, v) in array.into_iter().enumerate() {
+            charge_iteration(&self.item_template, runtime)?;
             let cols = cols.unwrap_or(range_len);
             let col_index = i % cols;
             let row_index = i / cols;
@@ -447,6 +527,17 @@ impl Renderable for TableRow {
 
         Ok(())
     }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.range.accept(visitor);
+        for attr in [&self.cols, &self.limit, &self.offset]
+            .into_iter()
+            .flatten()
+        {
+            attr.accept(visitor);
+        }
+        self.item_template.accept(visitor);
+    }
 }
 
 #[derive(Debug, Clone, ValueView, ObjectView)]
@@ -542,6 +633,16 @@ impl RangeExpression {
 
         Ok(range)
     }
+
+    pub fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        match *self {
+            RangeExpression::Array(ref arr) => arr.accept(visitor),
+            RangeExpression::Counted(ref start, ref end) => {
+                start.accept(visitor);
+                end.accept(visitor);
+            }
+        }
+    }
 }
 
 impl fmt::Display for RangeExpression {
@@ -560,6 +661,35 @@ pub enum Range<'r> {
 }
 
 impl Range<'_> {
+    /// Number of items, without evaluating them.
+    pub fn len(&self) -> usize {
+        match self {
+            Range::Array(array) => {
+                let array = array.as_view();
+                if let Some(x) = array.as_array() {
+                    x.size() as usize
+                } else if let Some(x) = array.as_object() {
+                    x.size() as usize
+                } else {
+                    0
+                }
+            }
+            Range::Counted(start, stop) => {
+                usize::try_from(i128::from(*stop) - i128::from(*start) + 1).unwrap_or_else(|_| {
+                    if stop < start {
+                        0
+                    } else {
+                        usize::MAX
+                    }
+                })
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     pub fn evaluate(&self) -> Result<Vec<ValueCow<'_>>> {
         let range = match self {
             Range::Array(array) => get_array(array.as_view())?,
@@ -607,6 +737,34 @@ fn int_argument(arg: &Expression, runtime: &dyn Runtime, arg_name: &str) -> Resu
     Ok(value as isize)
 }
 
+/// Evaluates the items a loop renders, failing before evaluating them when there are more
+/// iterations than the render score allows.
+fn loop_items<'r>(
+    range: &'r Range<'_>,
+    limit: Option<usize>,
+    offset: usize,
+    reversed: bool,
+    runtime: &dyn Runtime,
+) -> Result<Vec<ValueCow<'r>>> {
+    let len = range.len();
+    let offset = ::std::cmp::min(offset, len);
+    let iterations = limit
+        .map(|l| ::std::cmp::min(l, len))
+        .unwrap_or_else(|| len - offset);
+    runtime.resource_usage().check_render_score(iterations)?;
+
+    let array = range.evaluate()?;
+    Ok(iter_array(array, limit, offset, reversed))
+}
+
+/// Records an iteration towards the render score, so loops with an empty body count too.
+fn charge_iteration(body: &Template, runtime: &dyn Runtime) -> Result<()> {
+    if body.is_empty() {
+        runtime.resource_usage().increment_render_score(1)?;
+    }
+    Ok(())
+}
+
 fn iter_array(
     mut range: Vec<ValueCow<'_>>,
     limit: Option<usize>,
@@ -1146,4 +1304,27 @@ mod test {
         let output = template.render(&runtime).unwrap();
         assert_eq!(output, "1.1 1.2 1.3 2.1 2.2 2.3 ");
     }
+
+    #[test]
+    fn empty_loops_count_towards_render_score() {
+        let limits = liquid_core::runtime::ResourceLimits::new().with_render_score_limit(1000);
+        for text in [
+            "{% for i in (1..500) %}{% endfor %}{% for i in (1..500) %}{% endfor %}",
+            "{% for i in (1..1000000000000) %}{% endfor %}",
+            "{% tablerow i in (1..1000000000000) %}{% endtablerow %}",
+        ] {
+            let template = parser::parse(text, &options()).map(Template::new).unwrap();
+            let runtime = RuntimeBuilder::new().set_resource_limits(limits).build();
+            let err = template.render(&runtime).unwrap_err();
+            assert_eq!(
+                err.to_string().lines().next(),
+                Some("liquid: Render score limit exceeded")
+            );
+        }
+
+        let text = "{% for i in (1..500) %}{% endfor %}";
+        let template = parser::parse(text, &options()).map(Template::new).unwrap();
+        let runtime = RuntimeBuilder::new().set_resource_limits(limits).build();
+        assert_eq!(template.render(&runtime).unwrap(), "");
+    }
 }
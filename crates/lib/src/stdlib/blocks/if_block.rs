@@ -1,17 +1,19 @@
-use std::fmt;
 use std::io::Write;
 
 use liquid_core::error::ResultLiquidExt;
-use liquid_core::model::{ValueView, ValueViewCmp};
 use liquid_core::parser::BlockElement;
+use liquid_core::parser::ErrorMode;
+use liquid_core::parser::ParseTreeVisitor;
 use liquid_core::parser::TagToken;
-use liquid_core::Expression;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
+use liquid_core::runtime::{ComparisonOperator, Condition};
 use liquid_core::Language;
 use liquid_core::Renderable;
+use liquid_core::Result;
 use liquid_core::Runtime;
 use liquid_core::Template;
 use liquid_core::{BlockReflection, ParseBlock, TagBlock, TagTokenIter};
-use liquid_core::{Error, Result};
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct IfBlock;
@@ -59,7 +61,7 @@ fn parse_if(
     tokens: &mut TagBlock<'_, '_>,
     options: &Language,
 ) -> Result<Box<dyn Renderable>> {
-    let condition = parse_condition(arguments)?;
+    let condition = parse_condition(arguments, options)?;
 
     let mut if_true = Vec::new();
     let mut if_false = None;
@@ -122,7 +124,7 @@ impl ParseBlock for UnlessBlock {
         mut tokens: TagBlock<'_, '_>,
         options: &Language,
     ) -> Result<Box<dyn Renderable>> {
-        let condition = parse_condition(arguments)?;
+        let condition = parse_condition(arguments, options)?;
 
         let mut if_true = Vec::new();
         let mut if_false = None;
@@ -193,165 +195,46 @@ impl Renderable for Conditional {
 
         Ok(())
     }
-}
-
-#[derive(Clone, Debug)]
-enum Condition {
-    Binary(BinaryCondition),
-    Existence(ExistenceCondition),
-    Conjunction(Box<Condition>, Box<Condition>),
-    Disjunction(Box<Condition>, Box<Condition>),
-}
-
-impl Condition {
-    pub(crate) fn evaluate(&self, runtime: &dyn Runtime) -> Result<bool> {
-        match *self {
-            Condition::Binary(ref c) => c.evaluate(runtime),
-            Condition::Existence(ref c) => c.evaluate(runtime),
-            Condition::Conjunction(ref left, ref right) => {
-                Ok(left.evaluate(runtime)? && right.evaluate(runtime)?)
-            }
-            Condition::Disjunction(ref left, ref right) => {
-                Ok(left.evaluate(runtime)? || right.evaluate(runtime)?)
-            }
-        }
-    }
-}
-
-impl fmt::Display for Condition {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            Condition::Binary(ref c) => write!(f, "{c}"),
-            Condition::Existence(ref c) => write!(f, "{c}"),
-            Condition::Conjunction(ref left, ref right) => write!(f, "{left} and {right}"),
-            Condition::Disjunction(ref left, ref right) => write!(f, "{left} or {right}"),
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct BinaryCondition {
-    lh: Expression,
-    comparison: ComparisonOperator,
-    rh: Expression,
-}
-
-impl BinaryCondition {
-    pub(crate) fn evaluate(&self, runtime: &dyn Runtime) -> Result<bool> {
-        let a = self.lh.evaluate(runtime)?;
-        let ca = ValueViewCmp::new(a.as_view());
-        let b = self.rh.evaluate(runtime)?;
-        let cb = ValueViewCmp::new(b.as_view());
-
-        let result = match self.comparison {
-            ComparisonOperator::Equals => ca == cb,
-            ComparisonOperator::NotEquals => ca != cb,
-            ComparisonOperator::LessThan => ca < cb,
-            ComparisonOperator::GreaterThan => ca > cb,
-            ComparisonOperator::LessThanEquals => ca <= cb,
-            ComparisonOperator::GreaterThanEquals => ca >= cb,
-            ComparisonOperator::Contains => contains_check(a.as_view(), b.as_view())?,
-        };
-
-        Ok(result)
-    }
-}
-
-impl fmt::Display for BinaryCondition {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {} {}", self.lh, self.comparison, self.rh)
-    }
-}
 
-fn contains_check(a: &dyn ValueView, b: &dyn ValueView) -> Result<bool> {
-    if let Some(a) = a.as_scalar() {
-        let b = b.to_kstr();
-        Ok(a.to_kstr().contains(b.as_str()))
-    } else if let Some(a) = a.as_object() {
-        let b = b.as_scalar();
-        let check = b
-            .map(|b| a.contains_key(b.to_kstr().as_str()))
-            .unwrap_or(false);
-        Ok(check)
-    } else if let Some(a) = a.as_array() {
-        for elem in a.values() {
-            if ValueViewCmp::new(elem) == ValueViewCmp::new(b) {
-                return Ok(true);
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.condition.accept(visitor), runtime).await;
+            let condition = self.compare(runtime).trace_with(|| self.trace().into())?;
+            if condition {
+                self.if_true
+                    .render_to_async(writer, runtime)
+                    .await
+                    .trace_with(|| self.trace().into())?;
+            } else if let Some(ref template) = self.if_false {
+                template
+                    .render_to_async(writer, runtime)
+                    .await
+                    .trace("{{% else %}}")
+                    .trace_with(|| self.trace().into())?;
             }
-        }
-        Ok(false)
-    } else {
-        Err(unexpected_value_error(
-            "string | array | object",
-            Some(a.type_name()),
-        ))
-    }
-}
 
-#[derive(Clone, Debug)]
-enum ComparisonOperator {
-    Equals,
-    NotEquals,
-    LessThan,
-    GreaterThan,
-    LessThanEquals,
-    GreaterThanEquals,
-    Contains,
-}
-
-impl fmt::Display for ComparisonOperator {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let out = match *self {
-            ComparisonOperator::Equals => "==",
-            ComparisonOperator::NotEquals => "!=",
-            ComparisonOperator::LessThanEquals => "<=",
-            ComparisonOperator::GreaterThanEquals => ">=",
-            ComparisonOperator::LessThan => "<",
-            ComparisonOperator::GreaterThan => ">",
-            ComparisonOperator::Contains => "contains",
-        };
-        write!(f, "{out}")
+            Ok(())
+        })
     }
-}
 
-impl ComparisonOperator {
-    fn from_str(s: &str) -> ::std::result::Result<Self, ()> {
-        match s {
-            "==" => Ok(ComparisonOperator::Equals),
-            "!=" | "<>" => Ok(ComparisonOperator::NotEquals),
-            "<" => Ok(ComparisonOperator::LessThan),
-            ">" => Ok(ComparisonOperator::GreaterThan),
-            "<=" => Ok(ComparisonOperator::LessThanEquals),
-            ">=" => Ok(ComparisonOperator::GreaterThanEquals),
-            "contains" => Ok(ComparisonOperator::Contains),
-            _ => Err(()),
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.condition.accept(visitor);
+        self.if_true.accept(visitor);
+        if let Some(ref template) = self.if_false {
+            template.accept(visitor);
         }
     }
 }
 
-#[derive(Clone, Debug)]
-struct ExistenceCondition {
-    lh: Expression,
-}
-
-impl ExistenceCondition {
-    pub(crate) fn evaluate(&self, runtime: &dyn Runtime) -> Result<bool> {
-        let a = self.lh.try_evaluate(runtime);
-        let a = a.unwrap_or_default();
-        let is_truthy = a.query_state(liquid_core::model::State::Truthy);
-        Ok(is_truthy)
-    }
-}
-
-impl fmt::Display for ExistenceCondition {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.lh)
-    }
-}
-
 struct PeekableTagTokenIter<'a> {
     iter: TagTokenIter<'a>,
     peeked: Option<Option<TagToken<'a>>>,
+    skip_parens: bool,
 }
 
 impl<'a> Iterator for PeekableTagTokenIter<'a> {
@@ -360,19 +243,26 @@ impl<'a> Iterator for PeekableTagTokenIter<'a> {
     fn next(&mut self) -> Option<TagToken<'a>> {
         match self.peeked.take() {
             Some(v) => v,
-            None => self.iter.next(),
+            None => self.next_token(),
         }
     }
 }
 
 impl<'a> PeekableTagTokenIter<'a> {
+    fn next_token(&mut self) -> Option<TagToken<'a>> {
+        // Like Ruby's lax parser, parentheses are ignored rather than grouping.
+        self.iter
+            .by_ref()
+            .find(|token| !(self.skip_parens && matches!(token.as_str(), "(" | ")")))
+    }
+
     pub(crate) fn expect_next(&mut self, error_msg: &str) -> Result<TagToken<'a>> {
         self.next().ok_or_else(|| self.iter.raise_error(error_msg))
     }
 
     fn peek(&mut self) -> Option<&TagToken<'a>> {
         if self.peeked.is_none() {
-            self.peeked = Some(self.iter.next());
+            self.peeked = Some(self.next_token());
         }
         match self.peeked {
             Some(Some(ref value)) => Some(value),
@@ -390,7 +280,7 @@ fn parse_atom_condition(arguments: &mut PeekableTagTokenIter<'_>) -> Result<Cond
     let cond = match arguments
         .peek()
         .map(TagToken::as_str)
-        .and_then(|op| ComparisonOperator::from_str(op).ok())
+        .and_then(ComparisonOperator::from_symbol)
     {
         Some(op) => {
             arguments.next();
@@ -398,59 +288,52 @@ fn parse_atom_condition(arguments: &mut PeekableTagTokenIter<'_>) -> Result<Cond
                 .expect_next("Value expected.")?
                 .expect_value()
                 .into_result()?;
-            Condition::Binary(BinaryCondition {
-                lh,
-                comparison: op,
-                rh,
-            })
+            Condition::Binary(lh, op, rh)
         }
-        None => Condition::Existence(ExistenceCondition { lh }),
+        None => Condition::Existence(lh),
     };
 
     Ok(cond)
 }
 
-fn parse_conjunction_chain(arguments: &mut PeekableTagTokenIter<'_>) -> Result<Condition> {
-    let mut lh = parse_atom_condition(arguments)?;
-
-    while let Some("and") = arguments.peek().map(TagToken::as_str) {
-        arguments.next();
-        let rh = parse_atom_condition(arguments)?;
-        lh = Condition::Conjunction(Box::new(lh), Box::new(rh));
+/// Common parsing for "if" and "unless" condition
+fn parse_condition(mut arguments: TagTokenIter<'_>, options: &Language) -> Result<Condition> {
+    if options.grouped_conditions {
+        return arguments.expect_condition(options);
     }
 
-    Ok(lh)
-}
-
-/// Common parsing for "if" and "unless" condition
-fn parse_condition(arguments: TagTokenIter<'_>) -> Result<Condition> {
     let mut arguments = PeekableTagTokenIter {
         iter: arguments,
         peeked: None,
+        skip_parens: options.error_mode != ErrorMode::Strict,
     };
-    let mut lh = parse_conjunction_chain(&mut arguments)?;
+    let mut conditions = vec![parse_atom_condition(&mut arguments)?];
+    let mut operators = Vec::new();
 
     while let Some(token) = arguments.next() {
-        token
-            .expect_str("or")
-            .into_result_custom_msg("\"and\" or \"or\" expected.")?;
-
-        let rh = parse_conjunction_chain(&mut arguments)?;
-        lh = Condition::Disjunction(Box::new(lh), Box::new(rh));
+        let operator = token.as_str();
+        if operator != "and" && operator != "or" {
+            if options.error_mode != ErrorMode::Strict {
+                // Lax parsing ignores anything after the condition
+                break;
+            }
+            return Err(token.raise_custom_error("\"and\" or \"or\" expected."));
+        }
+        operators.push(operator == "and");
+        conditions.push(parse_atom_condition(&mut arguments)?);
     }
 
-    Ok(lh)
-}
-
-/// Format an error for an unexpected value.
-fn unexpected_value_error<S: ToString>(expected: &str, actual: Option<S>) -> Error {
-    let actual = actual.map(|x| x.to_string());
-    unexpected_value_error_string(expected, actual)
-}
+    // Like Ruby's liquid, `and` and `or` apply from right to left, without precedence.
+    let mut condition = conditions.pop().expect("at least one condition");
+    while let Some(lh) = conditions.pop() {
+        let (lh, rh) = (Box::new(lh), Box::new(condition));
+        condition = match operators.pop() {
+            Some(true) => Condition::Conjunction(lh, rh),
+            _ => Condition::Disjunction(lh, rh),
+        };
+    }
 
-fn unexpected_value_error_string(expected: &str, actual: Option<String>) -> Error {
-    let actual = actual.unwrap_or_else(|| "nothing".to_owned());
-    Error::with_msg(format!("Expected {expected}, found `{actual}`"))
+    Ok(condition)
 }
 
 #[cfg(test)]
@@ -754,4 +637,78 @@ mod test {
         let output = template.render(&runtime).unwrap();
         assert_eq!(output, "if true");
     }
+
+    #[test]
+    fn and_or_apply_right_to_left() {
+        let text = "{% if 1 == 2 and 1 == 2 or 1 == 1 %}if true{% else %}if false{% endif %}";
+        let template = parser::parse(text, &options()).map(Template::new).unwrap();
+
+        let runtime = RuntimeBuilder::new().build();
+        let output = template.render(&runtime).unwrap();
+        assert_eq!(output, "if false");
+    }
+
+    fn grouped_options() -> Language {
+        let mut options = options();
+        options.grouped_conditions = true;
+        options
+    }
+
+    #[test]
+    fn grouped_conditions() {
+        let runtime = RuntimeBuilder::new().build();
+        runtime.set_global("a".into(), Value::scalar(true));
+        let render = |text: &str| {
+            parser::parse(text, &grouped_options())
+                .map(Template::new)
+                .unwrap()
+                .render(&runtime)
+                .unwrap()
+        };
+
+        assert_eq!(
+            render("{% if 1 == 2 and 1 == 2 or 1 == 1 %}if true{% else %}if false{% endif %}"),
+            "if true"
+        );
+        assert_eq!(
+            render("{% if (1 == 1 or 2 == 2) and 3 != 3 %}if true{% else %}if false{% endif %}"),
+            "if false"
+        );
+        assert_eq!(
+            render("{% if not (a and 1 > 2) %}if true{% else %}if false{% endif %}"),
+            "if true"
+        );
+        assert_eq!(
+            render("{% unless not a %}unless true{% endunless %}"),
+            "unless true"
+        );
+    }
+
+    #[test]
+    fn grouped_conditions_change_precedence() {
+        let text = "{% if false and true or true %}T{% else %}F{% endif %}";
+        let runtime = RuntimeBuilder::new().build();
+        let render = |options: &Language| {
+            parser::parse(text, options)
+                .map(Template::new)
+                .unwrap()
+                .render(&runtime)
+                .unwrap()
+        };
+
+        assert_eq!(render(&options()), "F");
+        assert_eq!(render(&grouped_options()), "T");
+    }
+
+    #[test]
+    fn grouped_conditions_errors() {
+        for text in [
+            "{% if (1 == 1 %}{% endif %}",
+            "{% if 1 == 1) %}{% endif %}",
+            "{% if 1 == 1 | default: 2 %}{% endif %}",
+            "{% if %}{% endif %}",
+        ] {
+            assert!(parser::parse(text, &grouped_options()).is_err(), "{text}");
+        }
+    }
 }
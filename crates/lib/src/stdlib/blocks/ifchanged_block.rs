@@ -1,6 +1,9 @@
 use std::io::Write;
 
 use liquid_core::error::{ResultLiquidExt, ResultLiquidReplaceExt};
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{write_all_async, AsyncWrite, RenderFuture};
 use liquid_core::Language;
 use liquid_core::Renderable;
 use liquid_core::Result;
@@ -63,6 +66,18 @@ impl IfChanged {
     }
 }
 
+impl IfChanged {
+    /// `rendered`, if it changed since the last time.
+    fn changed(&self, rendered: Vec<u8>, runtime: &dyn Runtime) -> Option<String> {
+        let rendered = String::from_utf8(rendered).expect("render only writes UTF-8");
+        runtime
+            .registers()
+            .get_mut::<ChangedRegister>()
+            .has_changed(&rendered)
+            .then_some(rendered)
+    }
+}
+
 impl Renderable for IfChanged {
     fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
         let mut rendered = Vec::new();
@@ -70,17 +85,37 @@ impl Renderable for IfChanged {
             .render_to(&mut rendered, runtime)
             .trace_with(|| self.trace().into())?;
 
-        let rendered = String::from_utf8(rendered).expect("render only writes UTF-8");
-        if runtime
-            .registers()
-            .get_mut::<ChangedRegister>()
-            .has_changed(&rendered)
-        {
+        if let Some(rendered) = self.changed(rendered, runtime) {
             write!(writer, "{rendered}").replace("Failed to render")?;
         }
 
         Ok(())
     }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let mut rendered = Vec::new();
+            self.if_changed
+                .render_to_async(&mut rendered, runtime)
+                .await
+                .trace_with(|| self.trace().into())?;
+
+            if let Some(rendered) = self.changed(rendered, runtime) {
+                write_all_async(writer, rendered.as_bytes()).await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.if_changed.accept(visitor);
+    }
 }
 
 /// Remembers the content of the last rendered `ifstate` block.
@@ -1,6 +1,7 @@
 mod capture_block;
 mod case_block;
 mod comment_block;
+mod doc_block;
 mod for_block;
 mod if_block;
 mod ifchanged_block;
@@ -9,6 +10,7 @@ mod raw_block;
 pub use self::capture_block::CaptureBlock;
 pub use self::case_block::CaseBlock;
 pub use self::comment_block::CommentBlock;
+pub use self::doc_block::DocBlock;
 pub use self::for_block::ForBlock;
 pub use self::for_block::TableRowBlock;
 pub use self::if_block::IfBlock;
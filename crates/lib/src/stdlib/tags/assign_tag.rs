@@ -2,6 +2,7 @@ use std::io::Write;
 
 use liquid_core::error::ResultLiquidExt;
 use liquid_core::parser::FilterChain;
+use liquid_core::parser::ParseTreeVisitor;
 use liquid_core::Language;
 use liquid_core::Renderable;
 use liquid_core::Result;
@@ -45,13 +46,7 @@ impl ParseTag for AssignTag {
             .expect_str("=")
             .into_result_custom_msg("Assignment operator \"=\" expected.")?;
 
-        let src = arguments
-            .expect_next("FilterChain expected.")?
-            .expect_filter_chain(options)
-            .into_result()?;
-
-        // no more arguments should be supplied, trying to supply them is an error
-        arguments.expect_nothing()?;
+        let src = arguments.expect_condition_chain(options)?;
 
         Ok(Box::new(Assign { dst, src }))
     }
@@ -80,9 +75,17 @@ impl Renderable for Assign {
             .evaluate(runtime)
             .trace_with(|| self.trace().into())?
             .into_owned();
+        runtime
+            .resource_usage()
+            .increment_assign_score(&value)
+            .trace_with(|| self.trace().into())?;
         runtime.set_global(self.dst.clone(), value);
         Ok(())
     }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.src.accept(visitor);
+    }
 }
 
 #[cfg(test)]
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::io::Write;
 
 use liquid_core::error::{ResultLiquidExt, ResultLiquidReplaceExt};
+use liquid_core::parser::ParseTreeVisitor;
 use liquid_core::parser::TagToken;
 use liquid_core::parser::TryMatchToken;
 use liquid_core::Expression;
@@ -128,6 +129,12 @@ impl Renderable for Cycle {
         write!(writer, "{}", value.render()).replace("Failed to render")?;
         Ok(())
     }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        for value in &self.values {
+            value.accept(visitor);
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -0,0 +1,88 @@
+use liquid_core::runtime;
+use liquid_core::Language;
+use liquid_core::Renderable;
+use liquid_core::Result;
+use liquid_core::{ParseTag, TagReflection, TagTokenIter};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct EchoTag;
+
+impl EchoTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for EchoTag {
+    fn tag(&self) -> &'static str {
+        "echo"
+    }
+
+    fn description(&self) -> &'static str {
+        "output an expression, like `{{ }}` but usable within `liquid`"
+    }
+}
+
+impl ParseTag for EchoTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        // Like `{{ }}`, nothing is output without an expression.
+        if arguments.is_empty() {
+            return Ok(Box::new(runtime::Template::new(Vec::new())));
+        }
+        let src = arguments.expect_condition_chain(options)?;
+
+        Ok(Box::new(src))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    use crate::stdlib;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("echo".to_owned(), EchoTag.into());
+        options
+            .filters
+            .register("upcase".to_owned(), Box::new(stdlib::Upcase));
+        options
+    }
+
+    #[test]
+    fn echo_renders_filter_chain() {
+        let text = "{% echo name | upcase %}";
+        let template = parser::parse(text, &options())
+            .map(runtime::Template::new)
+            .unwrap();
+
+        let globals = liquid_core::object!({"name": "liquid"});
+        let runtime = RuntimeBuilder::new().set_globals(&globals).build();
+        let output = template.render(&runtime).unwrap();
+        assert_eq!(output, "LIQUID");
+    }
+
+    #[test]
+    fn echo_without_expression() {
+        let text = "a{% echo %}b";
+        let template = parser::parse(text, &options())
+            .map(runtime::Template::new)
+            .unwrap();
+
+        let runtime = RuntimeBuilder::new().build();
+        let output = template.render(&runtime).unwrap();
+        assert_eq!(output, "ab");
+    }
+}
@@ -1,7 +1,12 @@
+use std::collections::HashMap;
 use std::io::Write;
+use std::sync::Arc;
 
 use liquid_core::error::ResultLiquidExt;
-use liquid_core::model::KString;
+use liquid_core::model::{KString, KStringRef, ValueCow};
+use liquid_core::parser::ParseTreeVisitor;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
 use liquid_core::Expression;
 use liquid_core::Language;
 use liquid_core::Renderable;
@@ -81,45 +86,88 @@ struct Include {
     vars: Vec<(KString, Expression)>,
 }
 
-impl Renderable for Include {
-    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+impl Include {
+    fn name(&self, runtime: &dyn Runtime) -> Result<KString> {
         let value = self.partial.evaluate(runtime)?;
         if !value.is_scalar() {
             return Error::with_msg("Can only `include` strings")
                 .context("partial", format!("{}", value.source()))
                 .into_err();
         }
-        let name = value.to_kstr().into_owned();
-
-        {
-            // if there our additional variables creates a include object to access all the variables
-            // from e.g. { include 'image.html' path="foo.png" }
-            // then in image.html you could have <img src="{{include.path}}" />
-            let mut pass_through = std::collections::HashMap::new();
-            if !self.vars.is_empty() {
-                for (id, val) in &self.vars {
-                    let value = val
-                        .try_evaluate(runtime)
-                        .ok_or_else(|| Error::with_msg("failed to evaluate value"))?;
-
-                    pass_through.insert(id.as_ref(), value);
-                }
-            }
+        Ok(value.to_kstr().into_owned())
+    }
 
-            let scope = StackFrame::new(runtime, &pass_through);
-            let partial = scope
-                .partials()
-                .get(&name)
-                .trace_with(|| format!("{{% include {} %}}", self.partial).into())?;
+    fn pass_through<'r>(
+        &'r self,
+        runtime: &'r dyn Runtime,
+    ) -> Result<HashMap<KStringRef<'r>, ValueCow<'r>>> {
+        // if there our additional variables creates a include object to access all the variables
+        // from e.g. { include 'image.html' path="foo.png" }
+        // then in image.html you could have <img src="{{include.path}}" />
+        let mut pass_through = HashMap::new();
+        for (id, val) in &self.vars {
+            let value = val
+                .try_evaluate(runtime)
+                .ok_or_else(|| Error::with_msg("failed to evaluate value"))?;
+            pass_through.insert(id.as_ref(), value);
+        }
+        Ok(pass_through)
+    }
 
-            partial
-                .render_to(writer, &scope)
-                .trace_with(|| format!("{{% include {} %}}", self.partial).into())
+    fn partial(&self, scope: &dyn Runtime, name: &str) -> Result<Arc<dyn Renderable>> {
+        scope
+            .resource_usage()
+            .check_depth(scope.depth())
+            .trace_with(|| self.trace().into())?;
+        scope
+            .partials()
+            .get(name)
+            .trace_with(|| self.trace().into())
+    }
+
+    fn trace(&self) -> String {
+        format!("{{% include {} %}}", self.partial)
+    }
+}
+
+impl Renderable for Include {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let name = self.name(runtime)?;
+        let pass_through = self.pass_through(runtime)?;
+        let scope = StackFrame::new(runtime, &pass_through).with_name(name.clone());
+        self.partial(&scope, &name)?
+            .render_to(writer, &scope)
+            .trace_with(|| self.trace().into())
+            .context_key_with(|| self.partial.to_string().into())
+            .value_with(|| name.to_string().into())
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.accept(visitor), runtime).await;
+            let name = self.name(runtime)?;
+            let pass_through = self.pass_through(runtime)?;
+            let scope = StackFrame::new(runtime, &pass_through).with_name(name.clone());
+            self.partial(&scope, &name)?
+                .render_to_async(writer, &scope)
+                .await
+                .trace_with(|| self.trace().into())
                 .context_key_with(|| self.partial.to_string().into())
-                .value_with(|| name.to_string().into())?;
-        }
+                .value_with(|| name.to_string().into())
+        })
+    }
 
-        Ok(())
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_partial(&self.partial);
+        self.partial.accept(visitor);
+        for (_, val) in &self.vars {
+            val.accept(visitor);
+        }
     }
 }
 
@@ -147,7 +195,7 @@ mod test {
             true
         }
 
-        fn names(&self) -> Vec<&str> {
+        fn names(&self) -> Vec<borrow::Cow<'_, str>> {
             vec![]
         }
 
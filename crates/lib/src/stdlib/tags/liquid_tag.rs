@@ -0,0 +1,177 @@
+use std::io::Write;
+
+use liquid_core::error::ResultLiquidExt;
+use liquid_core::parser::ParseTreeVisitor;
+use liquid_core::runtime;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{AsyncWrite, RenderFuture};
+use liquid_core::Language;
+use liquid_core::Renderable;
+use liquid_core::Result;
+use liquid_core::Runtime;
+use liquid_core::{ParseTag, TagReflection, TagTokenIter};
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LiquidTag;
+
+impl LiquidTag {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TagReflection for LiquidTag {
+    fn tag(&self) -> &'static str {
+        "liquid"
+    }
+
+    fn description(&self) -> &'static str {
+        "group several tags, one per line and without delimiters"
+    }
+}
+
+impl ParseTag for LiquidTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        let template = match arguments.next() {
+            Some(markup) => markup.parse_liquid_markup(options)?,
+            None => Vec::new(),
+        };
+
+        // no more arguments should be supplied, trying to supply them is an error
+        arguments.expect_nothing()?;
+
+        let template = runtime::Template::new(template);
+        Ok(Box::new(Liquid { template }))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Liquid {
+    template: runtime::Template,
+}
+
+impl Renderable for Liquid {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        self.template
+            .render_to(writer, runtime)
+            .trace("{% liquid %}")
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            self.template
+                .render_to_async(writer, runtime)
+                .await
+                .trace("{% liquid %}")
+        })
+    }
+
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        self.template.accept(visitor);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use liquid_core::parser;
+    use liquid_core::runtime::RuntimeBuilder;
+
+    use crate::stdlib;
+
+    fn options() -> Language {
+        let mut options = Language::default();
+        options.tags.register("liquid".to_owned(), LiquidTag.into());
+        options
+            .tags
+            .register("echo".to_owned(), stdlib::EchoTag.into());
+        options
+            .tags
+            .register("assign".to_owned(), stdlib::AssignTag.into());
+        options
+            .blocks
+            .register("if".to_owned(), stdlib::IfBlock.into());
+        options
+            .blocks
+            .register("for".to_owned(), stdlib::ForBlock.into());
+        options
+    }
+
+    fn render(text: &str) -> Result<String> {
+        let template = parser::parse(text, &options()).map(runtime::Template::new)?;
+        let runtime = RuntimeBuilder::new().build();
+        template.render(&runtime)
+    }
+
+    #[test]
+    fn liquid_tag_parses_each_line() {
+        let text = concat!(
+            "{% liquid\n",
+            "  assign x = 1\n",
+            "\n",
+            "  if x\n",
+            "    for i in (1..3)\n",
+            "      echo i\n",
+            "    endfor\n",
+            "  endif\n",
+            "%}"
+        );
+        assert_eq!(render(text).unwrap(), "123");
+    }
+
+    #[test]
+    fn liquid_tag_can_be_empty() {
+        assert_eq!(render("a{% liquid %}b").unwrap(), "ab");
+    }
+
+    #[test]
+    fn liquid_tag_errors_keep_line_numbers() {
+        let text = "\n{% liquid\n  assign x = 1\n  unknown x\n%}";
+        let error = render(text).unwrap_err();
+        assert_eq!(error.get_location().unwrap().line(), 4);
+        assert_eq!(error.get_location().unwrap().column(), 3);
+    }
+
+    #[test]
+    fn liquid_tag_requires_closed_blocks() {
+        assert!(render("{% liquid if true\n echo 'a' %}").is_err());
+    }
+
+    #[test]
+    fn liquid_tag_keeps_warnings() {
+        let mut options = options();
+        options.error_mode = parser::ErrorMode::Warn;
+        let text = "{% liquid\n  echo 'a'\n  assign = 1\n  echo 'b'\n%}";
+        let (template, warnings) = parser::parse_with_warnings(text, &options).unwrap();
+        let template = runtime::Template::new(template);
+        let runtime = RuntimeBuilder::new().build();
+        assert_eq!(template.render(&runtime).unwrap(), "ab");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].get_location().unwrap().line(), 3);
+    }
+
+    #[test]
+    fn liquid_tag_counts_towards_max_depth() {
+        let mut options = options();
+        options.max_depth = 2;
+        let text = "{% if true %}{% liquid\n  if true\n  endif\n%}{% endif %}";
+        assert!(parser::parse(text, &options).is_ok());
+        let text =
+            "{% if true %}{% if true %}{% liquid\n  if true\n  endif\n%}{% endif %}{% endif %}";
+        assert!(parser::parse(text, &options).is_err());
+    }
+}
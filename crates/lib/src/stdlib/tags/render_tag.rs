@@ -1,12 +1,16 @@
+use std::collections::HashMap;
 use std::io::Write;
+use std::sync::Arc;
 
 use liquid_core::error::ResultLiquidExt;
-use liquid_core::model::KString;
+use liquid_core::model::{KString, KStringRef, ValueCow};
 use liquid_core::parser::TryMatchToken;
 use liquid_core::runtime::GlobalFrame;
 use liquid_core::runtime::Interrupt;
 use liquid_core::runtime::InterruptRegister;
 use liquid_core::runtime::SandboxedStackFrame;
+#[cfg(feature = "async")]
+use liquid_core::runtime::{resolve_lazy_values, AsyncWrite, RenderFuture};
 use liquid_core::Expression;
 use liquid_core::Language;
 use liquid_core::Renderable;
@@ -17,6 +21,7 @@ use liquid_core::{ParseTag, TagReflection, TagTokenIter};
 
 use crate::stdlib::ForloopObject;
 use crate::stdlib::RangeExpression;
+use liquid_core::parser::ParseTreeVisitor;
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct RenderTag;
@@ -150,89 +155,167 @@ struct Render {
     vars: Vec<(KString, Expression)>,
 }
 
-impl Renderable for Render {
-    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+impl Render {
+    fn name(&self, runtime: &dyn Runtime) -> Result<KString> {
         let value = self.partial.evaluate(runtime)?;
         if !value.is_scalar() {
             return Error::with_msg("Can only `include` strings")
                 .context("partial", format!("{}", value.source()))
                 .into_err();
         }
-        let name = value.to_kstr().into_owned();
+        Ok(value.to_kstr().into_owned())
+    }
+
+    fn root<'r>(
+        &'r self,
+        runtime: &'r dyn Runtime,
+    ) -> Result<HashMap<KStringRef<'r>, ValueCow<'r>>> {
+        let mut root = HashMap::new();
+        for (id, val) in &self.vars {
+            let value = val
+                .try_evaluate(runtime)
+                .ok_or_else(|| Error::with_msg("failed to evaluate value"))?;
+
+            root.insert(id.as_ref(), value);
+        }
+        Ok(root)
+    }
+
+    fn partial(&self, scope: &dyn Runtime, name: &str) -> Result<Arc<dyn Renderable>> {
+        scope
+            .resource_usage()
+            .check_depth(scope.depth())
+            .trace_with(|| self.trace().into())?;
+
+        scope
+            .partials()
+            .get(name)
+            .or_else(|_| scope.partials().get(&format!("{name}.liquid")))
+            .trace_with(|| self.trace().into())
+    }
+
+    fn trace(&self) -> String {
+        format!("{{% render {} %}}", self.partial)
+    }
+}
+
+impl Renderable for Render {
+    fn render_to(&self, writer: &mut dyn Write, runtime: &dyn Runtime) -> Result<()> {
+        let name = self.name(runtime)?;
 
         if let Some((range, var_name)) = &self.for_ {
-            let range = range
-                .evaluate(runtime)
-                .trace_with(|| format!("{{% render {} %}}", self.partial).into())?;
+            let range = range.evaluate(runtime).trace_with(|| self.trace().into())?;
             let array = range.evaluate()?;
 
-            if !array.is_empty() {
+            let len = array.len();
+            for (i, v) in array.into_iter().enumerate() {
+                let forloop = ForloopObject::new(i, len);
+
+                let mut root = self.root(runtime)?;
+                root.insert("forloop".into(), ValueCow::Borrowed(&forloop));
+                root.insert(var_name.as_ref(), v);
+
+                let scope = GlobalFrame::new(
+                    SandboxedStackFrame::new(runtime, &root).with_name(name.clone()),
+                );
+
+                self.partial(&scope, &name)?
+                    .render_to(writer, &scope)
+                    .trace_with(|| self.trace().into())
+                    .context_key("index")
+                    .value_with(|| format!("{}", i + 1).into())?;
+
+                // given that we're at the end of the loop body
+                // already, dealing with a `continue` signal is just
+                // clearing the interrupt and carrying on as normal. A
+                // `break` requires some special handling, though.
+                let current_interrupt = scope.registers().get_mut::<InterruptRegister>().reset();
+                if let Some(Interrupt::Break) = current_interrupt {
+                    break;
+                }
+            }
+        } else {
+            let root = self.root(runtime)?;
+            let scope =
+                GlobalFrame::new(SandboxedStackFrame::new(runtime, &root).with_name(name.clone()));
+
+            self.partial(&scope, &name)?
+                .render_to(writer, &scope)
+                .trace_with(|| self.trace().into())
+                .context_key_with(|| self.partial.to_string().into())
+                .value_with(|| name.to_string().into())?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            resolve_lazy_values(|visitor| self.accept(visitor), runtime).await;
+            let name = self.name(runtime)?;
+
+            if let Some((range, var_name)) = &self.for_ {
+                let range = range.evaluate(runtime).trace_with(|| self.trace().into())?;
+                let array = range.evaluate()?;
+
                 let len = array.len();
                 for (i, v) in array.into_iter().enumerate() {
                     let forloop = ForloopObject::new(i, len);
 
-                    let mut root = std::collections::HashMap::new();
-                    for (id, val) in &self.vars {
-                        let value = val
-                            .try_evaluate(runtime)
-                            .ok_or_else(|| Error::with_msg("failed to evaluate value"))?;
-
-                        root.insert(id.as_ref(), value);
-                    }
-                    root.insert("forloop".into(), liquid_core::ValueCow::Borrowed(&forloop));
+                    let mut root = self.root(runtime)?;
+                    root.insert("forloop".into(), ValueCow::Borrowed(&forloop));
                     root.insert(var_name.as_ref(), v);
 
-                    let scope = GlobalFrame::new(SandboxedStackFrame::new(runtime, &root));
-
-                    let partial = scope
-                        .partials()
-                        .get(&name)
-                        .or_else(|_| scope.partials().get(&format!("{name}.liquid")))
-                        .trace_with(|| format!("{{% render {} %}}", self.partial).into())?;
+                    let scope = GlobalFrame::new(
+                        SandboxedStackFrame::new(runtime, &root).with_name(name.clone()),
+                    );
 
-                    partial
-                        .render_to(writer, &scope)
-                        .trace_with(|| format!("{{% render {} %}}", self.partial).into())
+                    self.partial(&scope, &name)?
+                        .render_to_async(&mut *writer, &scope)
+                        .await
+                        .trace_with(|| self.trace().into())
                         .context_key("index")
                         .value_with(|| format!("{}", i + 1).into())?;
 
-                    // given that we're at the end of the loop body
-                    // already, dealing with a `continue` signal is just
-                    // clearing the interrupt and carrying on as normal. A
-                    // `break` requires some special handling, though.
+                    // See `render_to` for `break` and `continue`.
                     let current_interrupt =
                         scope.registers().get_mut::<InterruptRegister>().reset();
                     if let Some(Interrupt::Break) = current_interrupt {
                         break;
                     }
                 }
+            } else {
+                let root = self.root(runtime)?;
+                let scope = GlobalFrame::new(
+                    SandboxedStackFrame::new(runtime, &root).with_name(name.clone()),
+                );
+
+                self.partial(&scope, &name)?
+                    .render_to_async(writer, &scope)
+                    .await
+                    .trace_with(|| self.trace().into())
+                    .context_key_with(|| self.partial.to_string().into())
+                    .value_with(|| name.to_string().into())?;
             }
-        } else {
-            let mut root = std::collections::HashMap::new();
-            for (id, val) in &self.vars {
-                let value = val
-                    .try_evaluate(runtime)
-                    .ok_or_else(|| Error::with_msg("failed to evaluate value"))?;
-
-                root.insert(id.as_ref(), value);
-            }
-
-            let scope = GlobalFrame::new(SandboxedStackFrame::new(runtime, &root));
 
-            let partial = scope
-                .partials()
-                .get(&name)
-                .or_else(|_| scope.partials().get(&format!("{name}.liquid")))
-                .trace_with(|| format!("{{% render {} %}}", self.partial).into())?;
+            Ok(())
+        })
+    }
 
-            partial
-                .render_to(writer, &scope)
-                .trace_with(|| format!("{{% render {} %}}", self.partial).into())
-                .context_key_with(|| self.partial.to_string().into())
-                .value_with(|| name.to_string().into())?;
+    fn accept(&self, visitor: &mut dyn ParseTreeVisitor) {
+        visitor.visit_partial(&self.partial);
+        self.partial.accept(visitor);
+        if let Some((range, _)) = &self.for_ {
+            range.accept(visitor);
+        }
+        for (_, val) in &self.vars {
+            val.accept(visitor);
         }
-
-        Ok(())
     }
 }
 
@@ -257,7 +340,7 @@ mod test {
             true
         }
 
-        fn names(&self) -> Vec<&str> {
+        fn names(&self) -> Vec<std::borrow::Cow<'_, str>> {
             vec![]
         }
 
@@ -45,10 +45,17 @@ pub mod model {
 
 pub use crate::parser::*;
 pub use crate::template::*;
+pub use liquid_core::model::DropView;
 pub use liquid_core::model::{_ObjectView as ObjectView, _ValueView as ValueView};
 pub use liquid_core::object;
+pub use liquid_core::parser::ErrorMode;
+pub use liquid_core::parser::ParseTreeVisitor;
+pub use liquid_core::parser::{Doc, DocParam};
+pub use liquid_core::runtime::ResourceLimits;
+pub use liquid_core::runtime::Runtime;
+pub use liquid_core::runtime::Timing;
 pub use liquid_core::to_object;
 pub use liquid_core::Error;
 pub use liquid_core::Object;
 #[doc(hidden)]
-pub use liquid_derive::{ObjectView, ValueView};
+pub use liquid_derive::{DropView, ObjectView, ValueView};
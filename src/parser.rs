@@ -1,3 +1,4 @@
+use std::borrow;
 use std::path;
 use std::sync;
 
@@ -8,6 +9,8 @@ use liquid_core::runtime;
 use super::Template;
 use crate::reflection;
 use liquid_core::partials;
+#[cfg(feature = "extra")]
+use liquid_lib::extra;
 #[cfg(feature = "stdlib")]
 use liquid_lib::stdlib;
 
@@ -21,6 +24,16 @@ where
     tags: parser::PluginRegistry<Box<dyn parser::ParseTag>>,
     filters: parser::PluginRegistry<Box<dyn parser::ParseFilter>>,
     partials: Option<P>,
+    resource_limits: runtime::ResourceLimits,
+    error_mode: parser::ErrorMode,
+    strict_variables: bool,
+    strict_filters: bool,
+    string_escapes: bool,
+    grouped_conditions: bool,
+    trim_blocks: bool,
+    lstrip_blocks: bool,
+    tag_delimiters: (String, String),
+    output_delimiters: (String, String),
 }
 
 impl ParserBuilder<Partials> {
@@ -46,9 +59,11 @@ where
             .tag(stdlib::BreakTag)
             .tag(stdlib::ContinueTag)
             .tag(stdlib::CycleTag)
+            .tag(stdlib::EchoTag)
             .tag(stdlib::IncludeTag)
             .tag(stdlib::IncrementTag)
             .tag(stdlib::DecrementTag)
+            .tag(stdlib::LiquidTag)
             .tag(stdlib::RenderTag)
             .block(stdlib::RawBlock)
             .block(stdlib::IfBlock)
@@ -57,6 +72,7 @@ where
             .block(stdlib::ForBlock)
             .block(stdlib::TableRowBlock)
             .block(stdlib::CommentBlock)
+            .block(stdlib::DocBlock)
             .block(stdlib::CaptureBlock)
             .block(stdlib::CaseBlock)
             .filter(stdlib::Abs)
@@ -109,6 +125,33 @@ where
             .filter(stdlib::Where)
     }
 
+    #[cfg(feature = "extra")]
+    /// Add template inheritance, with `{% layout %}`/`{% extends %}`, `{% block %}` and
+    /// `{{ block.super }}`
+    pub fn layouts(self) -> Self {
+        self.tag(extra::LayoutTag)
+            .tag(extra::ExtendsTag)
+            .block(extra::BlockBlock)
+    }
+
+    #[cfg(feature = "extra")]
+    /// Add named slots, filled with `{% content_for %}` and read with `{% yield %}` and the
+    /// `content_for` filter
+    pub fn content_for(self) -> Self {
+        self.tag(extra::YieldTag)
+            .block(extra::ContentForBlock)
+            .filter(extra::ContentFor)
+    }
+
+    #[cfg(feature = "extra")]
+    /// Add macros, defined with `{% macro %}`, rendered with `{% call %}` and shared with
+    /// `{% import %}`
+    pub fn macros(self) -> Self {
+        self.tag(extra::CallTag)
+            .tag(extra::ImportTag)
+            .block(extra::MacroBlock)
+    }
+
     /// Inserts a new custom block into the parser
     pub fn block<B: Into<Box<dyn parser::ParseBlock>>>(mut self, block: B) -> Self {
         let block = block.into();
@@ -139,15 +182,264 @@ where
             tags,
             filters,
             partials: _partials,
+            resource_limits,
+            error_mode,
+            strict_variables,
+            strict_filters,
+            string_escapes,
+            grouped_conditions,
+            trim_blocks,
+            lstrip_blocks,
+            tag_delimiters,
+            output_delimiters,
         } = self;
         ParserBuilder {
             blocks,
             tags,
             filters,
             partials: Some(partials),
+            resource_limits,
+            error_mode,
+            strict_variables,
+            strict_filters,
+            string_escapes,
+            grouped_conditions,
+            trim_blocks,
+            lstrip_blocks,
+            tag_delimiters,
+            output_delimiters,
         }
     }
 
+    /// Stop rendering templates with an error once any of `limits` is exceeded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use liquid::ResourceLimits;
+    ///
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .resource_limits(ResourceLimits::new().with_render_score_limit(50))
+    ///     .build().unwrap()
+    ///     .parse("{% for i in (1..100) %}{{ i }}{% endfor %}").unwrap();
+    ///
+    /// let globals = liquid::Object::new();
+    /// assert!(template.render(&globals).is_err());
+    /// ```
+    pub fn resource_limits(mut self, limits: runtime::ResourceLimits) -> Self {
+        self.resource_limits = limits;
+        self
+    }
+
+    /// Set how malformed markup is handled, defaulting to [`ErrorMode::Strict`].
+    ///
+    /// Under [`ErrorMode::Warn`], the malformed markup is reported by [`Template::warnings`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use liquid::ErrorMode;
+    ///
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .error_mode(ErrorMode::Warn)
+    ///     .build().unwrap()
+    ///     .parse("Hello{{ | }}, {{ name }}!").unwrap();
+    /// assert_eq!(template.warnings().len(), 1);
+    ///
+    /// let globals = liquid::object!({ "name": "World" });
+    /// assert_eq!(template.render(&globals).unwrap(), "Hello, World!");
+    /// ```
+    ///
+    /// [`ErrorMode::Strict`]: parser::ErrorMode::Strict
+    /// [`ErrorMode::Warn`]: parser::ErrorMode::Warn
+    pub fn error_mode(mut self, mode: parser::ErrorMode) -> Self {
+        self.error_mode = mode;
+        self
+    }
+
+    /// Whether rendering an unknown variable is an error, defaulting to `true`.
+    ///
+    /// Otherwise, unknown variables render as `nil` and are reported by
+    /// [`RenderReport::warnings`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .strict_variables(false)
+    ///     .build().unwrap()
+    ///     .parse("Hello {{ name }}{{ title }}!").unwrap();
+    ///
+    /// let globals = liquid::object!({ "name": "World" });
+    /// let (output, report) = template.render_builder().render(&globals).unwrap();
+    /// assert_eq!(output, "Hello World!");
+    /// assert_eq!(report.warnings().len(), 1);
+    /// ```
+    ///
+    /// [`RenderReport::warnings`]: crate::RenderReport::warnings
+    pub fn strict_variables(mut self, strict: bool) -> Self {
+        self.strict_variables = strict;
+        self
+    }
+
+    /// Whether parsing an unknown filter is an error, defaulting to `true`.
+    ///
+    /// Otherwise, unknown filters pass their input through unchanged and are reported by
+    /// [`RenderReport::warnings`].
+    ///
+    /// [`RenderReport::warnings`]: crate::RenderReport::warnings
+    pub fn strict_filters(mut self, strict: bool) -> Self {
+        self.strict_filters = strict;
+        self
+    }
+
+    /// Whether backslash escapes in string literals are replaced, defaulting to `false`.
+    ///
+    /// The escapes are `\\`, `\"`, `\'`, `\n`, `\r`, `\t`, `\0` and `\u{...}` for any Unicode
+    /// character, and any other backslash is kept.  By default, backslashes are kept as-is, like
+    /// in Ruby's liquid, though a quote after one doesn't end the string either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .string_escapes(true)
+    ///     .build().unwrap()
+    ///     .parse(r#"{{ "He said \"hi\"\u{21}" }}"#).unwrap();
+    ///
+    /// let globals = liquid::Object::new();
+    /// assert_eq!(template.render(&globals).unwrap(), r#"He said "hi"!"#);
+    /// ```
+    pub fn string_escapes(mut self, escapes: bool) -> Self {
+        self.string_escapes = escapes;
+        self
+    }
+
+    /// Whether conditions may use `( )` grouping and `not`, defaulting to `false`.
+    ///
+    /// This also changes the precedence of `and` and `or`, in `if` and `unless` as well as in
+    /// outputs, `assign`, `echo` and filter arguments.  By default, `and` and `or` apply from
+    /// right to left, like in Ruby's liquid, so `false and true or true` is `false`.  With
+    /// grouped conditions, the operators bind like in most languages: `not`, then comparisons,
+    /// then `and`, then `or`, so the same condition is `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .grouped_conditions(true)
+    ///     .build().unwrap()
+    ///     .parse("{% if (sale or clearance) and not sold_out %}Buy now{% endif %}").unwrap();
+    ///
+    /// let globals = liquid::object!({ "sale": false, "clearance": true, "sold_out": false });
+    /// assert_eq!(template.render(&globals).unwrap(), "Buy now");
+    ///
+    /// let text = "{{ false and true or true }}";
+    /// let parser = liquid::ParserBuilder::with_stdlib();
+    /// let template = parser.build().unwrap().parse(text).unwrap();
+    /// assert_eq!(template.render(&globals).unwrap(), "false");
+    ///
+    /// let parser = liquid::ParserBuilder::with_stdlib().grouped_conditions(true);
+    /// let template = parser.build().unwrap().parse(text).unwrap();
+    /// assert_eq!(template.render(&globals).unwrap(), "true");
+    /// ```
+    pub fn grouped_conditions(mut self, grouped: bool) -> Self {
+        self.grouped_conditions = grouped;
+        self
+    }
+
+    /// Whether the first newline after a tag is removed, defaulting to `false`.
+    ///
+    /// Lines holding only a tag then leave no blank line behind.  A tag ending with `+%}` keeps
+    /// its newline.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .trim_blocks(true)
+    ///     .build().unwrap()
+    ///     .parse("{% if true %}\nyes\n{% endif %}\n{% if true +%}\nno\n{% endif %}").unwrap();
+    ///
+    /// let globals = liquid::Object::new();
+    /// assert_eq!(template.render(&globals).unwrap(), "yes\n\nno\n");
+    /// ```
+    pub fn trim_blocks(mut self, trim: bool) -> Self {
+        self.trim_blocks = trim;
+        self
+    }
+
+    /// Whether the spaces and tabs from the start of a line up to a tag are removed, defaulting
+    /// to `false`.
+    ///
+    /// Along with [`ParserBuilder::trim_blocks`], tags can then be indented on lines of their
+    /// own without showing up in the output.  A tag starting with `{%+` keeps its indentation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .trim_blocks(true)
+    ///     .lstrip_blocks(true)
+    ///     .build().unwrap()
+    ///     .parse("<ul>\n  {% for i in (1..2) %}\n  <li>{{ i }}</li>\n  {% endfor %}\n</ul>").unwrap();
+    ///
+    /// let globals = liquid::Object::new();
+    /// assert_eq!(
+    ///     template.render(&globals).unwrap(),
+    ///     "<ul>\n  <li>1</li>\n  <li>2</li>\n</ul>"
+    /// );
+    /// ```
+    pub fn lstrip_blocks(mut self, lstrip: bool) -> Self {
+        self.lstrip_blocks = lstrip;
+        self
+    }
+
+    /// Delimit tags with `start` and `end` instead of `{%` and `%}`.
+    ///
+    /// Delimiters must be valid for [`parser::Delimiters::new`], or building the parser fails.
+    ///
+    /// [`parser::Delimiters::new`]: parser::Delimiters::new
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .tag_delimiters("<%", "%>")
+    ///     .build().unwrap()
+    ///     .parse("<% if show -%> {% raw %} <%- endif %>").unwrap();
+    ///
+    /// let globals = liquid::object!({ "show": true });
+    /// assert_eq!(template.render(&globals).unwrap(), "{% raw %}");
+    /// ```
+    pub fn tag_delimiters(mut self, start: &str, end: &str) -> Self {
+        self.tag_delimiters = (start.to_owned(), end.to_owned());
+        self
+    }
+
+    /// Delimit outputs with `start` and `end` instead of `{{` and `}}`, to keep the `{{ }}` of
+    /// another language, like Vue or Handlebars, as-is.
+    ///
+    /// Delimiters must be valid for [`parser::Delimiters::new`], or building the parser fails.
+    ///
+    /// [`parser::Delimiters::new`]: parser::Delimiters::new
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .output_delimiters("[[", "]]")
+    ///     .build().unwrap()
+    ///     .parse("<p>{{ message }}</p><p>[[ user | upcase ]]</p>").unwrap();
+    ///
+    /// let globals = liquid::object!({ "user": "Jo" });
+    /// assert_eq!(template.render(&globals).unwrap(), "<p>{{ message }}</p><p>JO</p>");
+    /// ```
+    pub fn output_delimiters(mut self, start: &str, end: &str) -> Self {
+        self.output_delimiters = (start.to_owned(), end.to_owned());
+        self
+    }
+
     /// Create a parser
     pub fn build(self) -> Result<Parser> {
         let Self {
@@ -155,19 +447,47 @@ where
             tags,
             filters,
             partials,
+            resource_limits,
+            error_mode,
+            strict_variables,
+            strict_filters,
+            string_escapes,
+            grouped_conditions,
+            trim_blocks,
+            lstrip_blocks,
+            tag_delimiters,
+            output_delimiters,
         } = self;
 
         let mut options = parser::Language::empty();
         options.blocks = blocks;
         options.tags = tags;
         options.filters = filters;
+        options.max_depth = resource_limits.max_depth();
+        options.error_mode = error_mode;
+        options.strict_filters = strict_filters;
+        options.string_escapes = string_escapes;
+        options.grouped_conditions = grouped_conditions;
+        options.trim_blocks = trim_blocks;
+        options.lstrip_blocks = lstrip_blocks;
+        options.delimiters = parser::Delimiters::new(
+            &tag_delimiters.0,
+            &tag_delimiters.1,
+            &output_delimiters.0,
+            &output_delimiters.1,
+        )?;
         let options = sync::Arc::new(options);
         let partials = partials
             .map(|p| p.compile(options.clone()))
             .map(|r| r.map(Some))
             .unwrap_or(Ok(None))?
             .map(|p| p.into());
-        let p = Parser { options, partials };
+        let p = Parser {
+            options,
+            partials,
+            resource_limits,
+            strict_variables,
+        };
         Ok(p)
     }
 }
@@ -182,6 +502,16 @@ where
             tags: Default::default(),
             filters: Default::default(),
             partials: Default::default(),
+            resource_limits: Default::default(),
+            error_mode: Default::default(),
+            strict_variables: true,
+            strict_filters: true,
+            string_escapes: false,
+            grouped_conditions: false,
+            trim_blocks: false,
+            lstrip_blocks: false,
+            tag_delimiters: ("{%".to_owned(), "%}".to_owned()),
+            output_delimiters: ("{{".to_owned(), "}}".to_owned()),
         }
     }
 }
@@ -202,7 +532,7 @@ where
         Box::new(self.filters.plugins().map(|p| p.reflection()))
     }
 
-    fn partials(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+    fn partials(&self) -> Box<dyn Iterator<Item = borrow::Cow<'_, str>> + '_> {
         Box::new(
             self.partials
                 .as_ref()
@@ -212,10 +542,12 @@ where
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct Parser {
     options: sync::Arc<parser::Language>,
     partials: Option<sync::Arc<dyn runtime::PartialStore + Send + Sync>>,
+    resource_limits: runtime::ResourceLimits,
+    strict_variables: bool,
 }
 
 impl Parser {
@@ -239,10 +571,13 @@ impl Parser {
     /// ```
     ///
     pub fn parse(&self, text: &str) -> Result<Template> {
-        let template = parser::parse(text, &self.options).map(runtime::Template::new)?;
+        let (elements, warnings) = parser::parse_with_warnings(text, &self.options)?;
         Ok(Template {
-            template,
+            template: runtime::Template::new(elements),
+            warnings,
             partials: self.partials.clone(),
+            resource_limits: self.resource_limits,
+            strict_variables: self.strict_variables,
         })
     }
 
@@ -285,6 +620,17 @@ impl Parser {
     }
 }
 
+impl Default for Parser {
+    fn default() -> Self {
+        Self {
+            options: Default::default(),
+            partials: Default::default(),
+            resource_limits: Default::default(),
+            strict_variables: true,
+        }
+    }
+}
+
 impl reflection::ParserReflection for Parser {
     fn blocks(&self) -> Box<dyn Iterator<Item = &dyn parser::BlockReflection> + '_> {
         Box::new(self.options.blocks.plugins().map(|p| p.reflection()))
@@ -298,7 +644,7 @@ impl reflection::ParserReflection for Parser {
         Box::new(self.options.filters.plugins().map(|p| p.reflection()))
     }
 
-    fn partials(&self) -> Box<dyn Iterator<Item = &str> + '_> {
+    fn partials(&self) -> Box<dyn Iterator<Item = borrow::Cow<'_, str>> + '_> {
         Box::new(self.partials.as_ref().into_iter().flat_map(|s| s.names()))
     }
 }
@@ -1,3 +1,5 @@
+use std::borrow;
+
 use liquid_core::parser;
 
 pub use parser::BlockReflection;
@@ -11,5 +13,5 @@ pub trait ParserReflection {
 
     fn filters(&self) -> Box<dyn Iterator<Item = &dyn FilterReflection> + '_>;
 
-    fn partials(&self) -> Box<dyn Iterator<Item = &str> + '_>;
+    fn partials(&self) -> Box<dyn Iterator<Item = borrow::Cow<'_, str>> + '_>;
 }
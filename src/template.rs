@@ -1,35 +1,544 @@
 use std::io::Write;
 use std::sync;
 
-use liquid_core::error::Result;
+use liquid_core::error::{Error, Result, ResultLiquidExt, ResultLiquidReplaceExt};
+use liquid_core::model::KString;
 use liquid_core::runtime;
+use liquid_core::runtime::LayoutRegister;
 use liquid_core::runtime::PartialStore;
 use liquid_core::runtime::Renderable;
+use liquid_core::runtime::Runtime;
 
 pub struct Template {
     pub(crate) template: runtime::Template,
+    pub(crate) warnings: Vec<Error>,
     pub(crate) partials: Option<sync::Arc<dyn PartialStore + Send + Sync>>,
+    pub(crate) resource_limits: runtime::ResourceLimits,
+    pub(crate) strict_variables: bool,
 }
 
 impl Template {
     /// Renders an instance of the Template, using the given globals.
     pub fn render(&self, globals: &dyn crate::ObjectView) -> Result<String> {
+        let (output, _report) = self.render_builder().render(globals)?;
+        Ok(output)
+    }
+
+    /// Renders an instance of the Template, using the given globals.
+    pub fn render_to(&self, writer: &mut dyn Write, globals: &dyn crate::ObjectView) -> Result<()> {
+        self.render_builder().render_to(writer, globals)?;
+        Ok(())
+    }
+
+    /// Renders an instance of the Template without blocking, using the given globals.
+    ///
+    /// Lazy values made from futures are awaited, as are the tags rendering asynchronously, see
+    /// [`Renderable::render_to_async`].  Like all rendering, the future isn't `Send`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use liquid::model::{LazyValue, Value};
+    ///
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .build().unwrap()
+    ///     .parse("{{ stock }} left").unwrap();
+    ///
+    /// let mut globals = liquid::Object::new();
+    /// let stock = LazyValue::from_future(|| async { 3 });
+    /// globals.insert("stock".into(), Value::Lazy(stock));
+    ///
+    /// let output = futures_executor::block_on(template.render_async(&globals)).unwrap();
+    /// assert_eq!(output, "3 left");
+    /// ```
+    ///
+    /// [`Renderable::render_to_async`]: liquid_core::Renderable::render_to_async
+    #[cfg(feature = "async")]
+    pub async fn render_async(&self, globals: &dyn crate::ObjectView) -> Result<String> {
+        let (output, _report) = self.render_builder().render_async(globals).await?;
+        Ok(output)
+    }
+
+    /// Renders an instance of the Template to an asynchronous `writer`, using the given globals.
+    ///
+    /// For Tokio's `AsyncWrite`, see the `compat` module of `tokio-util`.
+    #[cfg(feature = "async")]
+    pub async fn render_to_async(
+        &self,
+        writer: &mut (dyn runtime::AsyncWrite + Unpin),
+        globals: &dyn crate::ObjectView,
+    ) -> Result<()> {
+        self.render_builder()
+            .render_to_async(writer, globals)
+            .await?;
+        Ok(())
+    }
+
+    /// Configure how to render the Template, like with an error placeholder or profiling, and
+    /// get what the render reported besides its output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .build().unwrap()
+    ///     .parse("{{ price | divided_by: 0 }} {{ title }}").unwrap();
+    ///
+    /// let globals = liquid::object!({ "price": 10, "title": "Shoes" });
+    /// let (output, report) = template
+    ///     .render_builder()
+    ///     .error_placeholder("N/A")
+    ///     .profiling(true)
+    ///     .render(&globals)
+    ///     .unwrap();
+    /// assert_eq!(output, "N/A Shoes");
+    /// assert_eq!(report.warnings().len(), 1);
+    /// assert_eq!(report.profile().unwrap().len(), 2);
+    /// ```
+    pub fn render_builder(&self) -> RenderBuilder<'_> {
+        RenderBuilder {
+            template: self,
+            error_placeholder: None,
+            profiling: false,
+            stream: false,
+        }
+    }
+
+    /// Renders the template, then the layouts it sets in its place, see [`LayoutRegister`].
+    fn render_layouts(
+        &self,
+        writer: &mut dyn Write,
+        runtime: &dyn Runtime,
+        stream: bool,
+    ) -> Result<()> {
+        let mut writer = LayoutWriter {
+            writer,
+            layout: runtime.registers().get_mut::<LayoutRegister>().clone(),
+        };
+        if stream {
+            self.template.stream_to(&mut writer, runtime)?;
+        } else {
+            self.template.render_to(&mut writer, runtime)?;
+        }
+        let mut rendered = 0;
+        while let Some(layout) = next_layout(runtime, &mut rendered)? {
+            let partial = runtime
+                .partials()
+                .get(&layout)
+                .trace_with(|| format!("{{% layout {layout} %}}").into())?;
+            partial
+                .render_to(&mut writer, runtime)
+                .trace_with(|| format!("{{% layout {layout} %}}").into())?;
+            if stream {
+                writer.flush().replace("Failed to render")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the template, then the layouts it sets in its place, see [`LayoutRegister`].
+    #[cfg(feature = "async")]
+    async fn render_layouts_async(
+        &self,
+        writer: &mut (dyn runtime::AsyncWrite + Unpin),
+        runtime: &dyn Runtime,
+    ) -> Result<()> {
+        let mut writer = AsyncLayoutWriter {
+            writer,
+            layout: runtime.registers().get_mut::<LayoutRegister>().clone(),
+        };
+        self.template.render_to_async(&mut writer, runtime).await?;
+        let mut rendered = 0;
+        while let Some(layout) = next_layout(runtime, &mut rendered)? {
+            let partial = runtime
+                .partials()
+                .get(&layout)
+                .trace_with(|| format!("{{% layout {layout} %}}").into())?;
+            partial
+                .render_to_async(&mut writer, runtime)
+                .await
+                .trace_with(|| format!("{{% layout {layout} %}}").into())?;
+        }
+        Ok(())
+    }
+
+    /// Malformed markup that was skipped while parsing, see [`ParserBuilder::error_mode`].
+    ///
+    /// Problems found while rendering are reported by [`RenderReport::warnings`].
+    ///
+    /// [`ParserBuilder::error_mode`]: crate::ParserBuilder::error_mode
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Walks the parse tree of the Template, reporting each node to `visitor`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use liquid::model::KString;
+    /// use liquid::{ParseTreeVisitor, ValueView};
+    ///
+    /// #[derive(Default)]
+    /// struct Variables(Vec<KString>);
+    ///
+    /// impl ParseTreeVisitor for Variables {
+    ///     fn visit_variable(&mut self, variable: &liquid_core::runtime::Variable) {
+    ///         self.0.push(variable.name().to_kstr().into_owned());
+    ///     }
+    /// }
+    ///
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .build().unwrap()
+    ///     .parse("{% if show %}{{ product.title | append: suffix }}{% endif %}").unwrap();
+    ///
+    /// let mut variables = Variables::default();
+    /// template.accept(&mut variables);
+    /// assert_eq!(variables.0, ["show", "product", "suffix"]);
+    /// ```
+    pub fn accept(&self, visitor: &mut dyn crate::ParseTreeVisitor) {
+        self.template.accept(visitor);
+    }
+
+    /// Documentation of the Template, from its first `{% doc %}` block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .build().unwrap()
+    ///     .parse("{% doc %}\n  @param {string} name - Who to greet\n{% enddoc %}Hi {{ name }}")
+    ///     .unwrap();
+    ///
+    /// let doc = template.doc().unwrap();
+    /// assert_eq!(doc.params()[0].name(), "name");
+    /// assert_eq!(doc.params()[0].type_name(), Some("string"));
+    /// ```
+    pub fn doc(&self) -> Option<crate::Doc> {
+        #[derive(Default)]
+        struct FirstDoc(Option<crate::Doc>);
+
+        impl crate::ParseTreeVisitor for FirstDoc {
+            fn visit_doc(&mut self, doc: &crate::Doc) {
+                if self.0.is_none() {
+                    self.0 = Some(doc.clone());
+                }
+            }
+        }
+
+        let mut first = FirstDoc::default();
+        self.accept(&mut first);
+        first.0
+    }
+}
+
+/// Renders a [`Template`] with optional settings, see [`Template::render_builder`].
+#[must_use]
+pub struct RenderBuilder<'t> {
+    template: &'t Template,
+    error_placeholder: Option<KString>,
+    profiling: bool,
+    stream: bool,
+}
+
+impl RenderBuilder<'_> {
+    /// Write `placeholder` in place of each tag or expression that fails, instead of stopping.
+    ///
+    /// The errors that were replaced are reported by [`RenderReport::warnings`].  Exceeding
+    /// [`ParserBuilder::resource_limits`] still stops the render.
+    ///
+    /// [`ParserBuilder::resource_limits`]: crate::ParserBuilder::resource_limits
+    pub fn error_placeholder<S: Into<KString>>(mut self, placeholder: S) -> Self {
+        self.error_placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// Record how long each tag, block and expression took, reported by
+    /// [`RenderReport::profile`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .build().unwrap()
+    ///     .parse("{{ 'a string' | upcase }}\n{% increment test %}").unwrap();
+    ///
+    /// let globals = liquid::Object::new();
+    /// let (output, report) = template.render_builder().profiling(true).render(&globals).unwrap();
+    /// let profile = report.profile().unwrap();
+    /// assert_eq!(output, "A STRING\n0");
+    /// assert_eq!(profile.len(), 2);
+    /// assert_eq!(profile[1].code(), Some("increment test"));
+    /// assert_eq!(profile[1].line_number(), Some(2));
+    /// ```
+    pub fn profiling(mut self, profiling: bool) -> Self {
+        self.profiling = profiling;
+        self
+    }
+
+    /// Flush the writer of [`RenderBuilder::render_to`] as each top-level tag, block or text
+    /// finishes, so the output can be streamed.
+    ///
+    /// Tags may also flush the writer to pass on what they rendered so far.
+    pub fn stream(mut self, stream: bool) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Renders the Template, using the given globals.
+    pub fn render(self, globals: &dyn crate::ObjectView) -> Result<(String, RenderReport)> {
         const BEST_GUESS: usize = 10_000;
         let mut data = Vec::with_capacity(BEST_GUESS);
-        self.render_to(&mut data, globals)?;
+        let report = self.render_to(&mut data, globals)?;
 
-        Ok(convert_buffer(data))
+        Ok((convert_buffer(data), report))
     }
 
-    /// Renders an instance of the Template, using the given globals.
-    pub fn render_to(&self, writer: &mut dyn Write, globals: &dyn crate::ObjectView) -> Result<()> {
-        let runtime = runtime::RuntimeBuilder::new().set_globals(globals);
-        let runtime = match self.partials {
+    /// Renders the Template to `writer`, using the given globals.
+    pub fn render_to(
+        self,
+        writer: &mut dyn Write,
+        globals: &dyn crate::ObjectView,
+    ) -> Result<RenderReport> {
+        let runtime = self.runtime(globals).build();
+        let mut writer = runtime.resource_usage().meter(writer);
+        self.template
+            .render_layouts(&mut writer, &runtime, self.stream)?;
+        Ok(RenderReport::new(&runtime))
+    }
+
+    /// Renders the Template in chunks, passing each to `on_chunk` as soon as it is ready, see
+    /// [`RenderBuilder::stream`].
+    ///
+    /// Rendering waits for `on_chunk` to return, so a slow consumer holds back the render, and an
+    /// error from `on_chunk` stops it.
+    ///
+    /// # Examples
+    ///
+    /// Streaming a page to another thread, at most one chunk ahead:
+    ///
+    /// ```
+    /// let template = liquid::ParserBuilder::with_stdlib()
+    ///     .build().unwrap()
+    ///     .parse("<head></head>{% for i in (1..3) %}<p>{{ i }}</p>{% endfor %}").unwrap();
+    ///
+    /// let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+    /// std::thread::scope(|scope| {
+    ///     scope.spawn(|| {
+    ///         let globals = liquid::Object::new();
+    ///         template.render_builder().render_chunks(&globals, |chunk| {
+    ///             sender.send(chunk.to_vec()).map_err(|_| liquid::Error::with_msg("Disconnected"))
+    ///         })
+    ///     });
+    ///     assert_eq!(receiver.recv().unwrap(), b"<head></head>");
+    ///     assert_eq!(receiver.recv().unwrap(), b"<p>1</p><p>2</p><p>3</p>");
+    /// });
+    /// ```
+    pub fn render_chunks(
+        self,
+        globals: &dyn crate::ObjectView,
+        on_chunk: impl FnMut(&[u8]) -> Result<()>,
+    ) -> Result<RenderReport> {
+        let mut chunks = Chunks {
+            buffer: Vec::new(),
+            on_chunk,
+            error: None,
+        };
+        let result = self
+            .stream(true)
+            .render_to(&mut chunks, globals)
+            .and_then(|report| chunks.emit().map(|()| report));
+        match chunks.error.take() {
+            Some(error) => Err(error),
+            None => result,
+        }
+    }
+
+    /// Renders the Template without blocking, using the given globals, see
+    /// [`Template::render_async`].
+    #[cfg(feature = "async")]
+    pub async fn render_async(
+        self,
+        globals: &dyn crate::ObjectView,
+    ) -> Result<(String, RenderReport)> {
+        const BEST_GUESS: usize = 10_000;
+        let mut data = Vec::with_capacity(BEST_GUESS);
+        let report = self.render_to_async(&mut data, globals).await?;
+
+        Ok((convert_buffer(data), report))
+    }
+
+    /// Renders the Template to an asynchronous `writer`, using the given globals, see
+    /// [`Template::render_async`].
+    ///
+    /// [`RenderBuilder::stream`] doesn't apply, an asynchronous `writer` is never flushed.
+    #[cfg(feature = "async")]
+    pub async fn render_to_async(
+        self,
+        writer: &mut (dyn runtime::AsyncWrite + Unpin),
+        globals: &dyn crate::ObjectView,
+    ) -> Result<RenderReport> {
+        let runtime = self.runtime(globals).build();
+        self.template.render_layouts_async(writer, &runtime).await?;
+        Ok(RenderReport::new(&runtime))
+    }
+
+    fn runtime<'g>(
+        &'g self,
+        globals: &'g dyn crate::ObjectView,
+    ) -> runtime::RuntimeBuilder<'g, 'g> {
+        let template = self.template;
+        let mut runtime = runtime::RuntimeBuilder::new()
+            .set_globals(globals)
+            .set_resource_limits(template.resource_limits)
+            .set_strict_variables(template.strict_variables)
+            .set_profiling(self.profiling);
+        if let Some(placeholder) = &self.error_placeholder {
+            runtime = runtime.set_error_placeholder(placeholder.clone());
+        }
+        match template.partials {
             Some(ref partials) => runtime.set_partials(partials.as_ref()),
             None => runtime,
-        };
-        let runtime = runtime.build();
-        self.template.render_to(writer, &runtime)
+        }
+    }
+}
+
+/// What a render found besides its output, see [`Template::render_builder`].
+#[derive(Debug, Default)]
+pub struct RenderReport {
+    warnings: Vec<Error>,
+    profile: Option<crate::Timing>,
+}
+
+impl RenderReport {
+    fn new(runtime: &dyn Runtime) -> Self {
+        Self {
+            warnings: runtime.warnings().take(),
+            profile: runtime.profiler().map(|profiler| profiler.finish()),
+        }
+    }
+
+    /// The problems that didn't stop rendering, like unknown variables when
+    /// [`ParserBuilder::strict_variables`] is off, or the errors replaced by
+    /// [`RenderBuilder::error_placeholder`].
+    ///
+    /// [`ParserBuilder::strict_variables`]: crate::ParserBuilder::strict_variables
+    pub fn warnings(&self) -> &[Error] {
+        &self.warnings
+    }
+
+    /// Take the problems that didn't stop rendering, see [`RenderReport::warnings`].
+    pub fn into_warnings(self) -> Vec<Error> {
+        self.warnings
+    }
+
+    /// How long each tag, block and expression took, when [`RenderBuilder::profiling`] is on.
+    pub fn profile(&self) -> Option<&crate::Timing> {
+        self.profile.as_ref()
+    }
+}
+
+/// Takes the layout set by the template or layout that just rendered.
+fn next_layout(runtime: &dyn Runtime, rendered: &mut usize) -> Result<Option<KString>> {
+    let Some(layout) = runtime.registers().get_mut::<LayoutRegister>().reset() else {
+        return Ok(None);
+    };
+    // Layouts extending each other in a loop would never finish.
+    *rendered += 1;
+    runtime
+        .resource_usage()
+        .check_depth(*rendered)
+        .trace_with(|| format!("{{% layout {layout} %}}").into())?;
+    Ok(Some(layout))
+}
+
+/// Drops the output of a template once it sets a layout, which renders in its place.
+///
+/// Templates within it, like a `capture`, write elsewhere and keep their output.
+struct LayoutWriter<'w> {
+    writer: &'w mut dyn Write,
+    layout: LayoutRegister,
+}
+
+impl Write for LayoutWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.layout.is_set() {
+            return Ok(buf.len());
+        }
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Drops the output of a template once it sets a layout, see [`LayoutWriter`].
+#[cfg(feature = "async")]
+struct AsyncLayoutWriter<'w> {
+    writer: &'w mut (dyn runtime::AsyncWrite + Unpin),
+    layout: LayoutRegister,
+}
+
+#[cfg(feature = "async")]
+impl runtime::AsyncWrite for AsyncLayoutWriter<'_> {
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        if this.layout.is_set() {
+            return std::task::Poll::Ready(Ok(buf.len()));
+        }
+        std::pin::Pin::new(&mut *this.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut *self.get_mut().writer).poll_flush(cx)
+    }
+
+    fn poll_close(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut *self.get_mut().writer).poll_close(cx)
+    }
+}
+
+/// Collects output until it is flushed to `on_chunk`.
+struct Chunks<F> {
+    buffer: Vec<u8>,
+    on_chunk: F,
+    error: Option<Error>,
+}
+
+impl<F: FnMut(&[u8]) -> Result<()>> Chunks<F> {
+    fn emit(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let result = (self.on_chunk)(&self.buffer);
+        self.buffer.clear();
+        result
+    }
+}
+
+impl<F: FnMut(&[u8]) -> Result<()>> Write for Chunks<F> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.emit().map_err(|error| {
+            // Reported as is by `render_chunks`, rather than as a failure to write.
+            self.error = Some(error);
+            std::io::Error::other("chunk was not accepted")
+        })
     }
 }
 
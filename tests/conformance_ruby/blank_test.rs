@@ -38,7 +38,7 @@ impl liquid::partials::PartialSource for BlankTestFilesystem {
         true
     }
 
-    fn names(&self) -> Vec<&str> {
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
         vec![]
     }
 
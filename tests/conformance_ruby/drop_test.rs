@@ -1,155 +1,306 @@
+use liquid::model::{ScalarCow, Value};
+use liquid::Runtime;
+
+#[derive(Clone, Debug, liquid::DropView)]
+#[drop(method_missing(lookup))]
+struct RuntimeDrop;
+
+impl RuntimeDrop {
+    fn loop_pos(&self, runtime: &dyn Runtime) -> Value {
+        let path = [ScalarCow::new("forloop"), ScalarCow::new("index")];
+        runtime
+            .try_get(&path)
+            .map(|value| value.into_owned())
+            .unwrap_or_default()
+    }
+
+    fn lookup(&self, name: &str, runtime: &dyn Runtime) -> Option<Value> {
+        if name == "loop_pos" {
+            return Some(self.loop_pos(runtime));
+        }
+        runtime
+            .try_get(&[ScalarCow::new(name)])
+            .map(|value| value.into_owned())
+    }
+}
+
+#[derive(Clone, Debug, liquid::DropView)]
+#[drop(methods(array, text))]
+struct TextDrop;
+
+impl TextDrop {
+    fn array(&self) -> Vec<&'static str> {
+        vec!["text1", "text2"]
+    }
+
+    fn text(&self) -> &'static str {
+        "text1"
+    }
+}
+
+#[derive(Clone, Debug, liquid::DropView)]
+#[drop(method_missing(catchall))]
+struct CatchallDrop;
+
+impl CatchallDrop {
+    fn catchall(&self, name: &str, _runtime: &dyn Runtime) -> Option<String> {
+        Some(format!("catchall_method: {name}"))
+    }
+}
+
+#[derive(Clone, Debug, liquid::DropView)]
+#[drop(methods(texts, catchall, context))]
+struct ProductDrop;
+
+impl ProductDrop {
+    fn texts(&self) -> TextDrop {
+        TextDrop
+    }
+
+    fn catchall(&self) -> CatchallDrop {
+        CatchallDrop
+    }
+
+    fn context(&self) -> RuntimeDrop {
+        RuntimeDrop
+    }
+
+    #[allow(dead_code)]
+    fn callmenot(&self) -> &'static str {
+        "protected"
+    }
+}
+
+#[derive(Clone, Debug, liquid::DropView)]
+#[drop(methods(size, first, count, min, max), method_missing(echo))]
+struct EnumerableDrop;
+
+impl EnumerableDrop {
+    fn size(&self) -> i64 {
+        3
+    }
+
+    fn first(&self) -> i64 {
+        1
+    }
+
+    fn count(&self) -> i64 {
+        3
+    }
+
+    fn min(&self) -> i64 {
+        1
+    }
+
+    fn max(&self) -> i64 {
+        3
+    }
+
+    fn echo(&self, name: &str, _runtime: &dyn Runtime) -> Option<String> {
+        Some(name.to_owned())
+    }
+}
+
+fn product() -> liquid::Object {
+    let mut assigns = liquid::Object::new();
+    assigns.insert("product".into(), Value::from_drop(ProductDrop));
+    assigns
+}
+
+fn collection() -> liquid::Object {
+    let mut assigns = liquid::Object::new();
+    assigns.insert("collection".into(), Value::from_drop(EnumerableDrop));
+    assigns
+}
+
 #[test]
-#[should_panic]
 fn test_product_drop() {
-    panic!("Implementation specific: drops");
+    assert_template_result!("  ", "  ", product());
 }
 
 #[test]
 #[should_panic]
 fn test_rendering_raises_on_tainted_attr() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: taint");
 }
 
 #[test]
 #[should_panic]
 fn test_rendering_warns_on_tainted_attr() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: taint");
 }
 
 #[test]
 #[should_panic]
 fn test_rendering_doesnt_raise_on_escaped_tainted_attr() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: taint");
 }
 
 #[test]
-#[should_panic]
 fn test_drop_does_only_respond_to_whitelisted_methods() {
-    panic!("Implementation specific: drops");
+    // Modified: unknown keys are render errors rather than empty
+    assert_render_error!("{{ product.inspect }}", product());
+    assert_render_error!("{{ product.pretty_inspect }}", product());
+    assert_render_error!("{{ product.whatever }}", product());
 }
 
 #[test]
 #[should_panic]
 fn test_drops_respond_to_to_liquid() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: to_liquid");
 }
 
 #[test]
-#[should_panic]
 fn test_text_drop() {
-    panic!("Implementation specific: drops");
+    assert_template_result!(" text1 ", " {{ product.texts.text }} ", product());
 }
 
 #[test]
-#[should_panic]
 fn test_catchall_unknown_method() {
-    panic!("Implementation specific: drops");
+    assert_template_result!(
+        " catchall_method: unknown ",
+        " {{ product.catchall.unknown }} ",
+        product()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_catchall_integer_argument_drop() {
-    panic!("Implementation specific: drops");
+    assert_template_result!(
+        " catchall_method: 8 ",
+        " {{ product.catchall[8] }} ",
+        product()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_text_array_drop() {
-    panic!("Implementation specific: drops");
+    assert_template_result!(
+        " text1  text2 ",
+        "{% for text in product.texts.array %} {{text}} {% endfor %}",
+        product()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_runtime_drop() {
-    panic!("Implementation specific: drops");
+    let mut assigns = liquid::Object::new();
+    assigns.insert("context".into(), Value::from_drop(RuntimeDrop));
+    assigns.insert("bar".into(), Value::scalar("carrot"));
+    assert_template_result!(" carrot ", " {{ context.bar }} ", assigns);
 }
 
 #[test]
-#[should_panic]
 fn test_nested_runtime_drop() {
-    panic!("Implementation specific: drops");
+    let mut assigns = product();
+    assigns.insert("foo".into(), Value::scalar("monkey"));
+    assert_template_result!(" monkey ", " {{ product.context.foo }} ", assigns);
 }
 
 #[test]
-#[should_panic]
 fn test_protected() {
-    panic!("Implementation specific: drops");
+    // Modified: unknown keys are render errors rather than empty
+    assert_render_error!(" {{ product.callmenot }} ", product());
 }
 
 #[test]
-#[should_panic]
 fn test_object_methods_not_allowed() {
-    panic!("Implementation specific: drops");
+    // Modified: unknown keys are render errors rather than empty
+    for method in [
+        "dup",
+        "clone",
+        "singleton_class",
+        "eval",
+        "class_eval",
+        "inspect",
+    ] {
+        assert_render_error!(format!(" {{{{ product.{method} }}}} "), product());
+    }
 }
 
 #[test]
 #[should_panic]
 fn test_scope() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: scopes");
 }
 
 #[test]
 #[should_panic]
 fn test_scope_though_proc() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: scopes");
 }
 
 #[test]
 #[should_panic]
 fn test_scope_with_assigns() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: scopes");
 }
 
 #[test]
 #[should_panic]
 fn test_scope_from_tags() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: scopes");
 }
 
 #[test]
-#[should_panic]
 fn test_access_runtime_from_drop() {
-    panic!("Implementation specific: drops");
+    let mut assigns = liquid::Object::new();
+    assigns.insert("context".into(), Value::from_drop(RuntimeDrop));
+    assigns.insert("dummy".into(), liquid::model::value!([1, 2, 3]));
+    assert_template_result!(
+        "123",
+        "{%for a in dummy%}{{ context.loop_pos }}{% endfor %}",
+        assigns
+    );
 }
 
 #[test]
 #[should_panic]
 fn test_enumerable_drop() {
-    panic!("Implementation specific: drops");
+    panic!("Implementation specific: drops are not arrays");
 }
 
 #[test]
-#[should_panic]
 fn test_enumerable_drop_size() {
-    panic!("Implementation specific: drops");
+    assert_template_result!("3", "{{collection.size}}", collection());
 }
 
 #[test]
-#[should_panic]
 fn test_enumerable_drop_will_invoke_liquid_method_missing_for_clashing_method_names() {
-    panic!("Implementation specific: drops");
+    for method in ["select", "each", "map", "cycle"] {
+        assert_template_result!(method, format!("{{{{collection.{method}}}}}"), collection());
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_some_enumerable_methods_still_get_invoked() {
-    panic!("Implementation specific: drops");
+    for (method, expected) in [("count", "3"), ("max", "3"), ("min", "1"), ("first", "1")] {
+        assert_template_result!(
+            expected,
+            format!("{{{{collection.{method}}}}}"),
+            collection()
+        );
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_empty_string_value_access() {
-    panic!("Implementation specific: drops");
+    // Modified: unknown keys are render errors rather than empty
+    let mut assigns = product();
+    assigns.insert("value".into(), Value::scalar(""));
+    assert_render_error!("{{ product[value] }}", assigns);
 }
 
 #[test]
-#[should_panic]
 fn test_nil_value_access() {
-    panic!("Implementation specific: drops");
+    // Modified: nil is not a valid key
+    let mut assigns = product();
+    assigns.insert("value".into(), Value::Nil);
+    assert_render_error!("{{ product[value] }}", assigns);
 }
 
 #[test]
-#[should_panic]
 fn test_default_to_s_on_drops() {
-    panic!("Implementation specific: drops");
+    assert_template_result!("ProductDrop", "{{ product }}", product());
+    assert_template_result!("EnumerableDrop", "{{ collection }}", collection());
 }
@@ -1,7 +1,50 @@
 #[test]
-#[should_panic]
 fn test_templates_parsed_with_line_numbers_renders_them_in_errors() {
-    panic!("Implementation specific: lax errors");
+    // Modified: failing filters stand in for `ErrorDrop`, and the error messages are returned
+    // rather than rendered
+    let template = r#"      Hello,
+
+      {{ 1 | divided_by: 0 }} will raise a standard error.
+
+      Bla bla test.
+
+      {{ 1 | modulo: 0 }} will raise a syntax error.
+
+      This is an argument error: {{ 'a' | divided_by: 0 }}
+
+      Bla.
+"#;
+
+    let expected = r#"      Hello,
+
+      Liquid error will raise a standard error.
+
+      Bla bla test.
+
+      Liquid error will raise a syntax error.
+
+      This is an argument error: Liquid error
+
+      Bla.
+"#;
+
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap();
+    let (output, report) = template
+        .render_builder()
+        .error_placeholder("Liquid error")
+        .render(&liquid::Object::new())
+        .unwrap();
+    let errors = report.warnings();
+    assert_eq!(output, expected);
+    let lines: Vec<_> = errors
+        .iter()
+        .map(|e| e.get_location().unwrap().line())
+        .collect();
+    assert_eq!(lines, [3, 7, 9]);
 }
 
 #[test]
@@ -39,21 +82,68 @@ fn test_lax_unrecognized_operator() {
 }
 
 #[test]
-#[should_panic]
 fn test_with_line_numbers_adds_numbers_to_parser_errors() {
-    panic!("Implementation specific: lax errors");
+    let err = assert_parse_error!(
+        r#"
+          foobar
+
+          {% "cat" | foobar %}
+
+          bla
+    "#,
+    );
+    let err = err.to_string();
+
+    let expected = regex::Regex::new(r"\bline 4\b").unwrap();
+    println!("err={err}");
+    assert!(expected.is_match(&err));
 }
 
 #[test]
-#[should_panic]
 fn test_with_line_numbers_adds_numbers_to_parser_errors_with_whitespace_trim() {
-    panic!("Implementation specific: lax errors");
+    let err = assert_parse_error!(
+        r#"
+          foobar
+
+          {%- "cat" | foobar -%}
+
+          bla
+    "#,
+    );
+    let err = err.to_string();
+
+    let expected = regex::Regex::new(r"\bline 4\b").unwrap();
+    println!("err={err}");
+    assert!(expected.is_match(&err));
+}
+
+fn warn() -> liquid::Parser {
+    liquid::ParserBuilder::with_stdlib()
+        .error_mode(liquid::ErrorMode::Warn)
+        .build()
+        .unwrap()
 }
 
 #[test]
-#[should_panic]
 fn test_parsing_warn_with_line_numbers_adds_numbers_to_lexer_errors() {
-    panic!("Implementation specific: lax errors");
+    let template = warn()
+        .parse(
+            r#"
+          foobar
+
+          {% if 1 =! 2 %}ok{% endif %}
+
+          bla
+    "#,
+        )
+        .unwrap();
+
+    let lines: Vec<_> = template
+        .warnings()
+        .iter()
+        .map(|w| w.get_location().unwrap().line())
+        .collect();
+    assert_eq!(lines, [4]);
 }
 
 #[test]
@@ -74,7 +164,6 @@ fn test_parsing_strict_with_line_numbers_adds_numbers_to_lexer_errors() {
 }
 
 #[test]
-#[should_panic] // liquid-rust#247
 fn test_syntax_errors_in_nested_blocks_have_correct_line_number() {
     let err = assert_parse_error!(
         r#"
@@ -101,15 +190,26 @@ fn test_strict_error_messages() {
 }
 
 #[test]
-#[should_panic]
 fn test_warnings() {
-    panic!("Implementation specific: lax errors");
+    let template = warn()
+        .parse("{% if ~~~ %}{{%%%}}{% else %}{{ hello. }}{% endif %}")
+        .unwrap();
+    assert_eq!(template.warnings().len(), 3);
+    assert_eq!(template.render(&liquid::Object::new()).unwrap(), "");
 }
 
 #[test]
-#[should_panic]
 fn test_warning_line_numbers() {
-    panic!("Implementation specific: lax errors");
+    let template = warn()
+        .parse("{% if ~~~ %}\n{{%%%}}hi\n{% if 1 =! 2 %}{% endif %}{% endif %}")
+        .unwrap();
+
+    let lines: Vec<_> = template
+        .warnings()
+        .iter()
+        .map(|w| w.get_location().unwrap().line())
+        .collect();
+    assert_eq!(lines, [1, 2, 3]);
 }
 
 #[test]
@@ -125,9 +225,20 @@ fn test_default_exception_renderer_with_internal_error() {
 }
 
 #[test]
-#[should_panic]
 fn test_setting_default_exception_renderer() {
-    panic!("Implementation specific: error propagation");
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse("This is a runtime error: {{ 'argument' | divided_by: 0 }}")
+        .unwrap();
+    let (output, report) = template
+        .render_builder()
+        .error_placeholder("")
+        .render(&liquid::Object::new())
+        .unwrap();
+    let errors = report.warnings();
+    assert_eq!(output, "This is a runtime error: ");
+    assert_eq!(errors.len(), 1);
 }
 
 #[test]
@@ -138,15 +249,19 @@ fn test_exception_renderer_exposing_non_liquid_error() {
 
 #[test]
 fn test_included_template_name_with_line_numbers() {
-    /*old_file_system = Liquid::Template.file_system
-
-    begin
-      Liquid::Template.file_system = TestFileSystem.new
-      template = Liquid::Template.parse("Argument error:\n{% include 'product' %}", line_numbers: true)
-      page = template.render('errors' => ErrorDrop.new)
-    ensure
-      Liquid::Template.file_system = old_file_system
-    end
-    assert_equal "Argument error:\nLiquid error (product line 1): argument error", page
-    assert_equal "product", template.errors.first.template_name*/
+    let mut partials = liquid::partials::EagerCompiler::<liquid::partials::InMemorySource>::empty();
+    partials.add("product", "{{ 'argument' | divided_by: 0 }}");
+    let liquid = liquid::ParserBuilder::with_stdlib()
+        .partials(partials)
+        .build()
+        .unwrap();
+
+    let template = liquid
+        .parse("Argument error:\n{% include 'product' %}")
+        .unwrap();
+    let err = template.render(&liquid::Object::new()).unwrap_err();
+
+    let location = err.get_location().unwrap();
+    assert_eq!(location.template(), Some("product"));
+    assert_eq!(location.line(), 1);
 }
@@ -1,191 +1,315 @@
+use liquid::{ParseTreeVisitor, ValueView};
+use liquid_core::runtime::Variable;
+
+/// Collects the root name of every variable, in the order visited.
+#[derive(Default)]
+struct VariableNames(Vec<String>);
+
+impl ParseTreeVisitor for VariableNames {
+    fn visit_variable(&mut self, variable: &Variable) {
+        self.0.push(variable.name().to_kstr().as_str().to_owned());
+    }
+}
+
+fn visit(template: &str) -> Vec<String> {
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap();
+    let mut names = VariableNames::default();
+    template.accept(&mut names);
+    names.0
+}
+
 #[test]
-#[should_panic]
 fn test_variable() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{{ test }}").as_slice());
 }
 
 #[test]
-#[should_panic]
 fn test_variable_with_filter() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test", "infilter"],
+        visit("{{ test | split: infilter }}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_dynamic_variable() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test", "inlookup"],
+        visit("{{ test[inlookup] }}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_if_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% if test %}{% endif %}").as_slice());
 }
 
 #[test]
-#[should_panic]
 fn test_complex_if_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% if 1 == 1 and 2 == test %}{% endif %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_if_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% if 1 == 1 %}{{ test }}{% endif %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_unless_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% unless test %}{% endunless %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_complex_unless_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% unless 1 == 1 and 2 == test %}{% endunless %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_unless_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% unless 1 == 1 %}{{ test }}{% endunless %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_elsif_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% if 1 == 1 %}{% elsif test %}{% endif %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_complex_elsif_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% if 1 == 1 %}{% elsif 1 == 1 and 2 == test %}{% endif %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_elsif_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% if 1 == 1 %}{% elsif 2 == 2 %}{{ test }}{% endif %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_else_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% if 1 == 1 %}{% else %}{{ test }}{% endif %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_case_left() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% case test %}{% endcase %}").as_slice());
 }
 
 #[test]
-#[should_panic]
 fn test_case_condition() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% case 1 %}{% when test %}{% endcase %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_case_when_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% case 1 %}{% when 2 %}{{ test }}{% endcase %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_case_else_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% case 1 %}{% else %}{{ test }}{% endcase %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_for_in() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% for x in test %}{% endfor %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_for_limit() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% for x in (1..5) limit: test %}{% endfor %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_for_offset() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% for x in (1..5) offset: test %}{% endfor %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_for_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% for x in (1..5) %}{{ test }}{% endfor %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_tablerow_in() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% tablerow x in test %}{% endtablerow %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_tablerow_limit() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% tablerow x in (1..5) limit: test %}{% endtablerow %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_tablerow_offset() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% tablerow x in (1..5) offset: test %}{% endtablerow %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_tablerow_body() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% tablerow x in (1..5) %}{{ test }}{% endtablerow %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_cycle() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% cycle test %}").as_slice());
 }
 
 #[test]
-#[should_panic]
 fn test_assign() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% assign x = test %}").as_slice());
 }
 
 #[test]
-#[should_panic]
 fn test_capture() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(
+        ["test"],
+        visit("{% capture x %}{{ test }}{% endcapture %}").as_slice()
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_include() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% include test %}").as_slice());
 }
 
 #[test]
-#[should_panic]
+#[should_panic] // liquid-rust#237
 fn test_include_with() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% include 'hai' with test %}").as_slice());
 }
 
 #[test]
-#[should_panic]
+#[should_panic] // liquid-rust#237
 fn test_include_for() {
-    panic!("Implementation specific: parsing API");
+    assert_eq!(["test"], visit("{% include 'hai' for test %}").as_slice());
+}
+
+#[derive(Debug, PartialEq)]
+enum Tree {
+    Tag(String, Vec<Tree>),
+    Variable(String),
+}
+
+/// Rebuilds the nesting of tags, with variables as leaves.
+#[derive(Default)]
+struct TreeBuilder {
+    stack: Vec<(String, Vec<Tree>)>,
+    root: Vec<Tree>,
+}
+
+impl TreeBuilder {
+    fn push(&mut self, node: Tree) {
+        match self.stack.last_mut() {
+            Some((_, children)) => children.push(node),
+            None => self.root.push(node),
+        }
+    }
+}
+
+impl ParseTreeVisitor for TreeBuilder {
+    fn enter_tag(&mut self, name: &str) {
+        self.stack.push((name.to_owned(), Vec::new()));
+    }
+
+    fn leave_tag(&mut self, _name: &str) {
+        let (name, children) = self.stack.pop().unwrap();
+        self.push(Tree::Tag(name, children));
+    }
+
+    fn visit_variable(&mut self, variable: &Variable) {
+        self.push(Tree::Variable(variable.to_string()));
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_preserve_tree_structure() {
-    panic!("Implementation specific: parsing API");
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse("{% for x in xs offset: test %}{% if x %}{{ other }}{% endif %}{% endfor %}")
+        .unwrap();
+    let mut tree = TreeBuilder::default();
+    template.accept(&mut tree);
+
+    assert_eq!(
+        tree.root,
+        vec![Tree::Tag(
+            "for".to_owned(),
+            vec![
+                Tree::Variable("xs".to_owned()),
+                Tree::Variable("test".to_owned()),
+                Tree::Tag(
+                    "if".to_owned(),
+                    vec![
+                        Tree::Variable("x".to_owned()),
+                        Tree::Variable("other".to_owned()),
+                    ]
+                ),
+            ]
+        )]
+    );
 }
@@ -40,22 +40,39 @@ fn test_unexpected_characters_syntax_error() {
     assert_parse_error!("{% if false || true %} YES {% endif %}");
 }
 
+fn lax() -> liquid::Parser {
+    liquid::ParserBuilder::with_stdlib()
+        .error_mode(liquid::ErrorMode::Lax)
+        .build()
+        .unwrap()
+}
+
 #[test]
-#[should_panic]
 fn test_no_error_on_lax_empty_filter() {
-    panic!("Implementation specific: lax parser");
+    lax().parse("{{test}}").unwrap();
+    lax().parse("{{|test}}").unwrap();
+    lax().parse("{{test ||}}").unwrap();
 }
 
 #[test]
-#[should_panic]
 fn test_meaningless_parens_lax() {
-    panic!("Implementation specific: lax parser");
+    assert_template_result!(
+        " YES ",
+        "{% if a == 'foo' or (b == 'bar' and c == 'baz') or false %} YES {% endif %}",
+        o!({"a": "a", "b": "bar", "c": "baz"}),
+        lax(),
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_unexpected_characters_silently_eat_logic_lax() {
-    panic!("Implementation specific: lax parser");
+    assert_template_result!(
+        " YES ",
+        "{% if true && false %} YES {% endif %}",
+        o!({}),
+        lax()
+    );
+    assert_template_result!("", "{% if false || true %} YES {% endif %}", o!({}), lax());
 }
 
 #[test]
@@ -64,9 +81,21 @@ fn test_raise_on_invalid_tag_delimiter() {
 }
 
 #[test]
-#[should_panic]
 fn test_unanchored_filter_arguments() {
-    panic!("Implementation specific: lax parser");
+    assert_template_result!(
+        "hi",
+        "{{ 'hi there' | split$$$:' ' | first }}",
+        o!({}),
+        lax(),
+    );
+    assert_template_result!("x", "{{ 'X' | downcase) }}", o!({}), lax());
+    // After the messed up quotes a filter without parameters can be found, then things break
+    assert_template_result!(
+        "here",
+        r#"{{ 'hi there' | split:"t"" | reverse | first}}"#,
+        o!({}),
+        lax(),
+    );
 }
 
 #[test]
@@ -76,9 +105,13 @@ fn test_invalid_variables_work() {
 }
 
 #[test]
-#[should_panic]
 fn test_extra_dots_in_ranges() {
-    panic!("Implementation specific: lax parser");
+    assert_template_result!(
+        "12345",
+        "{% for i in (1...5) %}{{ i }}{% endfor %}",
+        o!({}),
+        lax(),
+    );
 }
 
 #[test]
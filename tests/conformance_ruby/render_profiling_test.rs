@@ -1,83 +1,203 @@
+use std::borrow;
+
+#[derive(Default, Debug, Clone, Copy)]
+struct ProfilingFileSystem;
+
+impl liquid::partials::PartialSource for ProfilingFileSystem {
+    fn contains(&self, _name: &str) -> bool {
+        true
+    }
+
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
+        vec![]
+    }
+
+    fn try_get<'a>(&'a self, name: &str) -> Option<borrow::Cow<'a, str>> {
+        Some(
+            format!("Rendering template {{% assign template_name = '{name}' %}}\n{{{{ template_name }}}}")
+                .into(),
+        )
+    }
+}
+
+fn liquid() -> liquid::Parser {
+    liquid::ParserBuilder::with_stdlib()
+        .partials(liquid::partials::OnDemandCompiler::<ProfilingFileSystem>::empty())
+        .build()
+        .unwrap()
+}
+
+fn profile(template: &str, assigns: &liquid::Object) -> liquid::Timing {
+    let template = liquid().parse(template).unwrap();
+    let (_, report) = template
+        .render_builder()
+        .profiling(true)
+        .render(assigns)
+        .unwrap();
+    report.profile().unwrap().clone()
+}
+
 #[test]
-#[should_panic]
 fn test_template_allows_flagging_profiling() {
-    panic!("Implementation specific: profiling API");
+    let template = liquid().parse("{{ 'a string' | upcase }}").unwrap();
+    let output = template.render(&liquid::Object::new()).unwrap();
+    let (profiled_output, report) = template
+        .render_builder()
+        .profiling(true)
+        .render(&liquid::Object::new())
+        .unwrap();
+    assert_eq!(output, profiled_output);
+    assert_eq!(report.profile().unwrap().len(), 1);
 }
 
 #[test]
-#[should_panic]
 fn test_parse_makes_available_simple_profiling() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile("{{ 'a string' | upcase }}", &liquid::Object::new());
+    assert_eq!(profile.len(), 1);
+
+    let node = &profile[0];
+    assert_eq!(node.code(), Some("'a string' | upcase"));
 }
 
 #[test]
-#[should_panic]
 fn test_render_ignores_raw_strings_when_profiling() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile("This is raw string\nstuff\nNewline", &liquid::Object::new());
+    assert_eq!(profile.len(), 0);
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_includes_line_numbers_of_liquid_nodes() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{{ 'a string' | upcase }}\n{% increment test %}",
+        &liquid::Object::new(),
+    );
+    assert_eq!(profile.len(), 2);
+    // {{ 'a string' | upcase }}
+    assert_eq!(profile[0].line_number(), Some(1));
+    // {{ increment test }}
+    assert_eq!(profile[1].line_number(), Some(2));
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_includes_line_numbers_of_included_partials() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile("{% include 'a_template' %}", &liquid::Object::new());
+
+    let included_children = profile[0].children();
+    // {% assign template_name = 'a_template' %}
+    assert_eq!(included_children[0].line_number(), Some(1));
+    // {{ template_name }}
+    assert_eq!(included_children[1].line_number(), Some(2));
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_times_the_rendering_of_tokens() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile("{% include 'a_template' %}", &liquid::Object::new());
+
+    let node = &profile[0];
+    assert!(node.total_time() >= node.self_time());
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_times_the_entire_render() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile("{% include 'a_template' %}", &liquid::Object::new());
+
+    assert!(
+        profile.total_time() >= profile[0].total_time(),
+        "Total render time was not calculated"
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_uses_include_to_mark_children() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{{ 'a string' | upcase }}\n{% include 'a_template' %}",
+        &liquid::Object::new(),
+    );
+
+    let include_node = &profile[1];
+    assert_eq!(include_node.len(), 2);
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_marks_children_with_the_name_of_included_partial() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{{ 'a string' | upcase }}\n{% include 'a_template' %}",
+        &liquid::Object::new(),
+    );
+
+    let include_node = &profile[1];
+    assert_eq!(include_node.partial(), None);
+    for child in include_node {
+        assert_eq!(child.partial(), Some("a_template"));
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_supports_multiple_templates() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{{ 'a string' | upcase }}\n{% include 'a_template' %}\n{% include 'b_template' %}",
+        &liquid::Object::new(),
+    );
+
+    let a_template = &profile[1];
+    for child in a_template {
+        assert_eq!(child.partial(), Some("a_template"));
+    }
+
+    let b_template = &profile[2];
+    for child in b_template {
+        assert_eq!(child.partial(), Some("b_template"));
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_supports_rendering_the_same_partial_multiple_times() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{{ 'a string' | upcase }}\n{% include 'a_template' %}\n{% include 'a_template' %}",
+        &liquid::Object::new(),
+    );
+
+    let a_template1 = &profile[1];
+    for child in a_template1 {
+        assert_eq!(child.partial(), Some("a_template"));
+    }
+
+    let a_template2 = &profile[2];
+    for child in a_template2 {
+        assert_eq!(child.partial(), Some("a_template"));
+    }
 }
 
 #[test]
-#[should_panic]
 fn test_can_iterate_over_each_profiling_entry() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{{ 'a string' | upcase }}\n{% increment test %}",
+        &liquid::Object::new(),
+    );
+
+    let timing_count = profile.iter().count();
+    assert_eq!(timing_count, 2);
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_marks_children_of_if_blocks() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{% if true %} {% increment test %} {{ test }} {% endif %}",
+        &liquid::Object::new(),
+    );
+
+    assert_eq!(profile.len(), 1);
+    assert_eq!(profile[0].len(), 2);
 }
 
 #[test]
-#[should_panic]
 fn test_profiling_marks_children_of_for_blocks() {
-    panic!("Implementation specific: profiling API");
+    let profile = profile(
+        "{% for item in collection %} {{ item }} {% endfor %}",
+        &o!({"collection": ["one", "two"]}),
+    );
+
+    assert_eq!(profile.len(), 1);
+    // Will profile each invocation of the for block
+    assert_eq!(profile[0].len(), 2);
 }
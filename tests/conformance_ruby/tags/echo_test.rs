@@ -0,0 +1,8 @@
+#[test]
+fn test_echo_outputs_its_input() {
+    assert_template_result!(
+        "BAR",
+        "{%- echo variable-name | upcase -%}\n",
+        o!({"variable-name": "bar"}),
+    );
+}
@@ -301,17 +301,18 @@ fn test_if_with_custom_condition() {
 }
 
 #[test]
-#[ignore]
 fn test_operators_are_ignored_unless_isolated() {
-    panic!("TODO: Figure out what this is testing");
-    /*
-      original_op = Condition.operators["contains"]
-      Condition.operators["contains"] = :[]
+    // Modified: the original temporarily swaps `contains` for `String#[]`, which only matters if
+    // the operators within the literals were picked up.
+    let template =
+        r#"{% if "gnomeslab-and-or-liquid" contains "gnomeslab-and-or-liquid" %}yes{% endif %}"#;
+    assert_template_result!("yes", template);
 
-      assert_template_result!("yes",
-        %({% if "gnomeslab-and-or-liquid" contains "gnomeslab-and-or-liquid" %}yes{% endif %}))
-    ensure
-      Condition.operators["contains"] = original_op*/
+    let grouped = liquid::ParserBuilder::with_stdlib()
+        .grouped_conditions(true)
+        .build()
+        .unwrap();
+    assert_template_result!("yes", template, o!({}), grouped);
 }
 
 #[test]
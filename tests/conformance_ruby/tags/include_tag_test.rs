@@ -10,7 +10,7 @@ impl liquid::partials::PartialSource for TestFileSystem {
         true
     }
 
-    fn names(&self) -> Vec<&str> {
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
         vec![]
     }
 
@@ -81,13 +81,17 @@ fn test_include_tag_for() {
 }
 
 #[test]
-#[should_panic] // fails due to strict_variables
 fn test_include_tag_with_local_variables() {
+    let parser = liquid::ParserBuilder::with_stdlib()
+        .strict_variables(false)
+        .partials(liquid::partials::OnDemandCompiler::<TestFileSystem>::empty())
+        .build()
+        .unwrap();
     assert_template_result!(
         "Locale: test123 ",
         "{% include 'locale_variables' echo1: 'test123' %}",
         o!({}),
-        liquid()
+        parser
     );
 }
 
@@ -152,7 +156,6 @@ fn test_nested_include_with_variable() {
 }
 
 #[derive(Default, Debug, Clone, Copy)]
-#[expect(dead_code)]
 struct InfiniteFileSystem;
 
 impl liquid::partials::PartialSource for InfiniteFileSystem {
@@ -160,7 +163,7 @@ impl liquid::partials::PartialSource for InfiniteFileSystem {
         true
     }
 
-    fn names(&self) -> Vec<&str> {
+    fn names(&self) -> Vec<borrow::Cow<'_, str>> {
         vec![]
     }
 
@@ -170,16 +173,15 @@ impl liquid::partials::PartialSource for InfiniteFileSystem {
 }
 
 #[test]
-#[should_panic] // liquid-rust#300
 fn test_recursively_included_template_does_not_produce_endless_loop() {
-    panic!("We don't check recursion depth");
-    /*
-    liquid::ParserBuilder::with_stdlib()
-        .partials(liquid::partials::OnDemandCompiler::<TestFileSystem>::empty())
+    let template = liquid::ParserBuilder::with_stdlib()
+        .partials(liquid::partials::OnDemandCompiler::<InfiniteFileSystem>::empty())
         .build()
         .unwrap()
-    parser.parse("{% include 'loop' %}").unwrap();
-    */
+        .parse("{% include 'loop' %}")
+        .unwrap();
+    let err = template.render(&liquid::Object::new()).unwrap_err();
+    assert!(err.to_string().contains("Nesting too deep"), "{err}");
 }
 
 #[test]
@@ -0,0 +1,132 @@
+fn assert_syntax_error_line(template: &str, line: usize) {
+    let err = assert_parse_error!(template);
+    assert_eq!(err.get_location().map(|l| l.line()), Some(line), "{err}");
+}
+
+#[test]
+fn test_liquid_tag() {
+    assert_template_result!(
+        "1 2 3",
+        r#"{%- liquid
+  echo array | join: " "
+-%}
+"#,
+        o!({"array": [1, 2, 3]}),
+    );
+
+    assert_template_result!(
+        "1 2 3",
+        r#"{%- liquid
+  for value in array
+    echo value
+    unless forloop.last
+      echo " "
+    endunless
+  endfor
+-%}
+"#,
+        o!({"array": [1, 2, 3]}),
+    );
+
+    assert_template_result!(
+        "4 8 12 6",
+        r#"{%- liquid
+  for value in array
+    assign double_value = value | times: 2
+    echo double_value | times: 2
+    unless forloop.last
+      echo " "
+    endunless
+  endfor
+
+  echo " "
+  echo double_value
+-%}
+"#,
+        o!({"array": [1, 2, 3]}),
+    );
+
+    assert_template_result!(
+        "abc",
+        r#"{%- liquid echo "a" -%}
+b
+{%- liquid echo "c" -%}
+"#,
+    );
+}
+
+#[test]
+fn test_liquid_tag_errors() {
+    assert_syntax_error_line("{%- liquid error no such tag -%}\n", 1);
+
+    assert_syntax_error_line(
+        r#"{{ test }}
+
+{%-
+liquid
+  for value in array
+
+    error no such tag
+  endfor
+-%}
+"#,
+        7,
+    );
+
+    assert_syntax_error_line(
+        r#"{%- liquid
+  for value in array
+    echo 'forgot to close the for tag'
+-%}
+"#,
+        4,
+    );
+}
+
+#[test]
+fn test_line_number_is_correct_after_a_blank_token() {
+    assert_syntax_error_line("{% liquid echo ''\n\n error %}", 3);
+    assert_syntax_error_line("{% liquid echo ''\n  \n error %}", 3);
+}
+
+#[test]
+fn test_nested_liquid_tag() {
+    assert_template_result!(
+        "good",
+        r#"{%- if true %}
+  {%- liquid
+    echo "good"
+  %}
+{%- endif -%}
+"#,
+    );
+}
+
+#[test]
+fn test_cannot_open_blocks_living_past_a_liquid_tag() {
+    assert_parse_error!(
+        r#"{%- liquid
+  if true
+-%}
+{%- endif -%}
+"#
+    );
+}
+
+#[test]
+fn test_cannot_close_blocks_created_before_a_liquid_tag() {
+    assert_parse_error!(
+        r#"{%- if true -%}
+42
+{%- liquid endif -%}
+"#
+    );
+}
+
+#[test]
+fn test_liquid_tag_in_raw() {
+    assert_template_result!(
+        "{% liquid echo 'test' %}\n",
+        "{% raw %}{% liquid echo 'test' %}{% endraw %}\n",
+    );
+}
@@ -1,9 +1,12 @@
 mod break_tag_test;
 mod continue_tag_test;
+mod echo_test;
 mod for_tag_test;
 mod if_else_tag_test;
 mod include_tag_test;
 mod increment_tag_test;
+mod inline_comment_test;
+mod liquid_tag_test;
 mod raw_tag_test;
 mod render_tag_test;
 mod standard_tag_test;
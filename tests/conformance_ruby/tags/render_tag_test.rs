@@ -103,26 +103,26 @@ fn test_nested_render_tag() {
     );
 }
 
-/*
 #[test]
 fn test_recursively_rendered_template_does_not_produce_endless_loop() {
-  Liquid::Template.file_system = StubFileSystem.new("loop": "{% render 'loop' %}")
-
-  assert_raises(Liquid::StackLevelError) do
-    Template.parse("{% render 'loop' %}").render!
-  end
+    let template = liquid(o!({ "loop": "{% render 'loop' %}" }))
+        .parse("{% render 'loop' %}")
+        .unwrap();
+    let err = template.render(&liquid::Object::new()).unwrap_err();
+    assert!(err.to_string().contains("Nesting too deep"), "{err}");
 }
 
 #[test]
 fn test_sub_contexts_count_towards_the_same_recursion_limit() {
-  Liquid::Template.file_system = StubFileSystem.new(
-    "loop_render": "{% render 'loop_render' %}",
-  )
-  assert_raises(Liquid::StackLevelError) do
-    Template.parse("{% render 'loop_render' %}").render!
-  end
+    let template = liquid(o!({ "loop_render": "{% render 'loop_render' %}" }))
+        .parse("{% render 'loop_render' %}")
+        .unwrap();
+    let err = template.render(&liquid::Object::new()).unwrap_err();
+    assert!(err.to_string().contains("Nesting too deep"), "{err}");
 }
 
+/*
+
 #[test]
 fn test_dynamically_chosen_templates_are_not_allowed() {
   assert_syntax_error("{% assign name = 'snippet' %}{% render name %}")
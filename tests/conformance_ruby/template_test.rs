@@ -1,3 +1,6 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
 #[test]
 #[should_panic]
 fn test_instance_assigns_persist_on_same_template_object_between_parses() {
@@ -34,16 +37,46 @@ fn test_persistent_assigns_squash_instance_assigns() {
     panic!("Implementation specific: parse API");
 }
 
+fn counting_lambda() -> (Arc<AtomicI64>, liquid::model::Value) {
+    let global = Arc::new(AtomicI64::new(0));
+    let counter = global.clone();
+    let lambda = liquid::model::Value::lazy(move || counter.fetch_add(1, Ordering::SeqCst) + 1);
+    (global, lambda)
+}
+
 #[test]
-#[should_panic]
 fn test_lambda_is_called_once_from_persistent_assigns_over_multiple_parses_and_renders() {
-    panic!("Implementation specific: parse API");
+    // Modified: there are no persistent assigns, clones of the globals share the lambda instead,
+    // and it is called once per render
+    let (global, lambda) = counting_lambda();
+    let mut assigns = liquid::Object::new();
+    assigns.insert("number".into(), lambda);
+
+    let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+    let template = parser.parse("{{number}}{{number}}").unwrap();
+    assert_eq!("11", template.render(&assigns.clone()).unwrap());
+    let template = parser.parse("{{number}}").unwrap();
+    assert_eq!("2", template.render(&assigns.clone()).unwrap());
+    assert_eq!("3", template.render(&assigns).unwrap());
+    assert_eq!(3, global.load(Ordering::SeqCst));
 }
 
 #[test]
-#[should_panic]
 fn test_lambda_is_called_once_from_custom_assigns_over_multiple_parses_and_renders() {
-    panic!("Implementation specific: parse API");
+    // Modified: the lambda is called once per render, so no render sees stale data
+    let (global, lambda) = counting_lambda();
+    let mut assigns = liquid::Object::new();
+    assigns.insert("number".into(), lambda);
+
+    let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+    let template = parser
+        .parse("{{number}}{% assign copy = number %}{{copy}}")
+        .unwrap();
+    assert_eq!("11", template.render(&assigns).unwrap());
+    let template = parser.parse("{{number}}").unwrap();
+    assert_eq!("2", template.render(&assigns).unwrap());
+    assert_eq!("3", template.render(&assigns).unwrap());
+    assert_eq!(3, global.load(Ordering::SeqCst));
 }
 
 #[test]
@@ -52,34 +85,112 @@ fn test_resource_limits_works_with_custom_length_method() {
     panic!("Implementation specific: render API");
 }
 
+fn render_limited(text: &str, limits: liquid::ResourceLimits) -> Result<String, liquid::Error> {
+    liquid::ParserBuilder::with_stdlib()
+        .resource_limits(limits)
+        .build()
+        .unwrap()
+        .parse(text)
+        .unwrap()
+        .render(&liquid::Object::new())
+}
+
+fn assert_limit_exceeded(result: Result<String, liquid::Error>) {
+    let err = result.unwrap_err();
+    assert!(
+        err.to_string().contains("limit exceeded"),
+        "unexpected error: {err}"
+    );
+}
+
 #[test]
-#[should_panic]
 fn test_resource_limits_render_length() {
-    panic!("Implementation specific: render API");
+    let limits = liquid::ResourceLimits::new();
+    assert_limit_exceeded(render_limited(
+        "0123456789",
+        limits.with_render_length_limit(9),
+    ));
+    assert_eq!(
+        render_limited("0123456789", limits.with_render_length_limit(10)).unwrap(),
+        "0123456789"
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_resource_limits_render_score() {
-    panic!("Implementation specific: render API");
+    let limits = liquid::ResourceLimits::new();
+    assert_limit_exceeded(render_limited(
+        "{% for a in (1..10) %} {% for a in (1..10) %} foo {% endfor %} {% endfor %}",
+        limits.with_render_score_limit(50),
+    ));
+    assert_limit_exceeded(render_limited(
+        "{% for a in (1..100) %} foo {% endfor %}",
+        limits.with_render_score_limit(50),
+    ));
+    assert_eq!(
+        render_limited(
+            "{% for a in (1..100) %} foo {% endfor %}",
+            limits.with_render_score_limit(200)
+        )
+        .unwrap(),
+        " foo ".repeat(100)
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_resource_limits_assign_score() {
-    panic!("Implementation specific: render API");
+    let limits = liquid::ResourceLimits::new();
+    assert_limit_exceeded(render_limited(
+        "{% assign foo = 42 %}{% assign bar = 23 %}",
+        limits.with_assign_score_limit(1),
+    ));
+    assert_eq!(
+        render_limited(
+            "{% assign foo = 42 %}{% assign bar = 23 %}",
+            limits.with_assign_score_limit(2)
+        )
+        .unwrap(),
+        ""
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_resource_limits_assign_score_nested() {
-    panic!("Implementation specific: render API");
+    let limits = liquid::ResourceLimits::new();
+    assert_limit_exceeded(render_limited(
+        "{% assign foo = 'aaaa' | split: '' %}",
+        limits.with_assign_score_limit(3),
+    ));
+    assert_eq!(
+        render_limited(
+            "{% assign foo = 'aaaa' | split: '' %}",
+            limits.with_assign_score_limit(5)
+        )
+        .unwrap(),
+        ""
+    );
 }
 
 #[test]
-#[should_panic]
 fn test_resource_limits_aborts_rendering_after_first_error() {
-    panic!("Implementation specific: render API");
+    let limits = liquid::ResourceLimits::new();
+    assert_limit_exceeded(render_limited(
+        "{% for a in (1..100) %} foo1 {% endfor %} bar {% for a in (1..100) %} foo2 {% endfor %}",
+        limits.with_render_score_limit(50),
+    ));
+
+    // Even when other errors are replaced by a placeholder
+    let template = liquid::ParserBuilder::with_stdlib()
+        .resource_limits(limits.with_render_score_limit(50))
+        .build()
+        .unwrap()
+        .parse("{% for a in (1..100) %} foo1 {% endfor %} bar {% for a in (1..100) %} foo2 {% endfor %}")
+        .unwrap();
+    let result = template
+        .render_builder()
+        .error_placeholder("Liquid error")
+        .render(&liquid::Object::new());
+    assert_limit_exceeded(result.map(|(output, _)| output));
 }
 
 #[test]
@@ -89,9 +200,28 @@ fn test_resource_limits_hash_in_template_gets_updated_even_if_no_limits_are_set(
 }
 
 #[test]
-#[should_panic]
 fn test_render_length_persists_between_blocks() {
-    panic!("Implementation specific: render API");
+    let limits = liquid::ResourceLimits::new();
+    let text = "{% if true %}aaaa{% endif %}";
+    assert_limit_exceeded(render_limited(text, limits.with_render_length_limit(3)));
+    assert_eq!(
+        render_limited(text, limits.with_render_length_limit(4)).unwrap(),
+        "aaaa"
+    );
+
+    let text = "{% if true %}aaaa{% endif %}{% if true %}bbb{% endif %}";
+    assert_limit_exceeded(render_limited(text, limits.with_render_length_limit(6)));
+    assert_eq!(
+        render_limited(text, limits.with_render_length_limit(7)).unwrap(),
+        "aaaabbb"
+    );
+
+    let text = "{% if true %}a{% endif %}{% if true %}b{% endif %}{% if true %}a{% endif %}{% if true %}b{% endif %}{% if true %}a{% endif %}{% if true %}b{% endif %}";
+    assert_limit_exceeded(render_limited(text, limits.with_render_length_limit(5)));
+    assert_eq!(
+        render_limited(text, limits.with_render_length_limit(6)).unwrap(),
+        "ababab"
+    );
 }
 
 #[test]
@@ -113,9 +243,20 @@ fn test_render_bang_force_rethrow_errors_on_passed_runtime() {
 }
 
 #[test]
-#[should_panic]
 fn test_exception_renderer_that_returns_string() {
-    panic!("Implementation specific: error API");
+    let template = liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse("{{ 1 | divided_by: 0 }}")
+        .unwrap();
+    let (output, report) = template
+        .render_builder()
+        .error_placeholder("<!-- error -->")
+        .render(&liquid::Object::new())
+        .unwrap();
+    let errors = report.warnings();
+    assert_eq!(output, "<!-- error -->");
+    assert_eq!(errors.len(), 1);
 }
 
 #[test]
@@ -137,9 +278,28 @@ fn test_global_filter_option_when_native_filters_exist() {
 }
 
 #[test]
-#[should_panic]
 fn test_undefined_variables() {
-    panic!("Implementation specific: error API");
+    let template = liquid::ParserBuilder::with_stdlib()
+        .strict_variables(false)
+        .build()
+        .unwrap()
+        .parse("{{x}} {{y}} {{z.a}} {{z.b}} {{z.c.d}}")
+        .unwrap();
+    let (output, report) = template
+        .render_builder()
+        .render(&o!({ "x": 33, "z": { "a": 32, "c": { "e": 31 } } }))
+        .unwrap();
+    let warnings = report.warnings();
+    assert_eq!(output, "33  32  ");
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].to_string().contains("Unknown variable"));
+    assert!(warnings[1].to_string().contains("Unknown index"));
+    assert!(warnings[2].to_string().contains("Unknown index"));
+    let columns: Vec<_> = warnings
+        .iter()
+        .map(|w| w.get_location().unwrap().column())
+        .collect();
+    assert_eq!(columns, [9, 23, 31]);
 }
 
 #[test]
@@ -168,9 +328,22 @@ fn test_undefined_drop_methods_raise() {
 }
 
 #[test]
-#[should_panic]
 fn test_undefined_filters() {
-    panic!("Implementation specific: error API");
+    // Modified: unknown filters are skipped rather than stopping the render
+    let template = liquid::ParserBuilder::with_stdlib()
+        .strict_filters(false)
+        .build()
+        .unwrap()
+        .parse("{{a}} {{x | upcase | somefilter1 | somefilter2}}")
+        .unwrap();
+    let (output, report) = template
+        .render_builder()
+        .render(&o!({ "a": 123, "x": "foo" }))
+        .unwrap();
+    let warnings = report.warnings();
+    assert_eq!(output, "123 FOO");
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].to_string().contains("Unknown filter"));
 }
 
 #[test]
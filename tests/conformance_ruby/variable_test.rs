@@ -29,10 +29,12 @@ fn test_simple_with_whitespaces() {
 }
 
 #[test]
-#[should_panic]
 fn test_ignore_unknown() {
-    assert_template_result!(r#""#, r#"{{ test }}"#);
-    panic!("Requires `strict_variables: false`");
+    let parser = liquid::ParserBuilder::with_stdlib()
+        .strict_variables(false)
+        .build()
+        .unwrap();
+    assert_template_result!(r#""#, r#"{{ test }}"#, o!({}), parser);
 }
 
 #[test]
@@ -0,0 +1,68 @@
+#![cfg(feature = "extra")]
+
+use liquid::partials::{EagerCompiler, InMemorySource};
+use liquid::*;
+
+fn render(template: &str) -> String {
+    let mut source = InMemorySource::new();
+    source.add(
+        "theme",
+        "<head>{% yield head %}</head><body>{% block content %}{% endblock %}</body>",
+    );
+    source.add(
+        "section",
+        "{% content_for head %}<script src=\"{{ name }}.js\"></script>{% endcontent_for %}<section>{{ name }}</section>",
+    );
+    source.add(
+        "has_head",
+        "{% assign head = 'head' | content_for %}{% if head %}yes{% else %}no{% endif %}",
+    );
+    ParserBuilder::with_stdlib()
+        .layouts()
+        .content_for()
+        .partials(EagerCompiler::new(source))
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap()
+        .render(&Object::new())
+        .unwrap()
+}
+
+#[test]
+fn slots_collect_across_partials() {
+    let template = concat!(
+        "{% render 'section', name: 'a' %}{% render 'section', name: 'b' %}",
+        "|{% yield head %}",
+    );
+    assert_eq!(
+        render(template),
+        concat!(
+            "<section>a</section><section>b</section>",
+            "|<script src=\"a.js\"></script><script src=\"b.js\"></script>",
+        )
+    );
+}
+
+#[test]
+fn slots_are_queried_from_partials() {
+    assert_eq!(
+        render("{% render 'has_head' %}{% content_for head %}x{% endcontent_for %}{% render 'has_head' %}"),
+        "noyes"
+    );
+    assert_eq!(render("{{ 'head' | content_for }}"), "");
+}
+
+#[test]
+fn slots_fill_layouts() {
+    let template = concat!(
+        "{% layout 'theme' %}",
+        "{% content_for head %}<script src=\"page.js\"></script>{% endcontent_for %}",
+        "{% block content %}{% render 'section', name: 'main' %}{% endblock %}",
+    );
+    // The layout yields `head` before rendering the block, so only the page's script is in it.
+    assert_eq!(
+        render(template),
+        "<head><script src=\"page.js\"></script></head><body><section>main</section></body>"
+    );
+}
@@ -0,0 +1,135 @@
+use snapbox::assert_data_eq;
+use snapbox::str;
+
+fn parser() -> liquid::ParserBuilder {
+    liquid::ParserBuilder::with_stdlib()
+        .tag_delimiters("<%", "%>")
+        .output_delimiters("[[", "]]")
+}
+
+fn render(parser: liquid::ParserBuilder, text: &str) -> String {
+    let template = parser.build().unwrap().parse(text).unwrap();
+    let globals = liquid::object!({ "name": "Jo", "items": [1, 2] });
+    template.render(&globals).unwrap()
+}
+
+#[test]
+fn keeps_default_delimiters_as_is() {
+    let text = concat!(
+        "<div v-if=\"ok\">{{ message }}</div>{% if true %}\n",
+        "<% for i in items %>[[ i ]]<% endfor %>|[[ name | upcase ]]|<%# comment %>",
+    );
+    assert_eq!(
+        render(parser(), text),
+        "<div v-if=\"ok\">{{ message }}</div>{% if true %}\n12|JO|"
+    );
+}
+
+#[test]
+fn trims_whitespace() {
+    let text = "a  <%- assign x = 1 -%>  b  [[- name -]]  c";
+    assert_eq!(render(parser(), text), "abJoc");
+
+    let text = "<ul>\n  <% for i in items %>\n  <li>[[ i ]]</li>\n  <% endfor +%>\n</ul>";
+    let parser = parser().trim_blocks(true).lstrip_blocks(true);
+    assert_eq!(
+        render(parser, text),
+        "<ul>\n  <li>1</li>\n  <li>2</li>\n\n</ul>"
+    );
+}
+
+#[test]
+fn raw_keeps_delimiters() {
+    let text = "<% raw %><% if %>[[ name ]]{{ name }}<% endraw %>[[ name ]]";
+    assert_eq!(render(parser(), text), "<% if %>[[ name ]]{{ name }}Jo");
+}
+
+#[test]
+fn liquid_tag_uses_delimiters() {
+    let text = "<% liquid\n  assign greeting = 'Hi ' | append: name\n  echo greeting\n%>";
+    assert_eq!(render(parser(), text), "Hi Jo");
+}
+
+#[test]
+fn partials_use_delimiters() {
+    let mut partials = liquid::partials::InMemorySource::new();
+    partials.add("greeting", "Hi [[ name ]]{{ name }}");
+    let parser = parser().partials(liquid::partials::EagerCompiler::new(partials));
+    assert_eq!(
+        render(parser, "<% render 'greeting', name: name %>"),
+        "Hi Jo{{ name }}"
+    );
+}
+
+fn parse_error(text: &str) -> String {
+    match parser().build().unwrap().parse(text) {
+        Ok(_) => panic!("should fail"),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[test]
+fn errors_point_to_template() {
+    assert_data_eq!(
+        parse_error("a\n  <% if %>"),
+        str![[r#"
+liquid: Value expected.
+  |
+2 |   <% if %>
+  |        ^
+  |
+  at: line 2, column 8
+
+"#]]
+    );
+
+    assert_data_eq!(
+        parse_error("a\n[[ name | ]]"),
+        str![[r#"
+liquid: expected Identifier
+  |
+2 | [[ name | ]]
+  |           ^---
+  |
+  at: line 2, column 11
+
+"#]]
+    );
+}
+
+#[test]
+fn rejects_invalid_delimiters() {
+    assert!(liquid::ParserBuilder::new()
+        .tag_delimiters("", "%>")
+        .build()
+        .is_err());
+    assert!(liquid::ParserBuilder::new()
+        .output_delimiters("[ [", "]]")
+        .build()
+        .is_err());
+    assert!(liquid::ParserBuilder::new()
+        .tag_delimiters("[[", "]]")
+        .output_delimiters("[[", "]]")
+        .build()
+        .is_err());
+    assert!(liquid::ParserBuilder::new()
+        .tag_delimiters("[", "]")
+        .output_delimiters("[[", "]]")
+        .build()
+        .is_err());
+    assert!(liquid::ParserBuilder::new()
+        .tag_delimiters("<%", "%>")
+        .output_delimiters("<%=", "%>")
+        .build()
+        .is_err());
+    assert!(liquid::ParserBuilder::new()
+        .tag_delimiters("<%", "%>")
+        .output_delimiters("<%%", "%%>")
+        .build()
+        .is_err());
+    assert!(liquid::ParserBuilder::new()
+        .tag_delimiters("<%", "%>")
+        .output_delimiters("<<", ">>")
+        .build()
+        .is_ok());
+}
@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use liquid::model::{DropCache, Value};
+use liquid::ValueView;
+
+#[derive(Clone, Debug, Default, liquid::DropView)]
+#[drop(methods(cheap, expensive(cache)))]
+struct CountingDrop {
+    calls: Arc<AtomicUsize>,
+    #[drop(cache)]
+    cache: DropCache,
+}
+
+impl CountingDrop {
+    fn cheap(&self) -> i64 {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        1
+    }
+
+    fn expensive(&self) -> i64 {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        2
+    }
+}
+
+fn render(template: &str, drop: &CountingDrop) -> String {
+    let mut globals = liquid::Object::new();
+    globals.insert("drop".into(), Value::from_drop(drop.clone()));
+    liquid::ParserBuilder::with_stdlib()
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap()
+        .render(&globals)
+        .unwrap()
+}
+
+#[test]
+fn test_drop_value() {
+    let uut = CountingDrop::default();
+
+    assert_eq!(uut.render().to_string(), "CountingDrop");
+    assert_eq!(uut.type_name(), "drop");
+    assert!(uut.query_state(liquid::model::State::Truthy));
+    assert!(!uut.query_state(liquid::model::State::Blank));
+    assert!(uut.as_object().is_none());
+    assert!(uut.as_drop().is_some());
+    assert_eq!(uut.calls.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_methods_are_called_on_lookup() {
+    let uut = CountingDrop::default();
+
+    assert_eq!(render("{{ drop }}", &uut), "CountingDrop");
+    assert_eq!(uut.calls.load(Ordering::SeqCst), 0);
+
+    assert_eq!(render("{{ drop.cheap }}{{ drop.cheap }}", &uut), "11");
+    assert_eq!(uut.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_cached_methods_are_called_once() {
+    let uut = CountingDrop::default();
+
+    assert_eq!(
+        render("{{ drop.expensive }}{{ drop.expensive }}", &uut),
+        "22"
+    );
+    assert_eq!(render("{{ drop.expensive }}", &uut), "2");
+    assert_eq!(uut.calls.load(Ordering::SeqCst), 1);
+
+    uut.cache.clear();
+    assert_eq!(render("{{ drop.expensive }}", &uut), "2");
+    assert_eq!(uut.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_drops_survive_assign() {
+    let uut = CountingDrop::default();
+
+    assert_eq!(render("{% assign d = drop %}{{ d.expensive }}", &uut), "2");
+    assert_eq!(uut.calls.load(Ordering::SeqCst), 1);
+}
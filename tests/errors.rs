@@ -12,12 +12,12 @@ fn test_fuzz() {
         Err(err) => assert_data_eq!(
             err.to_string(),
             str![[r#"
-liquid:  --> 1:3
+liquid: expected Identifier
   |
 1 | {%
   |   ^---
   |
-  = expected Identifier
+  at: line 1, column 3
 
 "#]]
         ),
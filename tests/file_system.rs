@@ -0,0 +1,155 @@
+use liquid::partials::RefreshingCompiler;
+use liquid::partials::{EagerCompiler, FileSystemSource, LazyCompiler, OnDemandCompiler};
+use liquid::partials::{PartialCompiler, PartialSource};
+use liquid::*;
+
+fn snippets() -> FileSystemSource {
+    let mut source = FileSystemSource::new("tests/fixtures/partials/snippets");
+    source.add_pattern("_{}.liquid");
+    source
+}
+
+fn render<P: PartialCompiler>(partials: P, template: &str) -> String {
+    ParserBuilder::with_stdlib()
+        .partials(partials)
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap()
+        .render(&object!({
+            "product": { "title": "Draft 151cm" },
+            "price": 42,
+        }))
+        .unwrap()
+}
+
+#[test]
+pub fn ruby_style_names() {
+    let template = "{% include 'product' %} / {% include 'shop/price' %}";
+    let expected = "Product: Draft 151cm / 42 USD";
+    assert_eq!(render(EagerCompiler::new(snippets()), template), expected);
+    assert_eq!(render(LazyCompiler::new(snippets()), template), expected);
+    assert_eq!(
+        render(OnDemandCompiler::new(snippets()), template),
+        expected
+    );
+}
+
+#[test]
+pub fn jekyll_style_names() {
+    let source = FileSystemSource::new("tests/fixtures/partials/_includes");
+    assert_eq!(
+        render(OnDemandCompiler::new(source), "{% include 'footer.html' %}"),
+        "Footer"
+    );
+}
+
+#[test]
+pub fn multiple_roots() {
+    let mut source = snippets();
+    source.add_root("tests/fixtures/partials/_includes");
+    source.add_pattern("{}");
+    assert!(source.contains("product"));
+    assert!(source.contains("footer.html"));
+    assert_eq!(
+        source.names(),
+        [
+            "_product.liquid",
+            "footer.html",
+            "product",
+            "shop/_currency.liquid",
+            "shop/_price.liquid",
+            "shop/currency",
+            "shop/price",
+        ]
+    );
+}
+
+#[test]
+pub fn refuse_escaping_root() {
+    let source = snippets();
+    assert!(!source.contains("../secret"));
+    assert!(!source.contains("shop/../../secret"));
+    assert!(source.try_get("../secret").is_none());
+
+    let parser = ParserBuilder::with_stdlib()
+        .partials(LazyCompiler::new(source))
+        .build()
+        .unwrap();
+    let template = parser.parse("{% include '../secret' %}").unwrap();
+    assert!(template.render(&Object::new()).is_err());
+}
+
+#[test]
+pub fn refresh_edited_partials() {
+    let root = std::env::temp_dir().join(format!("liquid-refresh-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("_greeting.liquid"), "Hello").unwrap();
+
+    let mut source = FileSystemSource::new(&root);
+    source.add_pattern("_{}.liquid");
+    let template = ParserBuilder::with_stdlib()
+        .partials(RefreshingCompiler::new(source))
+        .build()
+        .unwrap()
+        .parse("{% include 'greeting' %}")
+        .unwrap();
+    let template = std::sync::Arc::new(template);
+    assert_eq!(template.render(&Object::new()).unwrap(), "Hello");
+
+    std::fs::write(root.join("_greeting.liquid"), "Hello again").unwrap();
+    let shared = template.clone();
+    let output = std::thread::spawn(move || shared.render(&Object::new()).unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(output, "Hello again");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+#[cfg(feature = "extra")]
+pub fn jekyll_style_layouts() {
+    let source = FileSystemSource::new("tests/fixtures/partials/_layouts");
+    let template = concat!(
+        "{% layout 'post.html' %}\n",
+        "{% block title %}{{ product.title }} - {{ block.super }}{% endblock %}\n",
+        "{% block post %}{{ price }} USD{% endblock %}\n",
+    );
+    let output = ParserBuilder::with_stdlib()
+        .layouts()
+        .partials(OnDemandCompiler::new(source))
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap()
+        .render(&object!({
+            "product": { "title": "Draft 151cm" },
+            "price": 42,
+        }))
+        .unwrap();
+    assert_eq!(
+        output,
+        concat!(
+            "<html><head><title>Draft 151cm - Site</title></head>\n",
+            "<body><article>42 USD</article></body></html>\n",
+        )
+    );
+}
+
+#[test]
+pub fn list_added_partials() {
+    let root = std::env::temp_dir().join(format!("liquid-names-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("_header.liquid"), "Header").unwrap();
+
+    let mut source = FileSystemSource::new(&root);
+    source.add_pattern("_{}.liquid");
+    assert_eq!(source.names(), ["header"]);
+    assert_eq!(source.names(), ["header"]);
+
+    std::fs::write(root.join("_footer.liquid"), "Footer").unwrap();
+    assert_eq!(source.names(), ["footer", "header"]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "extra")]
+
+use liquid::partials::{EagerCompiler, InMemorySource};
+use liquid::*;
+
+fn render(template: &str) -> Result<String, Error> {
+    let mut source = InMemorySource::new();
+    source.add(
+        "base",
+        "<title>{% block title %}Site{% endblock %}</title>{% block body %}{% endblock %}",
+    );
+    source.add(
+        "page",
+        "{% extends 'base' %}{% block body %}<main>{% block main %}{% endblock %}</main>{% endblock %}",
+    );
+    source.add("loop", "{% layout 'loop' %}");
+    ParserBuilder::with_stdlib()
+        .layouts()
+        .partials(EagerCompiler::new(source))
+        .build()
+        .unwrap()
+        .parse(template)
+        .unwrap()
+        .render(&Object::new())
+}
+
+#[test]
+fn layout_fills_in_blocks() {
+    let output =
+        render("{% layout 'base' %}ignored{% block body %}<p>Hi</p>{% endblock %}ignored").unwrap();
+    assert_eq!(output, "<title>Site</title><p>Hi</p>");
+}
+
+#[test]
+fn layout_can_be_conditional() {
+    let output =
+        render("{% if true %}{% layout 'base' %}{% endif %}{% block title %}Home{% endblock %}")
+            .unwrap();
+    assert_eq!(output, "<title>Home</title>");
+}
+
+#[test]
+fn layouts_nest() {
+    let output = render(concat!(
+        "{% layout 'page' %}",
+        "{% block title %}{{ block.super }} - Post{% endblock %}",
+        "{% block main %}Text{% endblock %}"
+    ))
+    .unwrap();
+    assert_eq!(output, "<title>Site - Post</title><main>Text</main>");
+}
+
+#[test]
+fn layout_keeps_captures() {
+    let output = render(concat!(
+        "{% layout 'base' %}",
+        "{% capture title %}Post{% endcapture %}",
+        "{% block title %}{{ title }}{% endblock %}"
+    ))
+    .unwrap();
+    assert_eq!(output, "<title>Post</title>");
+}
+
+#[test]
+fn layout_requires_partial() {
+    render("{% layout 'missing' %}").unwrap_err();
+}
+
+#[test]
+fn layout_loop_is_stopped() {
+    render("{% layout 'loop' %}").unwrap_err();
+}
+
+#[test]
+fn layout_renders_when_streamed() {
+    let template = ParserBuilder::with_stdlib()
+        .layouts()
+        .partials(EagerCompiler::new({
+            let mut source = InMemorySource::new();
+            source.add("base", "<body>{% block body %}{% endblock %}</body>");
+            source
+        }))
+        .build()
+        .unwrap()
+        .parse("{% layout 'base' %}ignored{% block body %}Hi{% endblock %}")
+        .unwrap();
+    let mut chunks = Vec::new();
+    template
+        .render_builder()
+        .render_chunks(&Object::new(), |chunk| {
+            chunks.push(String::from_utf8(chunk.to_vec()).unwrap());
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(chunks.concat(), "<body>Hi</body>");
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "extra")]
+
+use liquid::partials::{EagerCompiler, InMemorySource};
+use liquid::*;
+
+#[test]
+fn macros_are_imported_from_partials() {
+    let mut source = InMemorySource::new();
+    source.add(
+        "badges",
+        concat!(
+            "{% macro price_badge(price, label: 'Sale') %}",
+            "<span class=\"badge\">{{ label }}: {{ price | prepend: '$' }}</span>",
+            "{% endmacro %}",
+        ),
+    );
+    source.add(
+        "product",
+        "{% import 'badges' %}{% call price_badge(product.price, label: 'New') %}",
+    );
+    let template = ParserBuilder::with_stdlib()
+        .macros()
+        .partials(EagerCompiler::new(source))
+        .build()
+        .unwrap()
+        .parse(concat!(
+            "{% import 'badges' %}",
+            "{% for product in products %}{% call price_badge(product.price) %}{% endfor %}",
+            "|{% render 'product', product: products[0] %}",
+        ))
+        .unwrap();
+    let globals = object!({
+        "products": [{"price": 5}, {"price": 12.5}],
+    });
+    assert_eq!(
+        template.render(&globals).unwrap(),
+        concat!(
+            "<span class=\"badge\">Sale: $5</span><span class=\"badge\">Sale: $12.5</span>",
+            "|<span class=\"badge\">New: $5</span>",
+        )
+    );
+}
@@ -0,0 +1,285 @@
+#![cfg(feature = "async")]
+
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+
+use liquid::model::{LazyValue, Value};
+use liquid_core::runtime::{AsyncWrite, RenderFuture};
+use liquid_core::{Language, ParseTag, Renderable, Result, Runtime, TagReflection, TagTokenIter};
+
+/// A future that is only ready once opened, by another task of the same thread.
+#[derive(Clone, Default)]
+struct Gate {
+    state: Arc<Mutex<(bool, Option<Waker>)>>,
+}
+
+impl Gate {
+    fn open(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.0 = true;
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    }
+
+    async fn wait(self) {
+        std::future::poll_fn(|cx| {
+            let mut state = self.state.lock().unwrap();
+            if state.0 {
+                Poll::Ready(())
+            } else {
+                state.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await;
+    }
+}
+
+/// Run `render` while opening `gate`, which never happens if rendering blocks the thread.
+fn render_opening<T>(gate: &Gate, render: impl Future<Output = T>) -> T {
+    let mut render = pin!(render);
+    let mut opened = false;
+    futures_executor::block_on(std::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = render.as_mut().poll(cx) {
+            return Poll::Ready(output);
+        }
+        if !opened {
+            opened = true;
+            gate.open();
+        }
+        Poll::Pending
+    }))
+}
+
+fn parser() -> liquid::Parser {
+    liquid::ParserBuilder::with_stdlib()
+        .tag(WaitTag)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_matches_render() {
+    let template = parser()
+        .parse(concat!(
+            "{% assign total = 0 %}",
+            "{% for item in items limit:3 %}",
+            "{% if item.price > 1 %}{{ item.name | upcase }}{% else %}cheap{% endif %},",
+            "{% case forloop.index %}{% when 1 %}first {% else %}{{ forloop.index }} {% endcase %}",
+            "{% endfor %}",
+            "{% capture summary %}{{ items | size }} items{% endcapture %}{{ summary }}",
+        ))
+        .unwrap();
+    let globals = liquid::object!({
+        "items": [
+            {"name": "shirt", "price": 5},
+            {"name": "sock", "price": 1},
+            {"name": "hat", "price": 3},
+            {"name": "scarf", "price": 2},
+        ],
+    });
+
+    let expected = template.render(&globals).unwrap();
+    let actual = futures_executor::block_on(template.render_async(&globals)).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_awaits_lazy_values() {
+    let gate = Gate::default();
+    let waiting = gate.clone();
+    let stock = LazyValue::from_future(move || {
+        let waiting = waiting.clone();
+        async move {
+            waiting.wait().await;
+            liquid::object!({"count": 3})
+        }
+    });
+    let mut globals = liquid::Object::new();
+    globals.insert("stock".into(), Value::Lazy(stock.clone()));
+
+    let template = parser()
+        .parse("{% if stock.count > 0 %}{{ stock.count }} left{% endif %}")
+        .unwrap();
+    let output = render_opening(&gate, template.render_async(&globals)).unwrap();
+    assert_eq!(output, "3 left");
+    // The render awaited a copy of its own.
+    assert!(!stock.is_computed());
+}
+
+#[test]
+fn test_awaits_lazy_values_in_loops() {
+    let gate = Gate::default();
+    let waiting = gate.clone();
+    let mut globals = liquid::Object::new();
+    globals.insert(
+        "items".into(),
+        Value::array([
+            Value::Lazy(LazyValue::from_future(move || {
+                let waiting = waiting.clone();
+                async move {
+                    waiting.wait().await;
+                    "ready"
+                }
+            })),
+            Value::scalar("now"),
+        ]),
+    );
+
+    let template = parser()
+        .parse("{% for item in items %}{{ item }} {% endfor %}")
+        .unwrap();
+    let output = render_opening(&gate, template.render_async(&globals)).unwrap();
+    assert_eq!(output, "ready now ");
+}
+
+#[test]
+fn test_skips_unread_lazy_values() {
+    let unread = LazyValue::from_future(|| async { "unread" });
+    let mut globals = liquid::Object::new();
+    globals.insert("show".into(), Value::scalar(false));
+    globals.insert("unread".into(), Value::Lazy(unread.clone()));
+
+    let template = parser()
+        .parse("{% if show %}{{ unread }}{% endif %}")
+        .unwrap();
+    let output = futures_executor::block_on(template.render_async(&globals)).unwrap();
+    assert_eq!(output, "");
+    assert!(!unread.is_computed());
+}
+
+#[test]
+fn test_awaits_tags() {
+    let gate = Gate::default();
+    let mut globals = liquid::Object::new();
+    globals.insert("gate".into(), Value::Lazy(LazyValue::new(|| "open")));
+    GATE.with(|current| *current.lock().unwrap() = Some(gate.clone()));
+
+    let template = parser().parse("before {% wait %} after").unwrap();
+    let mut output = Vec::new();
+    render_opening(&gate, template.render_to_async(&mut output, &globals)).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "before waited after");
+}
+
+#[test]
+#[cfg(feature = "extra")]
+fn test_renders_layouts() {
+    let mut source = liquid::partials::InMemorySource::new();
+    source.add(
+        "base",
+        "<h1>{% block title %}{% endblock %}</h1>{% block body %}{% endblock %}",
+    );
+    let parser = liquid::ParserBuilder::with_stdlib()
+        .layouts()
+        .partials(liquid::partials::EagerCompiler::new(source))
+        .build()
+        .unwrap();
+    let template = parser
+        .parse("{% layout 'base' %}{% block title %}{{ title }}{% endblock %}{% block body %}Text{% endblock %}")
+        .unwrap();
+    let mut globals = liquid::Object::new();
+    globals.insert(
+        "title".into(),
+        Value::Lazy(LazyValue::from_future(|| async { "Title" })),
+    );
+
+    let output = futures_executor::block_on(template.render_async(&globals)).unwrap();
+    assert_eq!(output, "<h1>Title</h1>Text");
+}
+
+#[test]
+fn test_awaits_lazy_values_in_partials() {
+    let gate = Gate::default();
+    let waiting = gate.clone();
+    let mut globals = liquid::Object::new();
+    globals.insert(
+        "stock".into(),
+        Value::Lazy(LazyValue::from_future(move || {
+            let waiting = waiting.clone();
+            async move {
+                waiting.wait().await;
+                3
+            }
+        })),
+    );
+
+    let mut source = liquid::partials::InMemorySource::new();
+    source.add("stock", "{{ stock }} left");
+    let parser = liquid::ParserBuilder::with_stdlib()
+        .partials(liquid::partials::EagerCompiler::new(source))
+        .build()
+        .unwrap();
+    let template = parser
+        .parse("{% include 'stock' %}, {% capture c %}{% render 'stock', stock: stock %}{% endcapture %}{{ c }}")
+        .unwrap();
+    let output = render_opening(&gate, template.render_async(&globals)).unwrap();
+    assert_eq!(output, "3 left, 3 left");
+}
+
+#[test]
+fn test_render_errors_on_lazy_futures() {
+    let mut globals = liquid::Object::new();
+    globals.insert(
+        "stock".into(),
+        Value::Lazy(LazyValue::from_future(|| async { 3 })),
+    );
+
+    let template = parser().parse("{{ stock }}").unwrap();
+    template.render(&globals).unwrap_err();
+}
+
+thread_local! {
+    static GATE: Mutex<Option<Gate>> = const { Mutex::new(None) };
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct WaitTag;
+
+impl TagReflection for WaitTag {
+    fn tag(&self) -> &'static str {
+        "wait"
+    }
+
+    fn description(&self) -> &'static str {
+        "Waits for the gate of the current test to open."
+    }
+}
+
+impl ParseTag for WaitTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        arguments.expect_nothing()?;
+        Ok(Box::new(Wait))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Wait;
+
+impl Renderable for Wait {
+    fn render_to(&self, _writer: &mut dyn std::io::Write, _runtime: &dyn Runtime) -> Result<()> {
+        liquid::Error::with_msg("Only rendered asynchronously").into_err()
+    }
+
+    fn render_to_async<'a>(
+        &'a self,
+        writer: &'a mut (dyn AsyncWrite + Unpin),
+        _runtime: &'a dyn Runtime,
+    ) -> RenderFuture<'a> {
+        Box::pin(async move {
+            let gate = GATE.with(|current| current.lock().unwrap().clone().unwrap());
+            gate.wait().await;
+            liquid_core::runtime::write_all_async(writer, b"waited").await
+        })
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::Write;
+use std::sync::{mpsc, Mutex};
+use std::time::Duration;
+
+use liquid::model::{LazyValue, Value};
+use liquid_core::error::ResultLiquidReplaceExt;
+use liquid_core::{Language, ParseTag, Renderable, Result, Runtime, TagReflection, TagTokenIter};
+
+fn parser() -> liquid::Parser {
+    liquid::ParserBuilder::with_stdlib()
+        .tag(FlushTag)
+        .build()
+        .unwrap()
+}
+
+fn chunks(template: &str, globals: &liquid::Object) -> Vec<String> {
+    let template = parser().parse(template).unwrap();
+    let mut chunks = Vec::new();
+    template
+        .render_builder()
+        .render_chunks(globals, |chunk| {
+            chunks.push(String::from_utf8(chunk.to_vec()).unwrap());
+            Ok(())
+        })
+        .unwrap();
+    chunks
+}
+
+#[test]
+fn test_chunks_follow_top_level_elements() {
+    let globals = liquid::object!({"x": "X"});
+    assert_eq!(
+        chunks(
+            "a{{ x }}{% for i in (1..3) %}{{ i }}{% endfor %}{% assign y = 1 %}b",
+            &globals
+        ),
+        vec!["a", "X", "123", "b"]
+    );
+}
+
+#[test]
+fn test_chunks_match_render() {
+    let template = "{% capture c %}{{ x }}{% endcapture %}{% if x %}{{ c }}!{% endif %}\n";
+    let globals = liquid::object!({"x": "X"});
+    let rendered = parser().parse(template).unwrap().render(&globals).unwrap();
+    assert_eq!(chunks(template, &globals).concat(), rendered);
+}
+
+#[test]
+fn test_tags_flush() {
+    let globals = liquid::Object::new();
+    assert_eq!(
+        chunks(
+            "{% for i in (1..3) %}{{ i }}{% flush %}{% endfor %}done",
+            &globals
+        ),
+        vec!["1", "2", "3", "done"]
+    );
+}
+
+#[test]
+fn test_flush_in_capture_is_ignored() {
+    let globals = liquid::Object::new();
+    assert_eq!(
+        chunks(
+            "{% capture c %}a{% flush %}b{% endcapture %}{{ c }}",
+            &globals
+        ),
+        vec!["ab"]
+    );
+}
+
+#[test]
+fn test_chunks_are_sent_while_rendering() {
+    // The second section waits for the first to be received, which can't happen if the output
+    // was only sent at the end.
+    let (received, wait_received) = mpsc::channel::<()>();
+    let wait_received = Mutex::new(wait_received);
+    let mut globals = liquid::Object::new();
+    globals.insert(
+        "later".into(),
+        Value::Lazy(LazyValue::new(move || {
+            wait_received
+                .lock()
+                .unwrap()
+                .recv_timeout(Duration::from_secs(10))
+                .expect("first chunk to be received");
+            "later"
+        })),
+    );
+    let template = parser().parse("first {{ later }}").unwrap();
+
+    let (sender, receiver) = mpsc::sync_channel(0);
+    std::thread::scope(|scope| {
+        let render = scope.spawn(|| {
+            template.render_builder().render_chunks(&globals, |chunk| {
+                sender
+                    .send(chunk.to_vec())
+                    .map_err(|_| liquid::Error::with_msg("Disconnected"))
+            })
+        });
+        assert_eq!(receiver.recv().unwrap(), b"first ");
+        received.send(()).unwrap();
+        assert_eq!(receiver.recv().unwrap(), b"later");
+        render.join().unwrap().unwrap();
+    });
+}
+
+#[test]
+fn test_refused_chunk_stops_render() {
+    let template = parser().parse("a{% flush %}b{{ missing }}").unwrap();
+    let mut chunks = 0;
+    let error = template
+        .render_builder()
+        .render_chunks(&liquid::Object::new(), |_| {
+            chunks += 1;
+            liquid::Error::with_msg("Client went away").into_err()
+        })
+        .unwrap_err();
+    assert_eq!(chunks, 1);
+    assert!(error.to_string().contains("Client went away"), "{error}");
+}
+
+#[test]
+fn test_stream_to_flushes_writer() {
+    #[derive(Default)]
+    struct Flushes {
+        data: Vec<u8>,
+        flushed: Vec<usize>,
+    }
+
+    impl Write for Flushes {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.data.write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.flushed.push(self.data.len());
+            Ok(())
+        }
+    }
+
+    let template = parser().parse("ab{{ 1 }}cd").unwrap();
+    let mut writer = Flushes::default();
+    template
+        .render_builder()
+        .stream(true)
+        .render_to(&mut writer, &liquid::Object::new())
+        .unwrap();
+    assert_eq!(writer.data, b"ab1cd");
+    assert_eq!(writer.flushed, vec![2, 3, 5]);
+}
+
+/// Passes on the output so far.
+#[derive(Copy, Clone, Debug, Default)]
+struct FlushTag;
+
+impl TagReflection for FlushTag {
+    fn tag(&self) -> &'static str {
+        "flush"
+    }
+
+    fn description(&self) -> &'static str {
+        "Passes on the output rendered so far."
+    }
+}
+
+impl ParseTag for FlushTag {
+    fn parse(
+        &self,
+        mut arguments: TagTokenIter<'_>,
+        _options: &Language,
+    ) -> Result<Box<dyn Renderable>> {
+        arguments.expect_nothing()?;
+        Ok(Box::new(Flush))
+    }
+
+    fn reflection(&self) -> &dyn TagReflection {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Flush;
+
+impl Renderable for Flush {
+    fn render_to(&self, writer: &mut dyn Write, _runtime: &dyn Runtime) -> Result<()> {
+        writer.flush().replace("Failed to render")
+    }
+}
@@ -68,3 +68,157 @@ topic1-bar\
 ",
     );
 }
+
+#[test]
+pub fn string_escapes() {
+    let template = ParserBuilder::with_stdlib()
+        .string_escapes(true)
+        .build()
+        .unwrap()
+        .parse(
+            r#"{% assign lines = "a\nb" | split: "\n" %}{{ lines | join: "\t" }}{% if lines[0] == 'a' %}\'{% endif %}"#,
+        )
+        .unwrap();
+
+    let output = template.render(&Object::default()).unwrap();
+
+    assert_data_eq!(output, "a\tb\\'".raw());
+}
+
+#[test]
+pub fn string_escapes_with_leading_whitespace() {
+    let parser = ParserBuilder::with_stdlib()
+        .string_escapes(true)
+        .build()
+        .unwrap();
+    let render = |input: &str| {
+        parser
+            .parse(input)
+            .unwrap()
+            .render(&Object::default())
+            .unwrap()
+    };
+
+    assert_eq!(render(" hello"), " hello");
+    assert_eq!(render("\n<p>{{ 'x\ty' }}</p>"), "\n<p>x\ty</p>");
+    assert_eq!(render("{% liquid echo 1 %}"), "1");
+    assert_eq!(render("{% liquid\n  echo 'a\tb'\n%}"), "a\tb");
+}
+
+#[test]
+pub fn collection_literals() {
+    let parser = ParserBuilder::with_stdlib().build().unwrap();
+    let render = |input: &str| {
+        parser
+            .parse(input)
+            .unwrap()
+            .render(&object!({ "name": "c,d" }))
+            .unwrap()
+    };
+
+    assert_eq!(
+        render(r#"{% assign letters = ["a", 'b', name, [], ] %}{{ letters | join: "|" }}"#),
+        "a|b|c,d|"
+    );
+    assert_eq!(
+        render(r#"{{ ["b", "a"] | concat: [ name ] | sort | join }}"#),
+        "a b c,d"
+    );
+    assert_eq!(render("{% for i in [3, 2, 1] %}{{ i }}{% endfor %}"), "321");
+    assert_eq!(
+        render(r#"{% case [1, name] %}{% when [1, "c,d"] %}yes{% else %}no{% endcase %}"#),
+        "yes"
+    );
+    assert_eq!(
+        render(
+            r#"{% assign card = {"title": name, size: {"width": 2}} %}{{ card.title }} {{ card.size.width }}"#
+        ),
+        "c,d 2"
+    );
+    assert_eq!(render("{{ {} | size }}{{ [] | size }}"), "00");
+}
+
+#[test]
+pub fn conditions() {
+    let parser = ParserBuilder::with_stdlib().build().unwrap();
+    let render = |input: &str| {
+        parser
+            .parse(input)
+            .unwrap()
+            .render(&object!({ "price": 3, "compare_at": 5, "tags": ["sale"] }))
+            .unwrap()
+    };
+
+    assert_eq!(
+        render("{% assign is_sale = price < compare_at %}{{ is_sale }}"),
+        "true"
+    );
+    assert_eq!(render("{{ price == 3 }} {{ price <> 3 }}"), "true false");
+    assert_eq!(
+        render("{{ false or true and false }} {{ price > 1 and tags contains 'sale' }}"),
+        "false true"
+    );
+    assert_eq!(
+        render("{% liquid\necho price >= 3 and compare_at %}"),
+        "true"
+    );
+    assert_eq!(render("{{ nil | default: price > compare_at }}"), "false");
+    assert_eq!(render("{{ price | plus: 1 }}"), "4");
+}
+
+#[test]
+pub fn conditions_agree_with_if() {
+    for grouped in [false, true] {
+        let parser = ParserBuilder::with_stdlib()
+            .grouped_conditions(grouped)
+            .build()
+            .unwrap();
+        for condition in [
+            "false and true or true",
+            "true and false and false or true",
+            "true or false and false",
+            "false or true and false or true",
+            "1 == 1 and 2 == 1 or 3 == 3",
+        ] {
+            let text = format!(
+                "{{% if {condition} %}}true{{% else %}}false{{% endif %}} {{{{ {condition} }}}} \
+                 {{% assign x = {condition} %}}{{{{ x }}}} {{% echo {condition} %}}"
+            );
+            let output = parser.parse(&text).unwrap().render(&object!({})).unwrap();
+            let mut results = output.split(' ');
+            let expected = results.next().unwrap();
+            assert!(
+                results.all(|result| result == expected),
+                "`{condition}` with grouped_conditions({grouped}): {output}"
+            );
+        }
+    }
+}
+
+#[test]
+pub fn grouped_conditions_in_outputs() {
+    let parser = ParserBuilder::with_stdlib()
+        .grouped_conditions(true)
+        .build()
+        .unwrap();
+    let render = |input: &str| {
+        parser
+            .parse(input)
+            .unwrap()
+            .render(&object!({ "price": 3, "tags": ["sale"] }))
+            .unwrap()
+    };
+
+    assert_eq!(
+        render("{{ false and true or true }} {{ (false or true) and false }}"),
+        "true false"
+    );
+    assert_eq!(
+        render("{{ not missing }} {{ not (price > 1 and tags contains 'sale') }}"),
+        "true false"
+    );
+
+    let parser = ParserBuilder::with_stdlib().build().unwrap();
+    assert!(parser.parse("{{ not missing }}").is_err());
+    assert!(parser.parse("{{ (price > 1) and true }}").is_err());
+}